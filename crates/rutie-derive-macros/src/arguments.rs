@@ -0,0 +1,184 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{punctuated::Punctuated, token::Colon2, *};
+
+/// A typed input of a Ruby method or a Ruby function.
+pub struct Argument {
+    name: Ident,
+    ty: Punctuated<PathSegment, Colon2>,
+    optional: bool,
+}
+
+impl Argument {
+    /// Reads a typed input. It must be of the form `&T` or
+    /// `Option<&T>`. The latter represents an optional argument: it
+    /// is `None` when the argument is missing or `nil`.
+    pub fn from_typed_input(pat: &Pat, ty: &Type, callable_description: &str) -> Self {
+        let name = match pat {
+            Pat::Ident(ident) => ident.ident.clone(),
+            _ => panic!(
+                "Typed input has an unsupported form ({}), it must be an identifier",
+                callable_description
+            ),
+        };
+
+        match ty {
+            Type::Reference(TypeReference { elem, .. }) => Self {
+                name,
+                ty: reference_path(elem, callable_description),
+                optional: false,
+            },
+
+            Type::Path(TypePath {
+                qself: None,
+                path: Path { segments, .. },
+            }) if segments.len() == 1 && segments[0].ident == "Option" => {
+                match &segments[0].arguments {
+                    PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                        args, ..
+                    }) if args.len() == 1 => match &args[0] {
+                        GenericArgument::Type(Type::Reference(TypeReference { elem, .. })) => {
+                            Self {
+                                name,
+                                ty: reference_path(elem, callable_description),
+                                optional: true,
+                            }
+                        }
+                        _ => panic!(
+                            "Typed input has an unsupported form ({}), it must be of the form `Option<&T>`",
+                            callable_description
+                        ),
+                    },
+                    _ => panic!(
+                        "Typed input has an unsupported form ({}), it must be of the form `Option<&T>`",
+                        callable_description
+                    ),
+                }
+            }
+
+            _ => panic!(
+                "Typed input has an unsupported form ({}), it must be a reference type",
+                callable_description
+            ),
+        }
+    }
+}
+
+fn reference_path(elem: &Type, callable_description: &str) -> Punctuated<PathSegment, Colon2> {
+    match elem {
+        Type::Path(TypePath {
+            qself: None,
+            path: Path { segments: ty, .. },
+        }) => ty.clone(),
+        _ => panic!(
+            "Typed input has an unsupported form ({})",
+            callable_description
+        ),
+    }
+}
+
+/// Generates the code that reads the arguments from `argc` and
+/// `argv`, converts them to their Ruby types, and upcasts them to
+/// their Rust types.
+/// Generates the code reading the `arguments` of a Ruby method or
+/// function. When an argument is missing, the error names the
+/// callable after its Ruby name, e.g. `Store.threads?`, which is
+/// only known when it's called.
+pub fn parse_arguments(
+    arguments: &[Argument],
+    callable_kind: &str,
+    callable_owner: &str,
+    rust_name: &str,
+) -> TokenStream {
+    if arguments.is_empty() {
+        return quote! {};
+    }
+
+    let ruby_input_names = arguments
+        .iter()
+        .map(|argument| &argument.name)
+        .collect::<Vec<_>>();
+
+    let ruby_input_readers = arguments.iter().map(|argument| {
+        let ty = &argument.ty;
+
+        if argument.optional {
+            quote! {
+                arguments
+                    .get(argument_nth)
+                    .filter(|argument| !<rutie::AnyObject as rutie::Object>::is_nil(argument))
+                    .map(|argument| {
+                        <rutie::AnyObject as rutie::Object>
+                            ::try_convert_to::<< #ty as rutie_derive::ClassInfo>::RubyClass>(argument)
+                    })
+                    .transpose()
+                    .unwrap_or_else(|error| {
+                        rutie::VM::raise_ex(error);
+                        unreachable!()
+                    })
+            }
+        } else {
+            quote! {
+                arguments
+                    .get(argument_nth)
+                    .ok_or_else(|| {
+                        <rutie::AnyException as rutie::Exception>::new(
+                            "ArgumentError",
+                            Some(&format!(concat!("Argument #{} (`", stringify!( #ty ), "`) of ", #callable_kind, " `", #callable_owner, "{}` is missing"), argument_nth, rutie_derive::ruby_name(#rust_name))),
+                        )
+                    })
+                    .and_then(|argument| {
+                        <rutie::AnyObject as rutie::Object>
+                            ::try_convert_to::<< #ty as rutie_derive::ClassInfo>::RubyClass>(argument)
+                    })
+                    .unwrap_or_else(|error| {
+                        rutie::VM::raise_ex(error);
+                        unreachable!()
+                    })
+            }
+        }
+    });
+
+    let ruby_input_upcasts = arguments.iter().map(|argument| {
+        let name = &argument.name;
+        let ty = &argument.ty;
+        let upcast = quote! {
+            rutie_derive::UpcastRubyClass::<
+                <
+                    < #ty as rutie_derive::ClassInfo>::RubyClass as rutie_derive::ClassInfo
+                >::Class
+            >::upcast
+        };
+
+        if argument.optional {
+            quote! { #name.as_ref().map(|#name| #upcast(#name)) }
+        } else {
+            quote! { #upcast(&#name) }
+        }
+    });
+
+    quote! {
+        let ( #( #ruby_input_names ),* ) =
+            {
+                let arguments = rutie::util::parse_arguments(argc, argv);
+                let mut argument_nth = 0;
+
+                (
+                    #(
+                        {
+                            let argument = #ruby_input_readers;
+
+                            argument_nth += 1;
+
+                            argument
+                        }
+                    ),*
+                )
+            };
+
+        let ( #( #ruby_input_names ),* ) =
+            (
+                #( #ruby_input_upcasts ),*
+            );
+    }
+}
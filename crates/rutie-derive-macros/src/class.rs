@@ -18,6 +18,7 @@ pub fn entry(
     }
 
     let mut ruby_module = None;
+    let mut mark = false;
 
     for argument in arguments.iter() {
         match argument {
@@ -29,6 +30,10 @@ pub fn entry(
                 ruby_module = Some(value.value());
             }
 
+            NestedMeta::Meta(Meta::Path(name)) if name.is_ident("mark") => {
+                mark = true;
+            }
+
             argument => panic!(
                 "Unexpected argument `{:?}` from the `rubyclass` procedural macro",
                 argument
@@ -41,6 +46,7 @@ pub fn entry(
             &derive_input.ident,
             struct_data,
             &derive_input.generics,
+            mark,
             ruby_module.expect("The `module` argument of the `rubyclass` procedural macro is missing, e.g. `#[rubyclass(module = \"foo\")]`"),
         ),
 
@@ -58,6 +64,7 @@ fn derive_for_struct(
     struct_name: &Ident,
     _data: &DataStruct,
     generics: &Generics,
+    mark: bool,
     ruby_module: String,
 ) -> proc_macro2::TokenStream {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
@@ -74,6 +81,21 @@ fn derive_for_struct(
         span,
     );
 
+    // With the `mark` argument, the Ruby objects held by the Rust
+    // structure are marked by the garbage collector through
+    // `rutie_derive::Mark`.
+    let mark_function = if mark {
+        quote! {
+            , mark(data) {
+                use rutie_derive::Mark as _;
+
+                data.mark();
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     let mut ruby_module_parts = ruby_module.split("::");
     let ruby_module = ruby_module_parts.next().unwrap();
     let ruby_nested_modules = ruby_module_parts.collect::<Vec<_>>();
@@ -86,7 +108,7 @@ fn derive_for_struct(
             use super::*; // to get `lazy_static`, imported by the user.
             use rutie::{wrappable_struct};
 
-            wrappable_struct!(#struct_name, #wrapper_struct_name, #wrapper_const_name);
+            wrappable_struct!(#struct_name, #wrapper_struct_name, #wrapper_const_name #mark_function);
         }
 
         pub use #ruby_sub_module_hack::*;
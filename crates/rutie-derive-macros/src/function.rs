@@ -1,6 +1,6 @@
-use proc_macro2::Ident;
+use crate::arguments::{parse_arguments, Argument};
 use quote::quote;
-use syn::{punctuated::Punctuated, token::Colon2, *};
+use syn::*;
 
 pub fn entry(
     _attr: proc_macro::TokenStream,
@@ -43,87 +43,21 @@ pub fn entry(
     let ruby_function_visibility = &function.vis;
 
     let ruby_arguments_parsing = {
-        let (ruby_input_names, ruby_input_types): (
-            Vec<Ident>,
-            Vec<Punctuated<PathSegment, Colon2>>,
-        ) = function
+        let callable_description = format!("function `{}`", function_name);
+        let ruby_inputs = function
             .sig
             .inputs
             .iter()
-            .filter_map(|input| match input {
-                FnArg::Typed(PatType { pat, ty, .. }) => match (&**pat, &**ty) {
-                    (
-                        Pat::Ident(ident),
-                        Type::Reference(TypeReference { elem, .. }),
-                    ) => match &**elem {
-                        Type::Path(TypePath {
-                            qself: None,
-                            path: Path { segments: ty, .. },
-                        }) => Some((ident.ident.clone(), ty.clone())),
-                        _ => panic!(
-                            "Typed input has an unsupported form (function `{}`)",
-                            function_name
-                        ),
-                    },
-                    _ => panic!(
-                        "Typed input has an unsupported form (function `{}`), it must be a reference type",
-                        function_name
-                    ),
-                },
+            .map(|input| match input {
+                FnArg::Typed(PatType { pat, ty, .. }) => {
+                    Argument::from_typed_input(pat, ty, &callable_description)
+                }
 
                 FnArg::Receiver(..) => unreachable!(),
             })
-            .unzip();
-
-        if ruby_input_names.is_empty() {
-            quote! {}
-        } else {
-            quote! {
-                let ( #( #ruby_input_names ),* ) =
-                {
-                    let arguments = rutie::util::parse_arguments(argc, argv);
-                    let mut argument_nth = 0;
-
-                    (
-                        #(
-                            {
-                                let argument = arguments
-                                    .get(argument_nth)
-                                    .ok_or_else(|| {
-                                        <rutie::AnyException as rutie::Exception>::new(
-                                            "ArgumentError",
-                                            Some(&format!(concat!("Argument #{} (`", stringify!( #ruby_input_types ), "`) of function `", stringify!( #ruby_function_name ), "` is missing"), argument_nth)),
-                                        )
-                                    })
-                                    .and_then(|argument| {
-                                        <rutie::AnyObject as rutie::Object>
-                                            ::try_convert_to::<< #ruby_input_types as rutie_derive::ClassInfo>::RubyClass>(argument)
-                                    })
-                                    .unwrap_or_else(|error| {
-                                        rutie::VM::raise_ex(error);
-                                        unreachable!()
-                                    });
-
-                                argument_nth += 1;
-
-                                argument
-                            }
-                        ),*
-                    )
-                };
-
-                let ( #( #ruby_input_names ),* ) =
-                    (
-                        #(
-                            rutie_derive::UpcastRubyClass::<
-                                    <
-                                            < #ruby_input_types as rutie_derive::ClassInfo>::RubyClass as rutie_derive::ClassInfo
-                                            >::Class
-                                    >::upcast(&#ruby_input_names)
-                        ),*
-                    );
-            }
-        }
+            .collect::<Vec<_>>();
+
+        parse_arguments(&ruby_inputs, "function", "", &function_name)
     };
 
     let ruby_output = match function.sig.output {
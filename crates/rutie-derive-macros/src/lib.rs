@@ -1,3 +1,4 @@
+mod arguments;
 mod class;
 mod function;
 mod methods;
@@ -1,6 +1,7 @@
+use crate::arguments::{parse_arguments, Argument};
 use proc_macro2::{Group, Ident, Span, TokenStream, TokenTree};
 use quote::{quote, ToTokens};
-use syn::{punctuated::Punctuated, token::Colon2, *};
+use syn::*;
 
 pub fn entry(
     _attr: proc_macro::TokenStream,
@@ -72,10 +73,8 @@ pub fn entry(
                 let mut need_mut_self = false;
 
                 let ruby_arguments_parsing = {
-                    let (ruby_input_names, ruby_input_types): (
-                        Vec<Ident>,
-                        Vec<Punctuated<PathSegment, Colon2>>,
-                    ) = method
+                    let callable_description = format!("method `{}.{}`", ty_name, method_name);
+                    let ruby_inputs = method
                         .sig
                         .inputs
                         .iter()
@@ -86,77 +85,18 @@ pub fn entry(
 
                                 None
                             }
-                            FnArg::Typed(PatType { pat, ty, .. }) => match (&**pat, &**ty) {
-                                (
-                                    Pat::Ident(ident),
-                                    Type::Reference(TypeReference { elem, .. }),
-                                ) => match &**elem {
-                                    Type::Path(TypePath {
-                                        qself: None,
-                                        path: Path { segments: ty, .. },
-                                    }) => Some((ident.ident.clone(), ty.clone())),
-                                    _ => panic!(
-                                        "Typed input has an unsupported form (method `{}`)",
-                                        method_name
-                                    ),
-                                },
-                                _ => panic!(
-                                    "Typed input has an unsupported form (method `{}`), it must be a reference type",
-                                    method_name
-                                ),
-                            },
+                            FnArg::Typed(PatType { pat, ty, .. }) => {
+                                Some(Argument::from_typed_input(pat, ty, &callable_description))
+                            }
                         })
-                        .unzip();
-
-                    if ruby_input_names.is_empty() {
-                        quote! {}
-                    } else {
-                        quote! {
-                            let ( #( #ruby_input_names ),* ) =
-                                {
-                                    let arguments = rutie::util::parse_arguments(argc, argv);
-                                    let mut argument_nth = 0;
-
-                                    (
-                                        #(
-                                            {
-                                                let argument = arguments
-                                                    .get(argument_nth)
-                                                    .ok_or_else(|| {
-                                                        <rutie::AnyException as rutie::Exception>::new(
-                                                            "ArgumentError",
-                                                            Some(&format!(concat!("Argument #{} (`", stringify!( #ruby_input_types ), "`) of method `", stringify!( #ty_name ), ".", stringify!( #ruby_method_name ), "` is missing"), argument_nth)),
-                                                        )
-                                                    })
-                                                    .and_then(|argument| {
-                                                        <rutie::AnyObject as rutie::Object>
-                                                            ::try_convert_to::<< #ruby_input_types as rutie_derive::ClassInfo>::RubyClass>(argument)
-                                                    })
-                                                    .unwrap_or_else(|error| {
-                                                        rutie::VM::raise_ex(error);
-                                                        unreachable!()
-                                                    });
-
-                                                argument_nth += 1;
-
-                                                argument
-                                            }
-                                        ),*
-                                    )
-                                };
-
-                            let ( #( #ruby_input_names ),* ) =
-                                (
-                                    #(
-                                        rutie_derive::UpcastRubyClass::<
-                                            <
-                                                < #ruby_input_types as rutie_derive::ClassInfo>::RubyClass as rutie_derive::ClassInfo
-                                            >::Class
-                                        >::upcast(&#ruby_input_names)
-                                    ),*
-                                );
-                        }
-                    }
+                        .collect::<Vec<_>>();
+
+                    parse_arguments(
+                        &ruby_inputs,
+                        "method",
+                        &format!("{}.", ty_name),
+                        &method_name,
+                    )
                 };
 
                 let ruby_input_receiver = if need_mut_self {
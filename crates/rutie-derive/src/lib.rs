@@ -1,4 +1,6 @@
+mod name;
 mod upcast;
 
+pub use name::*;
 pub use rutie_derive_macros::{rubyclass, rubyfunction, rubymethods};
 pub use upcast::*;
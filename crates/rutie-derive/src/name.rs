@@ -0,0 +1,27 @@
+use std::{ffi::CStr, os::raw::c_char};
+
+extern "C" {
+    // `ID` is an `uintptr_t`.
+    fn rb_frame_this_func() -> usize;
+    fn rb_id2name(id: usize) -> *const c_char;
+}
+
+/// Returns the Ruby name of the method or the function being called,
+/// e.g. `threads?`, which can differ from its Rust name, e.g.
+/// `threads`. The Rust name is returned if the Ruby one is unknown.
+pub fn ruby_name(rust_name: &str) -> String {
+    let name = unsafe {
+        match rb_frame_this_func() {
+            0 => return rust_name.to_string(),
+            id => rb_id2name(id),
+        }
+    };
+
+    if name.is_null() {
+        return rust_name.to_string();
+    }
+
+    unsafe { CStr::from_ptr(name) }
+        .to_string_lossy()
+        .into_owned()
+}
@@ -3,6 +3,13 @@ pub trait ClassInfo {
     type RubyClass;
 }
 
+/// Marks the Ruby objects held by a Rust structure when the garbage
+/// collector marks its Ruby object, see `#[rubyclass(module = "…",
+/// mark)]`.
+pub trait Mark {
+    fn mark(&self);
+}
+
 pub trait UpcastRubyClass<T> {
     fn upcast(&self) -> &T;
 
@@ -0,0 +1,315 @@
+//! An on-disk cache of compiled modules.
+
+use crate::{
+    error::{to_compile_err, to_ruby_err, ArgumentError, IOError, RuntimeError},
+    module::{artifact, Module},
+    options,
+    prelude::*,
+    store::Store,
+    wat,
+};
+use rutie::{AnyException, AnyObject, Hash, Integer, NilClass, Object, RString, Symbol};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The extension of the cache entries.
+const EXTENSION: &str = "wasmer-module";
+
+/// The extension of the temporary files written before being renamed
+/// to entries.
+const TEMPORARY_EXTENSION: &str = "tmp";
+
+/// The age after which a temporary file is considered orphaned, e.g.
+/// left by a process that crashed while writing an entry.
+const ORPHAN_AGE: Duration = Duration::from_secs(60 * 60);
+
+#[rubyclass(module = "Wasmer::Cache")]
+pub struct FileSystem {
+    directory: PathBuf,
+    max_size: Option<u64>,
+    key: Option<Vec<u8>>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl FileSystem {
+    /// Computes the key of a module, from its bytes and from
+    /// everything the compiled code depends on.
+    fn key(store: &Store, bytes: &[u8]) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(
+            format!(
+                "wasmer-ruby {} wasmer {} {}\0",
+                env!("CARGO_PKG_VERSION"),
+                wasmer::VERSION,
+                store.engine_descriptor()
+            )
+            .as_bytes(),
+        );
+        hasher.update(bytes);
+
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// Lists the entries of the cache, with their sizes and
+    /// modification times. Entries that disappear in the meantime,
+    /// e.g. evicted by another process, are ignored.
+    fn entries(&self) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let mut entries = Vec::new();
+
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|extension| extension.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+
+            if let Ok(metadata) = fs::metadata(&path) {
+                let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+
+                entries.push((path, metadata.len(), modified));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Writes an entry atomically: the bytes are written into a
+    /// temporary file which is then renamed, so that another process
+    /// never reads a partial entry.
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        let nanoseconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or(0);
+        let temporary_path = self.directory.join(format!(
+            ".{}.{}.{}.{}",
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(""),
+            process::id(),
+            nanoseconds,
+            TEMPORARY_EXTENSION
+        ));
+
+        fs::write(&temporary_path, bytes)
+            .and_then(|_| fs::rename(&temporary_path, path))
+            .map_err(|error| {
+                let _ = fs::remove_file(&temporary_path);
+
+                error
+            })
+    }
+
+    /// Removes the temporary files that have been left behind, e.g. by
+    /// a process that crashed while writing an entry. The recent ones
+    /// may still be written by another process, and are kept.
+    fn remove_orphans(&self) -> io::Result<()> {
+        let now = SystemTime::now();
+
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|extension| extension.to_str())
+                != Some(TEMPORARY_EXTENSION)
+            {
+                continue;
+            }
+
+            let is_orphan = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .map(|modified| now.duration_since(modified).unwrap_or_default() > ORPHAN_AGE)
+                .unwrap_or(false);
+
+            if is_orphan {
+                match fs::remove_file(&path) {
+                    Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                    _ => (),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Marks the entry at `path` as recently used, so that the
+    /// eviction removes the least recently used entries first. It's
+    /// best-effort, e.g. the cache directory may be read-only.
+    fn touch(path: &Path) {
+        let _ = fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+    }
+
+    /// Removes the least recently used entries until the cache fits
+    /// in `max_size`. The entry at `keep`, which has just been
+    /// written, is never removed.
+    fn evict(&mut self, keep: &Path) -> io::Result<()> {
+        let max_size = match self.max_size {
+            Some(max_size) => max_size,
+            None => return Ok(()),
+        };
+
+        let mut entries = self.entries()?;
+        let mut size = entries.iter().map(|(_, size, _)| size).sum::<u64>();
+
+        entries.sort_by_key(|(_, _, modified)| *modified);
+
+        for (path, entry_size, _) in entries {
+            if size <= max_size {
+                break;
+            }
+
+            if path == keep {
+                continue;
+            }
+
+            match fs::remove_file(&path) {
+                Ok(()) => self.evictions += 1,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+                Err(error) => return Err(error),
+            }
+
+            size -= entry_size;
+        }
+
+        Ok(())
+    }
+}
+
+fn to_io_err(error: io::Error) -> AnyException {
+    to_ruby_err::<IOError, _>(error)
+}
+
+#[rubymethods]
+impl FileSystem {
+    pub fn new(directory: &RString, options: Option<&Hash>) -> RubyResult<AnyObject> {
+        options::check_keys(options, &["max_size", "key"])?;
+
+        let max_size = options::get_integer(options, "max_size")?
+            .map(|max_size| {
+                if max_size < 0 {
+                    Err(to_ruby_err::<ArgumentError, _>(
+                        "The maximum size of the cache must be positive",
+                    ))
+                } else {
+                    Ok(max_size as u64)
+                }
+            })
+            .transpose()?;
+
+        let key = options::get_bytes(options, "key")?;
+        let directory = PathBuf::from(directory.to_str());
+
+        fs::create_dir_all(&directory).map_err(|error| {
+            to_ruby_err::<IOError, _>(format!(
+                "Failed to create the cache directory `{}`: {}",
+                directory.display(),
+                error
+            ))
+        })?;
+
+        let cache = FileSystem {
+            directory,
+            max_size,
+            key,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        };
+
+        cache.remove_orphans().map_err(to_io_err)?;
+
+        Ok(FileSystem::ruby_new(cache))
+    }
+
+    pub fn load_or_compile(
+        &mut self,
+        store: &Store,
+        bytes: &RString,
+        options: Option<&Hash>,
+    ) -> RubyResult<AnyObject> {
+        options::check_keys(options, &["keep_bytes"])?;
+
+        let keep_bytes = options::get_bool(options, "keep_bytes")?.unwrap_or(false);
+        let bytes = wat::to_binary(bytes.to_bytes_unchecked(), None)?.into_owned();
+        let path = self
+            .directory
+            .join(format!("{}.{}", Self::key(store, &bytes), EXTENSION));
+
+        if let Ok(serialized_module) = fs::read(&path) {
+            if let Ok(module) =
+                artifact::deserialize(store, &serialized_module, self.key.as_deref())
+            {
+                self.hits += 1;
+                Self::touch(&path);
+
+                return Ok(Module::ruby_new(Module::raw_new(
+                    module, store, bytes, keep_bytes,
+                )));
+            }
+
+            // An entry that cannot be deserialized, e.g. written by
+            // another version of Wasmer, signed with another key or
+            // corrupted, is replaced.
+            let _ = fs::remove_file(&path);
+        }
+
+        self.misses += 1;
+
+        let module = wasmer::Module::new(store.inner(), &bytes)
+            .map_err(|error| to_compile_err(&error, &error))?;
+        let serialized_module = artifact::serialize(store, &module, self.key.as_deref())?;
+
+        self.write(&path, &serialized_module).map_err(to_io_err)?;
+        self.evict(&path).map_err(to_io_err)?;
+
+        Ok(Module::ruby_new(Module::raw_new(
+            module, store, bytes, keep_bytes,
+        )))
+    }
+
+    pub fn directory(&self) -> RubyResult<RString> {
+        Ok(RString::new_utf8(&self.directory.to_string_lossy()))
+    }
+
+    pub fn size(&self) -> RubyResult<Integer> {
+        Ok(Integer::from(
+            self.entries()
+                .map_err(to_io_err)?
+                .iter()
+                .map(|(_, size, _)| size)
+                .sum::<u64>(),
+        ))
+    }
+
+    pub fn stats(&self) -> RubyResult<Hash> {
+        let mut stats = Hash::new();
+        stats.store(Symbol::new("hits"), Integer::from(self.hits));
+        stats.store(Symbol::new("misses"), Integer::from(self.misses));
+        stats.store(Symbol::new("evictions"), Integer::from(self.evictions));
+
+        Ok(stats)
+    }
+
+    pub fn clear(&self) -> RubyResult<NilClass> {
+        for (path, _, _) in self.entries().map_err(to_io_err)? {
+            match fs::remove_file(&path) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                    return Err(to_io_err(error))
+                }
+                _ => (),
+            }
+        }
+
+        self.remove_orphans().map_err(to_io_err)?;
+
+        Ok(NilClass::new())
+    }
+}
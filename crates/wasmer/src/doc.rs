@@ -72,6 +72,17 @@ pub mod Ruby {
     /// ```
     pub struct Hash<K, V>;
 
+    /// A native Ruby symbol.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() { rutie_test::test_ruby!(r#"
+    /// :foo
+    /// # "#); }
+    /// ```
+    pub struct Symbol;
+
     /// Represents any kind of object.
     pub struct Any;
 }
@@ -491,7 +502,283 @@ pub mod Wasmer {
 
     impl Store {
         /// Creates a new `Store`.
-        pub fn new() -> Self {
+        ///
+        /// The following keyword arguments are supported:
+        ///
+        /// * `features`, the [`Features`] to enable when validating
+        ///   and compiling modules,
+        /// * `threads`, to enable the [threads proposal], which is
+        ///   required to create shared memories (see [`Memory`]). It
+        ///   takes precedence over `features`,
+        /// * `target`, the [`Target`] to compile the modules for. It
+        ///   defaults to the host. The modules compiled for another
+        ///   target can be serialized, but not instantiated.
+        ///
+        /// [threads proposal]: https://github.com/WebAssembly/threads
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new threads: true
+        ///
+        /// assert { store.threads? }
+        ///
+        /// store = Wasmer::Store.new features: Wasmer::Features.new(simd: false)
+        ///
+        /// assert { not store.features.simd? }
+        /// # "#); }
+        /// ```
+        pub fn new(
+            features: Option<Features>,
+            threads: Option<Boolean>,
+            target: Option<Target>,
+        ) -> Self {
+            x!()
+        }
+
+        /// Returns the [`Features`] enabled in this store.
+        pub fn features(&self) -> Features {
+            x!()
+        }
+
+        /// Returns the [`Target`] this store compiles for.
+        pub fn target(&self) -> Target {
+            x!()
+        }
+
+        /// Returns whether the threads proposal is enabled.
+        pub fn threads(&self) -> Boolean {
+            x!()
+        }
+    }
+
+    /// The target a [`Store`] compiles the modules for: a target
+    /// triple and a set of CPU features.
+    ///
+    /// Compiling for another target than the host, e.g. for
+    /// `aarch64-unknown-linux-gnu` on a `x86_64` machine, produces
+    /// modules that can be serialized (see [`Module::serialize`]) and
+    /// deserialized on a machine of this target. They can't be
+    /// instantiated on the host: [`Instance::new`] raises a
+    /// `RuntimeError`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() { rutie_test::test_ruby!(r#"
+    /// target = Wasmer::Target.new triple: "aarch64-unknown-linux-gnu"
+    /// store = Wasmer::Store.new target: target
+    ///
+    /// serialized_module = Wasmer::Module.new(store, "(module)").serialize
+    ///
+    /// assert { target.triple == "aarch64-unknown-linux-gnu" }
+    /// assert { store.target.triple == "aarch64-unknown-linux-gnu" }
+    /// # "#); }
+    /// ```
+    pub struct Target;
+
+    impl Target {
+        /// Creates a new `Target`, with the following keyword
+        /// arguments:
+        ///
+        /// * `triple`, the target triple, e.g.
+        ///   `"x86_64-unknown-linux-gnu"`. It defaults to the host's,
+        /// * `cpu_features`, an `Array` of CPU features the compiled
+        ///   code can use, e.g. `["sse2", "avx2"]`. It defaults to the
+        ///   host's features if the triple is the host's, and to no
+        ///   feature otherwise.
+        ///
+        /// An `ArgumentError` is raised if the triple or a CPU
+        /// feature is unknown.
+        pub fn new(triple: Option<String>, cpu_features: Option<Array<String>>) -> Self {
+            x!()
+        }
+
+        /// Returns the target triple.
+        pub fn triple(&self) -> String {
+            x!()
+        }
+
+        /// Returns the CPU features.
+        pub fn cpu_features(&self) -> Array<String> {
+            x!()
+        }
+
+        /// Returns whether the code compiled for this target can run
+        /// on the host, i.e. whether the triple is the host's and
+        /// the host supports all the CPU features. In Ruby, this
+        /// method is named `host?`.
+        pub fn is_host(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns the triple followed by the CPU features, e.g.
+        /// `"x86_64-unknown-linux-gnu (sse2, sse3)"`.
+        pub fn to_s(&self) -> String {
+            x!()
+        }
+    }
+
+    /// Controls which WebAssembly proposals are enabled. Each
+    /// feature corresponds to a [WebAssembly proposal].
+    ///
+    /// Features are passed to [`Store::new`], and to
+    /// [`Module::validate`] to check a module against a different set
+    /// of features than the store's.
+    ///
+    /// [WebAssembly proposal]: https://github.com/WebAssembly/proposals
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() { rutie_test::test_ruby!(r#"
+    /// features = Wasmer::Features.new bulk_memory: false, tail_call: true
+    ///
+    /// assert { not features.bulk_memory? }
+    /// assert { features.tail_call? }
+    /// assert { features.simd? }
+    /// # "#); }
+    /// ```
+    pub struct Features;
+
+    impl Features {
+        /// Creates a new `Features`. Each feature is a keyword
+        /// argument of the same name. By default, `reference_types`,
+        /// `simd`, `bulk_memory` and `multi_value` are enabled, the
+        /// others are disabled.
+        pub fn new(
+            threads: Option<Boolean>,
+            reference_types: Option<Boolean>,
+            simd: Option<Boolean>,
+            bulk_memory: Option<Boolean>,
+            multi_value: Option<Boolean>,
+            tail_call: Option<Boolean>,
+            module_linking: Option<Boolean>,
+            multi_memory: Option<Boolean>,
+            memory64: Option<Boolean>,
+        ) -> Self {
+            x!()
+        }
+
+        /// Returns whether the [threads
+        /// proposal](https://github.com/WebAssembly/threads) is
+        /// enabled.
+        pub fn threads(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [reference types
+        /// proposal](https://github.com/WebAssembly/reference-types)
+        /// is enabled.
+        pub fn reference_types(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [SIMD
+        /// proposal](https://github.com/WebAssembly/simd) is enabled.
+        pub fn simd(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [bulk memory
+        /// proposal](https://github.com/WebAssembly/bulk-memory-operations)
+        /// is enabled.
+        pub fn bulk_memory(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [multi-value
+        /// proposal](https://github.com/WebAssembly/multi-value) is
+        /// enabled.
+        pub fn multi_value(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [tail call
+        /// proposal](https://github.com/WebAssembly/tail-call) is
+        /// enabled.
+        pub fn tail_call(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [module linking
+        /// proposal](https://github.com/WebAssembly/module-linking)
+        /// is enabled.
+        pub fn module_linking(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [multi-memory
+        /// proposal](https://github.com/WebAssembly/multi-memory) is
+        /// enabled.
+        pub fn multi_memory(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns whether the [64-bit memory
+        /// proposal](https://github.com/WebAssembly/memory64) is
+        /// enabled.
+        pub fn memory64(&self) -> Boolean {
+            x!()
+        }
+
+        /// Returns all the features as a `Hash`, with the feature
+        /// names as keys.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// features = Wasmer::Features.new threads: true
+        ///
+        /// assert { features.to_h[:threads] == true }
+        /// assert { features.to_h[:memory64] == false }
+        /// # "#); }
+        /// ```
+        pub fn to_h(&self) -> Hash<Symbol, Boolean> {
+            x!()
+        }
+    }
+
+    /// Raised when a WebAssembly module is invalid, see
+    /// [`Module::validate_or_raise`], or fails to compile, see
+    /// [`Module::new`].
+    ///
+    /// It is a subclass of `RuntimeError`.
+    pub struct CompileError;
+
+    impl CompileError {
+        /// Returns the offset (in bytes) of the error in the module,
+        /// or `nil` when it's unknown, e.g. when the module uses a
+        /// feature the compiler doesn't support.
+        pub fn offset(&self) -> Option<Integer> {
+            x!()
+        }
+
+        /// Returns the reason of the error, without the offset.
+        pub fn reason(&self) -> String {
+            x!()
+        }
+    }
+
+    /// Error raised by [`Module::deserialize`] when a serialized
+    /// module is refused.
+    ///
+    /// It is a subclass of `RuntimeError`.
+    pub struct DeserializeError;
+
+    impl DeserializeError {
+        /// Returns why the serialized module has been refused:
+        ///
+        /// * `:malformed`, it isn't a serialized module,
+        /// * `:tampered`, its checksum or its signature doesn't match,
+        /// * `:unauthenticated`, it's signed but no key has been given,
+        ///   or the reverse,
+        /// * `:incompatible`, it has been serialized by another
+        ///   version, or for another engine, target or set of
+        ///   features.
+        pub fn reason(&self) -> Symbol {
             x!()
         }
     }
@@ -535,20 +822,263 @@ pub mod Wasmer {
         /// enabled WebAssembly features in the Store engine to assure
         /// deterministic validation of the `Module`.
         ///
+        /// The `features` keyword argument overrides the features of
+        /// the store, see [`Features`].
+        ///
+        /// Like [`Module::new`], it accepts the WebAssembly text
+        /// format too, and returns `false` if the text is malformed.
+        ///
         /// # Example
         ///
         /// ```rust
         /// # fn main() { rutie_test::test_ruby!(r#"
         /// wasm_bytes = "\x00asm\x01\x00\x00\x00";
         /// Wasmer::Module.validate Wasmer::Store.new, wasm_bytes
+        ///
+        /// assert { Wasmer::Module.validate Wasmer::Store.new, "(module)" }
+        ///
+        /// ## A module using the bulk memory proposal.
+        /// wasm_bytes = Wasmer::wat2wasm(
+        ///   (<<~WAST)
+        ///   (module
+        ///     (memory 1)
+        ///     (func (memory.fill (i32.const 0) (i32.const 0) (i32.const 0))))
+        ///   WAST
+        /// )
+        /// features = Wasmer::Features.new bulk_memory: false
+        ///
+        /// assert { Wasmer::Module.validate Wasmer::Store.new, wasm_bytes }
+        /// assert { not Wasmer::Module.validate Wasmer::Store.new, wasm_bytes, features: features }
+        /// # "#); }
+        /// ```
+        pub fn validate(store: Store, bytes: String, features: Option<Features>) -> Boolean {
+            x!()
+        }
+
+        /// Validates a new WebAssembly Module like
+        /// [`Module::validate`], but raises a [`CompileError`] that
+        /// explains why the module is invalid. In Ruby, this method
+        /// is named `validate!`.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// wasm_bytes = Wasmer::wat2wasm "(module (func (result i32) (i64.const 1)))"
+        ///
+        /// begin
+        ///   Wasmer::Module.validate! Wasmer::Store.new, wasm_bytes
+        ///   assert { false }
+        /// rescue Wasmer::CompileError => error
+        ///   assert { error.offset > 0 }
+        ///   assert { error.reason.start_with? "type mismatch" }
+        /// end
+        /// # "#); }
+        /// ```
+        pub fn validate_or_raise(store: Store, bytes: String, features: Option<Features>) {
+            x!()
+        }
+
+        /// Validates a new WebAssembly Module, and returns all the
+        /// errors. Each error is a `Hash` with an `:offset` (in
+        /// bytes), and a `:reason`. The empty list means the module
+        /// is valid.
+        ///
+        /// An invalid section stops the validation, but all the
+        /// function bodies are validated independently from each
+        /// other.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// wasm_bytes = Wasmer::wat2wasm(
+        ///   (<<~WAST)
+        ///   (module
+        ///     (func (result i32) (i64.const 1))
+        ///     (func (result i32) (i32.const 2))
+        ///     (func (result i64) (i32.const 3)))
+        ///   WAST
+        /// )
+        /// errors = Wasmer::Module.validation_errors Wasmer::Store.new, wasm_bytes
+        ///
+        /// assert { errors.length == 2 }
+        /// assert { errors[0][:offset] < errors[1][:offset] }
+        /// assert { errors[0][:reason].include? "type mismatch" }
         /// # "#); }
         /// ```
-        pub fn validate(store: Store, bytes: String) -> Boolean {
+        pub fn validation_errors(
+            store: Store,
+            bytes: String,
+            features: Option<Features>,
+        ) -> Array<Hash<Symbol, Any>> {
             x!()
         }
 
         /// Creates a new [`Module`].
-        pub fn new(store: Store, bytes: String) -> Self {
+        ///
+        /// The bytes can be either WebAssembly bytes, or WebAssembly
+        /// text (WAT), which is detected and converted automatically,
+        /// so calling [`wat2wasm`] first isn't necessary.
+        ///
+        /// The bytes are given as a `String`, frozen or not, or as
+        /// any object responding to `read`, like a `File`, a
+        /// `StringIO` or a `Tempfile`, which is read until its end. A
+        /// `TypeError` is raised for any other object. The same goes
+        /// for [`Module::validate`], [`Module::validate_or_raise`],
+        /// [`Module::validation_errors`] and [`Module::deserialize`].
+        ///
+        /// The WebAssembly bytes are only held until
+        /// [`Module::info`] reads the structure of the module, unless
+        /// the `keep_bytes` keyword argument is `true`, which is
+        /// required by [`Module::to_wat`].
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// require "stringio"
+        ///
+        /// store = Wasmer::Store.new
+        /// module_ = Wasmer::Module.new store, "(module (func (export \"answer\") (result i32) (i32.const 42)))"
+        ///
+        /// assert { module_.exports[0].name == "answer" }
+        ///
+        /// module_ = Wasmer::Module.new store, StringIO.new("(module (func (export \"answer\")))")
+        ///
+        /// assert { module_.exports[0].name == "answer" }
+        /// # "#); }
+        /// ```
+        pub fn new(store: Store, bytes: String, keep_bytes: Option<Boolean>) -> Self {
+            x!()
+        }
+
+        /// Creates a new [`Module`] from a file, which can contain
+        /// either WebAssembly bytes (e.g. a `.wasm` file) or
+        /// WebAssembly text (e.g. a `.wat` file).
+        ///
+        /// The file is read and compiled without being loaded into a
+        /// Ruby `String`. An `IOError` is raised if the file cannot be
+        /// read, and a `RuntimeError` if it cannot be compiled; both
+        /// messages include the path. The `keep_bytes` keyword argument
+        /// is the same as for [`Module::new`].
+        ///
+        /// # Example
+        ///
+        /// ```rust,ignore
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// module_ = Wasmer::Module.from_file Wasmer::Store.new, "tests/tests.wasm"
+        /// # "#); }
+        /// ```
+        pub fn from_file(store: Store, path: String, keep_bytes: Option<Boolean>) -> Self {
+            x!()
+        }
+
+        /// Disassembles the module to WebAssembly text format. It
+        /// accepts the same options as [`wasm2wat`].
+        ///
+        /// The module must have been created with `keep_bytes: true`,
+        /// see [`Module::new`], otherwise a `RuntimeError` is raised
+        /// because its WebAssembly bytes aren't available anymore. The
+        /// same goes for a deserialized module.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// module_ = Wasmer::Module.new Wasmer::Store.new, "(module (func (export \"answer\") (result i32) (i32.const 42)))", keep_bytes: true
+        ///
+        /// assert { module_.to_wat.include? "(export \"answer\" (func 0))" }
+        /// assert { module_.to_wat(fold: true).include? "(i32.const 42)" }
+        /// # "#); }
+        /// ```
+        pub fn to_wat(
+            &self,
+            fold: Option<Boolean>,
+            print_offsets: Option<Boolean>,
+            skeleton: Option<Boolean>,
+            name_unnamed: Option<Boolean>,
+        ) -> String {
+            x!()
+        }
+
+        /// Returns the internal structure of the module, as a `Hash`
+        /// with the following keys:
+        ///
+        /// * `:functions`, the functions defined by the module (the
+        ///   imported ones are excluded), as `Hash`es with an `:index`
+        ///   in the function index space, a `:name` from the `name`
+        ///   custom section if any, a `:type` as a [`FunctionType`],
+        ///   the number of declared `:locals`, and the `:local_names`
+        ///   as a `Hash` from local indices to names,
+        /// * `:tables`, `:memories` and `:globals`, the definitions of
+        ///   the module as [`TableType`]s, [`MemoryType`]s and
+        ///   [`GlobalType`]s; a 64-bit memory, whose limits don't fit
+        ///   in a [`MemoryType`], is a `Hash` with a `:minimum`, a
+        ///   `:maximum`, `:shared` and `memory64: true` instead,
+        /// * `:start`, the index of the start function, or `nil`,
+        /// * `:elements`, the element segments, as `Hash`es with a
+        ///   `:mode` (`:active`, `:passive` or `:declared`), a
+        ///   `:table` index, an `:offset`, a `:type` and a `:size` (in
+        ///   elements),
+        /// * `:data`, the data segments, as `Hash`es with a `:mode`
+        ///   (`:active` or `:passive`), a `:memory` index, an
+        ///   `:offset` and a `:size` (in bytes).
+        ///
+        /// The indices and offsets of passive segments are `nil`, and
+        /// so are the offsets that aren't constant.
+        ///
+        /// The structure is read once, on the first call, from the
+        /// WebAssembly bytes of the module. A `RuntimeError` is raised
+        /// if the module has been deserialized, since its WebAssembly
+        /// bytes aren't available.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// module_ = Wasmer::Module.new Wasmer::Store.new, (<<~WAST)
+        /// (module
+        ///   (memory 1)
+        ///   (data (i32.const 8) "hello")
+        ///   (func $init (local i32 i64))
+        ///   (start $init))
+        /// WAST
+        ///
+        /// info = module_.info
+        ///
+        /// assert { info[:functions][0][:name] == "init" }
+        /// assert { info[:functions][0][:locals] == 2 }
+        /// assert { info[:start] == 0 }
+        /// assert { info[:data][0][:offset] == 8 }
+        /// assert { info[:data][0][:size] == 5 }
+        /// # "#); }
+        /// ```
+        pub fn info(&self) -> Hash<Symbol, Any> {
+            x!()
+        }
+
+        /// Returns the names of all the functions of the module,
+        /// imported ones included, in the order of the function index
+        /// space. They are read from the `name` custom section; the
+        /// unnamed functions are named after their index, e.g.
+        /// `func[3]`.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// module_ = Wasmer::Module.new Wasmer::Store.new, (<<~WAST)
+        /// (module
+        ///   (import "env" "log" (func $log))
+        ///   (func $main)
+        ///   (func))
+        /// WAST
+        ///
+        /// assert { module_.function_names == ["log", "main", "func[2]"] }
+        /// # "#); }
+        /// ```
+        pub fn function_names(&self) -> Array<String> {
             x!()
         }
 
@@ -608,7 +1138,26 @@ pub mod Wasmer {
             x!()
         }
 
-        /// Get the custom sections of the module given a `name`.
+        /// Returns the names of the custom sections of the module,
+        /// except the `name` section which is read by the engine (see
+        /// [`Module::function_names`]).
+        ///
+        /// # Example
+        ///
+        /// ```rust,ignore
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// bytes = IO.read "custom_sections.wasm", mode: "rb"
+        /// module_ = Wasmer::Module.new Wasmer::Store.new, bytes
+        ///
+        /// assert { module_.custom_section_names.sort == ["easter_egg", "hello"] }
+        /// # "#); }
+        /// ```
+        pub fn custom_section_names(&self) -> Array<String> {
+            x!()
+        }
+
+        /// Get the custom sections of the module given a `name`. The
+        /// payloads are binary strings, i.e. encoded in `ASCII-8BIT`.
         ///
         /// # Important
         ///
@@ -635,47 +1184,144 @@ pub mod Wasmer {
             x!()
         }
 
-        /// Serializes a module into a binary representation that the
-        /// engine can later process via [`Module::deserialize`].
+        /// Decodes the `producers` custom section, which lists the
+        /// languages, the tools and the SDKs that have produced the
+        /// module. It returns a `Hash` from the fields (`"language"`,
+        /// `"processed-by"` or `"sdk"`) to `Hash`es of names and
+        /// versions, or `nil` if the section is absent.
+        ///
+        /// A `RuntimeError` is raised if the section is malformed.
         ///
         /// # Example
         ///
         /// ```rust
         /// # fn main() { rutie_test::test_ruby!(r#"
-        /// module_ = Wasmer::Module.new Wasmer::Store.new, "(module)"
-        /// assert { module_.serialize.is_a?(String) }
+        /// module_ = Wasmer::Module.new(
+        ///   Wasmer::Store.new,
+        ///   '(module (@custom "producers" "\01\0cprocessed-by\01\05rustc\061.56.0"))'
+        /// )
+        ///
+        /// assert { module_.producers == {"processed-by" => {"rustc" => "1.56.0"}} }
         /// # "#); }
         /// ```
-        pub fn serialize(&self) -> String {
+        pub fn producers(&self) -> Option<Hash<String, Hash<String, String>>> {
             x!()
         }
 
-        /// Deserializes a serialized module binary into a Module.
-        ///
-        /// **Note**: the module has to be serialized before with the
-        /// serialize method.
-        ///
-        /// # Safety
-        ///
-        /// This function is inherently unsafe as the provided bytes:
-        ///
-        /// 1. Are going to be deserialized directly into Rust objects.
-        /// 2. Contains the function assembly bodies and, if
-        ///    intercepted, a malicious actor could inject code into
-        ///    executable memory.
+        /// Decodes the `target_features` custom section, which lists
+        /// the features used by the module. It returns a `Hash` from
+        /// the feature names to `:used`, `:disallowed` or
+        /// `:required`, or `nil` if the section is absent.
         ///
-        /// And as such, the deserialize method is unsafe.
+        /// A `RuntimeError` is raised if the section is malformed.
         ///
         /// # Example
         ///
         /// ```rust
         /// # fn main() { rutie_test::test_ruby!(r#"
-        /// store = Wasmer::Store.new
+        /// module_ = Wasmer::Module.new(
+        ///   Wasmer::Store.new,
+        ///   '(module (@custom "target_features" "\01+\07simd128"))'
+        /// )
         ///
-        /// serialized_module = Wasmer::Module.new(
-        ///   store,
-        ///   (<<~WAST)
-        ///   (module
+        /// assert { module_.target_features == {"simd128" => :used} }
+        /// # "#); }
+        /// ```
+        pub fn target_features(&self) -> Option<Hash<String, Symbol>> {
+            x!()
+        }
+
+        /// Decodes the `sourceMappingURL` custom section, i.e. the
+        /// URL of the source map of the module, or `nil` if the
+        /// section is absent.
+        ///
+        /// A `RuntimeError` is raised if the section is malformed.
+        pub fn source_mapping_url(&self) -> Option<String> {
+            x!()
+        }
+
+        /// Decodes the `dylink.0` custom section, which describes a
+        /// module meant to be dynamically linked. It returns a `Hash`
+        /// with the following keys, or `nil` if the section is
+        /// absent:
+        ///
+        /// * `:memory_size` and `:table_size`, the sizes required by
+        ///   the module, and `:memory_alignment` and
+        ///   `:table_alignment`, their alignments (as powers of 2),
+        /// * `:needed`, the names of the dynamic libraries it
+        ///   depends on,
+        /// * `:exports`, a `Hash` from export names to their flags,
+        /// * `:imports`, the flags of the imports, as `Hash`es with a
+        ///   `:module`, a `:name` and `:flags`.
+        ///
+        /// A `RuntimeError` is raised if the section is malformed.
+        pub fn dylink(&self) -> Option<Hash<Symbol, Any>> {
+            x!()
+        }
+
+        /// Serializes a module into a binary representation that the
+        /// engine can later process via [`Module::deserialize`].
+        ///
+        /// The binary representation records the versions of this
+        /// extension and of Wasmer, the engine, the target and the
+        /// features of the store, and is followed by a checksum.
+        ///
+        /// If a `key` is given, the binary representation is signed
+        /// with it, and can only be deserialized with the same key.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// module_ = Wasmer::Module.new Wasmer::Store.new, "(module)"
+        /// assert { module_.serialize.is_a?(String) }
+        /// assert { module_.serialize(key: "secret").encoding == Encoding::ASCII_8BIT }
+        /// # "#); }
+        /// ```
+        pub fn serialize(&self, key: Option<String>) -> String {
+            x!()
+        }
+
+        /// Serializes a module like [`Module::serialize`], and writes
+        /// it into the file at `path`.
+        pub fn serialize_to_file(&self, path: String, key: Option<String>) {
+            x!()
+        }
+
+        /// Deserializes a serialized module binary into a Module.
+        ///
+        /// **Note**: the module has to be serialized before with the
+        /// serialize method.
+        ///
+        /// # Safety
+        ///
+        /// The engine deserializes the function bodies directly into
+        /// executable memory. Thus, before that, the binary
+        /// representation is checked: a [`DeserializeError`] is
+        /// raised if it's malformed, if its checksum doesn't match,
+        /// if it has been serialized by another version of this
+        /// extension or of Wasmer, or for another engine, target or
+        /// set of features than the store's.
+        ///
+        /// The checksum only detects accidental corruption, not
+        /// tampering: anyone able to modify an unsigned module can
+        /// recompute its checksum, and run arbitrary code in this
+        /// process. An unsigned module must thus only be deserialized
+        /// from a trusted place. To deserialize modules coming from an
+        /// untrusted place, sign them with a `key` that is kept
+        /// secret: a module that isn't signed with the same key is
+        /// refused.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new
+        ///
+        /// serialized_module = Wasmer::Module.new(
+        ///   store,
+        ///   (<<~WAST)
+        ///   (module
         ///     (func (export "function") (param i32 i64)))
         ///   WAST
         /// ).serialize
@@ -692,7 +1338,31 @@ pub mod Wasmer {
         /// assert { exports[0].type.results == [] }
         /// # "#); }
         /// ```
-        pub fn deserialize(bytes: String) -> Self {
+        ///
+        /// With a key:
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new
+        /// serialized_module = Wasmer::Module.new(store, "(module)").serialize key: "secret"
+        ///
+        /// module_ = Wasmer::Module.deserialize store, serialized_module, key: "secret"
+        ///
+        /// begin
+        ///   Wasmer::Module.deserialize store, serialized_module, key: "another secret"
+        ///   assert { false }
+        /// rescue Wasmer::DeserializeError => error
+        ///   assert { error.reason == :tampered }
+        /// end
+        /// # "#); }
+        /// ```
+        pub fn deserialize(store: Store, bytes: String, key: Option<String>) -> Self {
+            x!()
+        }
+
+        /// Reads the file at `path`, and deserializes it like
+        /// [`Module::deserialize`].
+        pub fn deserialize_from_file(store: Store, path: String, key: Option<String>) -> Self {
             x!()
         }
     }
@@ -987,6 +1657,41 @@ pub mod Wasmer {
         pub fn register(&self, namespace_name: String, namespace: Hash<String, Any>) {
             x!()
         }
+
+        /// Returns a new import object combining the namespaces of
+        /// this import object and of `other`. Namespaces with the same
+        /// name are merged; a `RuntimeError` is raised if both import
+        /// objects provide the same name in the same namespace.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new
+        /// function = Wasmer::Function.new store, -> () { }, Wasmer::FunctionType.new([], [])
+        ///
+        /// math = Wasmer::ImportObject.new
+        /// math.register "math", {sum: function}
+        ///
+        /// env = Wasmer::ImportObject.new
+        /// env.register "env", {log: function}
+        ///
+        /// import_object = math.merge env
+        ///
+        /// assert { import_object.contains_namespace? "math" }
+        /// assert { import_object.contains_namespace? "env" }
+        /// assert { !math.contains_namespace? "env" }
+        /// # "#); }
+        /// ```
+        pub fn merge(&self, other: ImportObject) -> ImportObject {
+            x!()
+        }
+
+        /// Like [`ImportObject::merge`], but adds the namespaces of
+        /// `other` to this import object.
+        pub fn extend(&mut self, other: ImportObject) {
+            x!()
+        }
     }
 
     /// Represents a WebAssembly function instance.
@@ -1063,6 +1768,11 @@ pub mod Wasmer {
         }
 
         /// Calls the function with arguments. It returns zero or more results.
+        ///
+        /// If the function traps, a `RuntimeError` is raised. Its
+        /// message contains the WebAssembly backtrace, where the
+        /// functions are named after the `name` custom section of
+        /// their module, or after their index, e.g. `func[3]`.
         pub fn call(x0: Any, x1: Any, x2: Any, etc: Any) -> Any {
             x!()
         }
@@ -1071,6 +1781,41 @@ pub mod Wasmer {
         pub fn r#type(&self) -> FunctionType {
             x!()
         }
+
+        /// Returns the name of the function, as declared by the
+        /// `name` custom section of its module, or `func[N]` where
+        /// `N` is its index if it's unnamed. The functions read from
+        /// a [`Table`] or a [`Global`], or returned by a function, are
+        /// named the same way when they are defined by the module of
+        /// the instance they come from. It is `nil` for host
+        /// functions, and for functions defined by another module.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// module_ = Wasmer::Module.new Wasmer::Store.new, (<<~WAST)
+        /// (module
+        ///   (func $sum (export "sum") (param i32 i32) (result i32)
+        ///     (i32.add (local.get 0) (local.get 1)))
+        ///   (func (export "nop")))
+        /// WAST
+        /// instance = Wasmer::Instance.new module_, nil
+        ///
+        /// assert { instance.exports.sum.name == "sum" }
+        /// assert { instance.exports.nop.name == "func[1]" }
+        /// assert { instance.exports.sum.inspect == "#<Wasmer::Function sum: (i32, i32) -> (i32)>" }
+        /// # "#); }
+        /// ```
+        pub fn name(&self) -> Option<String> {
+            x!()
+        }
+
+        /// Returns a human-readable representation of the function,
+        /// with its name and its signature.
+        pub fn inspect(&self) -> String {
+            x!()
+        }
     }
 
     /// A WebAssembly memory instance.
@@ -1096,13 +1841,60 @@ pub mod Wasmer {
     /// ```rust
     /// # fn main() { rutie_test::test_ruby!(r#"
     /// store = Wasmer::Store.new
-    /// memory_type = Wasmer::MemoryType.new 3, 10, true
+    /// memory_type = Wasmer::MemoryType.new 3, 10, false
     /// memory = Wasmer::Memory.new store, memory_type
     ///
     /// assert { memory.size == 3 }
     /// # "#); }
     /// ```
     ///
+    /// Creates a shared [`Memory`], which requires the threads
+    /// proposal to be enabled on the [`Store`], and a maximum size:
+    ///
+    /// ```rust
+    /// # fn main() { rutie_test::test_ruby!(r#"
+    /// store = Wasmer::Store.new threads: true
+    /// memory_type = Wasmer::MemoryType.new 1, 10, true
+    /// memory = Wasmer::Memory.new store, memory_type
+    ///
+    /// assert { memory.type.shared? }
+    /// # "#); }
+    /// ```
+    ///
+    /// A shared memory can be passed to several Ruby threads, and
+    /// synchronized with the `atomic_*` methods. Only the host can
+    /// create shared memories for now: the compiler doesn't support
+    /// modules declaring a shared memory, and fails to compile them.
+    /// Modules can import one though, so that several instances,
+    /// possibly running on different Ruby threads, share it:
+    ///
+    /// ```rust
+    /// # fn main() { rutie_test::test_ruby!(r#"
+    /// store = Wasmer::Store.new threads: true
+    /// memory = Wasmer::Memory.new store, Wasmer::MemoryType.new(1, 1, true)
+    /// module_ = Wasmer::Module.new store, (<<~WAST)
+    /// (module
+    ///   (import "env" "memory" (memory 1 1 shared))
+    ///   (func (export "increment")
+    ///     (drop (i32.atomic.rmw.add (i32.const 0) (i32.const 1)))))
+    /// WAST
+    ///
+    /// import_object = Wasmer::ImportObject.new
+    /// import_object.register "env", {memory: memory}
+    ///
+    /// 2.times.map {
+    ///   Thread.new { Wasmer::Instance.new(module_, import_object).exports.increment.() }
+    /// }.each(&:join)
+    ///
+    /// assert { memory.atomic_load(0) == 2 }
+    /// # "#); }
+    /// ```
+    ///
+    /// The compiler doesn't support the `memory.atomic.wait32`,
+    /// `memory.atomic.wait64` and `memory.atomic.notify`
+    /// instructions yet, the host has to synchronize the instances
+    /// with [`Memory::atomic_wait`] and [`Memory::atomic_notify`].
+    ///
     /// Gets a memory from the exports of an instance:
     ///
     /// ```rust,ignore
@@ -1192,6 +1984,72 @@ pub mod Wasmer {
         pub fn int32_view(&self) -> Int32View {
             x!()
         }
+
+        /// Atomically reads the unsigned 32-bit integer at `offset`
+        /// (in bytes). The offset must be aligned to 4 bytes.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new threads: true
+        /// memory = Wasmer::Memory.new store, Wasmer::MemoryType.new(1, 1, true)
+        ///
+        /// memory.atomic_store 8, 42
+        ///
+        /// assert { memory.atomic_load(8) == 42 }
+        /// # "#); }
+        /// ```
+        pub fn atomic_load(&self, offset: Integer) -> Integer {
+            x!()
+        }
+
+        /// Atomically writes the unsigned 32-bit integer `value` at
+        /// `offset` (in bytes). The offset must be aligned to 4
+        /// bytes.
+        pub fn atomic_store(&self, offset: Integer, value: Integer) {
+            x!()
+        }
+
+        /// Blocks the current thread until another thread calls
+        /// [`Memory::atomic_notify`] on the same `offset`, like
+        /// `memory.atomic.wait32`. The memory must be shared.
+        ///
+        /// If the value at `offset` is not `expected`, it returns
+        /// `:not_equal` immediately. If `timeout` (in seconds) has
+        /// elapsed, it returns `:timed_out`. Otherwise, it returns
+        /// `:ok`.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new threads: true
+        /// memory = Wasmer::Memory.new store, Wasmer::MemoryType.new(1, 1, true)
+        ///
+        /// waiter = Thread.new { memory.atomic_wait 0, 0 }
+        /// sleep 0.01 until memory.atomic_notify(0, 1) == 1
+        ///
+        /// assert { waiter.value == :ok }
+        /// assert { memory.atomic_wait(0, 1) == :not_equal }
+        /// assert { memory.atomic_wait(0, 0, 0.01) == :timed_out }
+        /// # "#); }
+        /// ```
+        pub fn atomic_wait(
+            &self,
+            offset: Integer,
+            expected: Integer,
+            timeout: Option<Float>,
+        ) -> Symbol {
+            x!()
+        }
+
+        /// Wakes up at most `count` threads waiting on `offset` (all
+        /// of them by default), like `memory.atomic.notify`. It
+        /// returns the number of woken threads.
+        pub fn atomic_notify(&self, offset: Integer, count: Option<Integer>) -> Integer {
+            x!()
+        }
     }
 
     pub struct Uint8View;
@@ -1276,12 +2134,122 @@ pub mod Wasmer {
     /// A table created by the host or in WebAssembly code will be
     /// accessible and mutable from both host and WebAssembly.
     ///
+    /// The elements of a `funcref` table are [`Function`]s, and the
+    /// elements of an `externref` table are any Ruby objects. A null
+    /// reference is represented by `nil`. Ruby objects referenced by
+    /// WebAssembly, e.g. stored in an `externref` table, are kept
+    /// alive until WebAssembly drops its last reference to them, or
+    /// until their store, and the tables, globals, functions and
+    /// instances created with it, are all collected.
+    ///
     /// Specification: <https://webassembly.github.io/spec/core/exec/runtime.html#table-instances>
     pub struct Table;
 
     impl Table {
-        /// Creates a new `Table`.
-        pub fn new(store: Store, table_type: TableType, initia_value: Value) -> Self {
+        /// Creates a new `Table`. All its elements are set to
+        /// `initial_value`, which is `nil` by default.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new
+        /// table = Wasmer::Table.new store, Wasmer::TableType.new(Wasmer::Type::FUNC_REF, 3, nil)
+        ///
+        /// assert { table.size == 3 }
+        /// assert { table[0] == nil }
+        /// # "#); }
+        /// ```
+        pub fn new(store: Store, table_type: TableType, initial_value: Option<Any>) -> Self {
+            x!()
+        }
+
+        /// Returns the size of the table, i.e. its number of elements.
+        pub fn size(&self) -> Integer {
+            x!()
+        }
+
+        /// Returns the table type.
+        pub fn r#type(&self) -> TableType {
+            x!()
+        }
+
+        /// Returns the element at `index`, i.e. a [`Function`], a Ruby
+        /// object or `nil`. An `IndexError` is raised if `index` is
+        /// out of bounds.
+        ///
+        /// Note: This method is bound to `[]`.
+        pub fn get(&self, index: Integer) -> Any {
+            x!()
+        }
+
+        /// Sets the element at `index`. It makes it possible to
+        /// register a host function in the indirect call table of an
+        /// instance, for example.
+        ///
+        /// Note: This method is bound to `[]=`.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new
+        /// module_ = Wasmer::Module.new store, (<<~WAST)
+        /// (module
+        ///   (type $callback (func (result i32)))
+        ///   (table (export "table") 1 funcref)
+        ///   (func (export "call") (result i32)
+        ///     (call_indirect (type $callback) (i32.const 0))))
+        /// WAST
+        /// instance = Wasmer::Instance.new module_, nil
+        ///
+        /// callback = Wasmer::Function.new store, -> { 42 }, Wasmer::FunctionType.new([], [Wasmer::Type::I32])
+        /// instance.exports.table[0] = callback
+        ///
+        /// assert { instance.exports.call.() == 42 }
+        /// # "#); }
+        /// ```
+        pub fn set(&self, index: Integer, value: Any) {
+            x!()
+        }
+
+        /// Grows the table by `delta` elements, which are set to
+        /// `initial_value` (`nil` by default). It returns the
+        /// previous size of the table.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// store = Wasmer::Store.new
+        /// table = Wasmer::Table.new store, Wasmer::TableType.new(Wasmer::Type::EXTERN_REF, 1, 10)
+        ///
+        /// assert { table.grow(2, "hello") == 1 }
+        /// assert { table.to_a == [nil, "hello", "hello"] }
+        /// # "#); }
+        /// ```
+        pub fn grow(&self, delta: Integer, initial_value: Option<Any>) -> Integer {
+            x!()
+        }
+
+        /// Iterates over all the elements of the table, and returns
+        /// the table. Without a block, it returns an `Enumerator`.
+        /// `Table` includes `Enumerable`.
+        pub fn each(&self) -> Any {
+            x!()
+        }
+
+        /// Copies `length` elements of `source_table` starting at
+        /// `source_index` into this table starting at
+        /// `destination_index`. An `IndexError` is raised if a range
+        /// is out of bounds.
+        pub fn copy(
+            &self,
+            destination_index: Integer,
+            source_table: Table,
+            source_index: Integer,
+            length: Integer,
+        ) {
             x!()
         }
     }
@@ -1347,6 +2315,272 @@ pub mod Wasmer {
         }
     }
 
+    /// Error raised when some WebAssembly text cannot be parsed, see
+    /// [`wat2wasm`].
+    ///
+    /// It is a subclass of `RuntimeError`. Its message already
+    /// contains the position of the error and the snippet.
+    pub struct WatError;
+
+    impl WatError {
+        /// Returns the line of the error, starting from 1.
+        pub fn line(&self) -> Integer {
+            x!()
+        }
+
+        /// Returns the column of the error, starting from 1.
+        pub fn column(&self) -> Integer {
+            x!()
+        }
+
+        /// Returns the file name given to [`wat2wasm`], if any.
+        pub fn path(&self) -> Option<String> {
+            x!()
+        }
+
+        /// Returns the line of the error with a caret pointing at
+        /// the column.
+        pub fn snippet(&self) -> String {
+            x!()
+        }
+    }
+
+    /// Translates WebAssembly text source to WebAssembly binary
+    /// format.
+    ///
+    /// The optional `filename` is only used to point at the right
+    /// file when an error is raised. A [`WatError`] is raised if the
+    /// text cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() { rutie_test::test_ruby!(r#"
+    /// wasm_bytes = Wasmer::wat2wasm "(module)"
+    ///
+    /// assert { wasm_bytes == "\x00asm\x01\x00\x00\x00" }
+    ///
+    /// begin
+    ///   Wasmer::wat2wasm "(module\n  (func (result i32) (i32.const)))", filename: "answer.wat"
+    ///   assert { false }
+    /// rescue Wasmer::WatError => error
+    ///   assert { error.line == 2 }
+    ///   assert { error.path == "answer.wat" }
+    ///   assert { error.snippet.start_with? "   2 |" }
+    /// end
+    /// # "#); }
+    /// ```
+    pub fn wat2wasm(wat: String, filename: Option<String>) -> String {
+        x!()
+    }
+
+    /// Disassembles WebAssembly binary to WebAssembly text format.
+    ///
+    /// The following options are all disabled by default:
+    ///
+    /// * `fold` prints the instructions as folded expressions, e.g.
+    ///   `(i32.add (local.get 0) (i32.const 1))`,
+    /// * `print_offsets` prints the offset (in bytes) of each line
+    ///   as a comment,
+    /// * `skeleton` skips the function bodies and the contents of the
+    ///   data and element segments,
+    /// * `name_unnamed` synthesizes names for all the unnamed items,
+    ///   e.g. `$#func0`.
+    ///
+    /// See [`Module::to_wat`] to disassemble a compiled module.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() { rutie_test::test_ruby!(r#"
+    /// assert { Wasmer::wasm2wat("\x00asm\x01\x00\x00\x00") == "(module)" }
+    ///
+    /// wasm_bytes = Wasmer::wat2wasm(
+    ///   (<<~WAST)
+    ///   (module
+    ///     (func (param i32) (result i32)
+    ///       local.get 0
+    ///       i32.const 1
+    ///       i32.add))
+    ///   WAST
+    /// )
+    ///
+    /// assert { Wasmer::wasm2wat(wasm_bytes, fold: true).include? "(i32.add\n" }
+    /// assert { Wasmer::wasm2wat(wasm_bytes, skeleton: true).include? "..." }
+    /// # "#); }
+    /// ```
+    pub fn wasm2wat(
+        bytes: String,
+        fold: Option<Boolean>,
+        print_offsets: Option<Boolean>,
+        skeleton: Option<Boolean>,
+        name_unnamed: Option<Boolean>,
+    ) -> String {
+        x!()
+    }
+
+    /// Caches of compiled modules.
+    pub mod Cache {
+        use super::*;
+        use crate::doc::Ruby::*;
+
+        /// An on-disk cache of compiled modules, which saves the
+        /// compilation time of the modules that have already been
+        /// compiled, e.g. by a previous run of the program.
+        ///
+        /// The modules are keyed by a hash of their bytes, of the
+        /// versions of this extension and of Wasmer, and of the
+        /// compiler, the engine, the target and the features of the
+        /// store. The entries are written atomically, so that several
+        /// processes can share the same cache directory.
+        ///
+        /// # Security
+        ///
+        /// The entries contain machine code, which is loaded as is.
+        /// Without a `key`, they are only protected by a checksum,
+        /// which detects accidental corruption, not tampering: the
+        /// cache directory must be trusted, i.e. only writable by the
+        /// users trusted to run code. With a `key`, the entries are
+        /// signed, and the ones that aren't signed with the same key
+        /// are recompiled.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// require "tmpdir"
+        ///
+        /// Dir.mktmpdir do |directory|
+        ///   cache = Wasmer::Cache::FileSystem.new directory, max_size: 64 * 1024 * 1024
+        ///   store = Wasmer::Store.new
+        ///
+        ///   module_ = cache.load_or_compile store, "(module)"
+        ///   module_ = cache.load_or_compile store, "(module)"
+        ///
+        ///   assert { cache.stats == {hits: 1, misses: 1, evictions: 0} }
+        /// end
+        /// # "#); }
+        /// ```
+        pub struct FileSystem;
+
+        impl FileSystem {
+            /// Creates a cache in `directory`, which is created if it
+            /// doesn't exist.
+            ///
+            /// When `max_size` (in bytes) is given, the least recently
+            /// used entries are evicted when the cache grows beyond
+            /// it. When `key` is given, the entries are signed with it,
+            /// see [`Module::serialize`].
+            ///
+            /// The temporary files left behind by a process that
+            /// crashed while writing an entry are removed.
+            pub fn new(directory: String, max_size: Option<Integer>, key: Option<String>) -> Self {
+                x!()
+            }
+
+            /// Returns the module compiled from `bytes` (which can
+            /// also be WebAssembly text), by deserializing it from
+            /// the cache if possible, or by compiling it and storing
+            /// it in the cache otherwise.
+            ///
+            /// An entry that cannot be deserialized, e.g. because it
+            /// has been written by another version of Wasmer or
+            /// signed with another key, is replaced.
+            ///
+            /// The `keep_bytes` keyword argument is the same as for
+            /// [`Module::new`].
+            pub fn load_or_compile(
+                &self,
+                store: Store,
+                bytes: String,
+                keep_bytes: Option<Boolean>,
+            ) -> Module {
+                x!()
+            }
+
+            /// Returns the directory of the cache.
+            pub fn directory(&self) -> String {
+                x!()
+            }
+
+            /// Returns the size (in bytes) of all the entries of the
+            /// cache.
+            pub fn size(&self) -> Integer {
+                x!()
+            }
+
+            /// Returns the statistics of this cache object, as a
+            /// `Hash` with the number of `:hits`, `:misses` and
+            /// `:evictions`.
+            pub fn stats(&self) -> Hash<Symbol, Integer> {
+                x!()
+            }
+
+            /// Removes all the entries of the cache, and the orphaned
+            /// temporary files.
+            pub fn clear(&self) {
+                x!()
+            }
+        }
+    }
+
+    /// A runner for the `.wast` scripts of the [WebAssembly
+    /// specification test suite][spec].
+    ///
+    /// [spec]: https://github.com/WebAssembly/spec/tree/main/test/core
+    pub mod Wast {
+        use super::*;
+        use crate::doc::Ruby::*;
+
+        /// Runs a `.wast` script, given either its path or its
+        /// contents, with the given `store`.
+        ///
+        /// The `module`, `register`, `invoke`, `assert_return`,
+        /// `assert_trap`, `assert_exhaustion`, `assert_invalid`,
+        /// `assert_malformed` and `assert_unlinkable` directives are
+        /// supported. The modules can import the usual `spectest`
+        /// module.
+        ///
+        /// It returns a report, i.e. an `Array` with a `Hash` per
+        /// directive, which contains:
+        ///
+        /// * `:line` and `:column`, the position of the directive,
+        /// * `:directive`, the name of the directive as a `Symbol`,
+        ///   e.g. `:assert_return`,
+        /// * `:status`, either `:passed`, `:failed` or `:skipped`,
+        /// * `:message`, which explains why the directive has failed
+        ///   or has been skipped, `nil` otherwise.
+        ///
+        /// A [`WatError`] is raised if the script cannot be parsed.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// report = Wasmer::Wast.run(
+        ///   (<<~WAST),
+        ///   (module
+        ///     (func (export "div") (param i32 i32) (result i32)
+        ///       (i32.div_s (local.get 0) (local.get 1))))
+        ///
+        ///   (assert_return (invoke "div" (i32.const 6) (i32.const 3)) (i32.const 2))
+        ///   (assert_trap (invoke "div" (i32.const 1) (i32.const 0)) "integer divide by zero")
+        ///   (assert_invalid (module (func (result i32) (i64.const 1))) "type mismatch")
+        ///   WAST
+        ///   store: Wasmer::Store.new
+        /// )
+        ///
+        /// assert { report.length == 4 }
+        /// assert { report.all? { |entry| entry[:status] == :passed } }
+        /// assert { report[1][:directive] == :assert_return }
+        /// assert { report[1][:line] == 5 }
+        /// # "#); }
+        /// ```
+        pub fn run(path_or_string: String, store: Store) -> Array<Hash<Symbol, Any>> {
+            x!()
+        }
+    }
+
     /// Wasmer's [WASI] implementation.
     ///
     /// From the user perspective, WASI is a bunch of imports. To
@@ -1412,40 +2646,104 @@ pub mod Wasmer {
 
             /// Adds an argument.
             ///
-            /// Arguments must not contain the nul (`0x0`) byte.
+            /// Arguments must not contain the nul (`0x0`) byte, otherwise
+            /// an `ArgumentError` is raised.
             pub fn argument(&mut self, argument: String) -> Self {
                 x!()
             }
 
             /// Adds multiple arguments.
             ///
-            /// Arguments must not contain the nul (`0x0`) byte.
+            /// Arguments must not contain the nul (`0x0`) byte, otherwise
+            /// an `ArgumentError` is raised.
             pub fn arguments(&mut self, arguments: Array<String>) -> Self {
                 x!()
             }
 
+            /// Adds the arguments of the host process, i.e. the
+            /// content of `ARGV`.
+            pub fn inherit_arguments(&mut self) -> Self {
+                x!()
+            }
+
             /// Add an environment variable pair.
             ///
-            /// Environment variable keys and values must not contain
-            /// the byte `=` (`0x3d`) or null (`0x0`).
+            /// Environment variable keys must not be empty, and must
+            /// not contain the byte `=` (`0x3d`) or nul (`0x0`).
+            /// Values must not contain the nul byte. Otherwise an
+            /// `ArgumentError` is raised.
             pub fn environment(&mut self, key: String, value: String) -> Self {
                 x!()
             }
 
             /// Add environment variable pairs.
             ///
-            /// Environment variable keys and values must not contain
-            /// the byte `=` (`0x3d`) or null (`0x0`).
+            /// Environment variable keys must not be empty, and must
+            /// not contain the byte `=` (`0x3d`) or nul (`0x0`).
+            /// Values must not contain the nul byte. Otherwise an
+            /// `ArgumentError` is raised.
             pub fn environments(&mut self, pairs: Hash<String, String>) -> Self {
                 x!()
             }
 
-            /// Preopen a directory with a different name exposed to the WASI.
+            /// Adds the environment variables of the host process,
+            /// i.e. the content of `ENV`, sorted by key.
             ///
-            /// This opens the given directories at the virtual root,
+            /// The variables are selected with the following keyword
+            /// arguments:
+            ///
+            /// * `only`, an `Array` of the only keys to inherit,
+            /// * `except`, an `Array` of the keys not to inherit.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// Wasmer::Wasi::StateBuilder.new("program")
+            ///   .inherit_environment(only: ["HOME", "LANG", "PATH"])
+            ///
+            /// Wasmer::Wasi::StateBuilder.new("program")
+            ///   .inherit_environment(except: ["AWS_SECRET_ACCESS_KEY"])
+            /// ```
+            pub fn inherit_environment(
+                &mut self,
+                only: Option<Array<String>>,
+                except: Option<Array<String>>,
+            ) -> Self {
+                x!()
+            }
+
+            /// Preopen a directory.
+            ///
+            /// This opens the given directory at the virtual root,
             /// `/`, and allows the WASI module to read and write to the
-            /// given directories.
-            pub fn preopen_directory(&mut self, alias: String, value: String) -> Self {
+            /// given directory by default.
+            ///
+            /// The permissions are restricted with the following
+            /// keyword arguments:
+            ///
+            /// * `read`, whether the program can read the files. It
+            ///   defaults to `true`,
+            /// * `write`, whether the program can modify the files. It
+            ///   defaults to `true`,
+            /// * `create`, whether the program can create files. It
+            ///   defaults to `write`, and requires it.
+            ///
+            /// An `ArgumentError` is raised if no permission is
+            /// granted, or if `create` is granted without `write`.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// Wasmer::Wasi::StateBuilder.new("program")
+            ///   .preopen_directory("inputs", write: false)
+            /// ```
+            pub fn preopen_directory(
+                &mut self,
+                path: String,
+                read: Option<Boolean>,
+                write: Option<Boolean>,
+                create: Option<Boolean>,
+            ) -> Self {
                 x!()
             }
 
@@ -1454,17 +2752,209 @@ pub mod Wasmer {
             /// This opens the given directories at the virtual root,
             /// `/`, and allows the WASI module to read and write to the
             /// given directories.
-            pub fn preopen_directories(&mut self, pairs: Hash<String, String>) -> Self {
+            ///
+            /// The permissions of all the directories are restricted
+            /// with the `read`, `write` and `create` keyword
+            /// arguments, like [`StateBuilder::preopen_directory`].
+            pub fn preopen_directories(
+                &mut self,
+                pairs: Hash<String, String>,
+                read: Option<Boolean>,
+                write: Option<Boolean>,
+                create: Option<Boolean>,
+            ) -> Self {
                 x!()
             }
 
             /// Preopen a directory with a different name exposed to the WASI.
-            pub fn map_directory(&mut self, alias: String, value: String) -> Self {
+            ///
+            /// The permissions are restricted with the `read`,
+            /// `write` and `create` keyword arguments, like
+            /// [`StateBuilder::preopen_directory`].
+            pub fn map_directory(
+                &mut self,
+                alias: String,
+                value: String,
+                read: Option<Boolean>,
+                write: Option<Boolean>,
+                create: Option<Boolean>,
+            ) -> Self {
                 x!()
             }
 
             /// Preopen directories with a different name exposed to the WASI.
-            pub fn map_directories(&mut self, pairs: Hash<String, String>) -> Self {
+            ///
+            /// The permissions of all the directories are restricted
+            /// with the `read`, `write` and `create` keyword
+            /// arguments, like [`StateBuilder::preopen_directory`].
+            pub fn map_directories(
+                &mut self,
+                pairs: Hash<String, String>,
+                read: Option<Boolean>,
+                write: Option<Boolean>,
+                create: Option<Boolean>,
+            ) -> Self {
+                x!()
+            }
+
+            /// Sets the standard input of the program, which can be:
+            ///
+            /// * a `String`, read from start to end,
+            /// * an `IO`-like object responding to `read`, like a
+            ///   `File` or a `StringIO`, which is read lazily, as the
+            ///   program reads its standard input,
+            /// * a block, called each time the program needs more
+            ///   input, which returns the next chunk as a `String`, or
+            ///   `nil` at the end of the input.
+            ///
+            /// By default, the program reads the standard input of the
+            /// host process.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// Wasmer::Wasi::StateBuilder.new("program").stdin "hello"
+            /// Wasmer::Wasi::StateBuilder.new("program").stdin File.open("input.txt")
+            /// Wasmer::Wasi::StateBuilder.new("program").stdin { socket.gets }
+            /// ```
+            pub fn stdin(&mut self, input: Option<Any>) -> Self {
+                x!()
+            }
+
+            /// Makes the program read the standard input of the host
+            /// process, which is the default. It cancels a previous
+            /// call to [`StateBuilder::stdin`].
+            pub fn inherit_stdin(&mut self) -> Self {
+                x!()
+            }
+
+            /// Captures the standard output of the program, instead of
+            /// writing it to the standard output of the host process.
+            ///
+            /// Without a block, the output is buffered in memory, and
+            /// read with [`Environment::stdout`]. With a block, the
+            /// output is streamed: the block receives each chunk as a
+            /// binary `String` as soon as the program writes it.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// environment = Wasmer::Wasi::StateBuilder.new("program")
+            ///   .capture_stdout
+            ///   .capture_stderr { |chunk| $stderr.write chunk }
+            ///   .finalize
+            /// ```
+            pub fn capture_stdout(&mut self) -> Self {
+                x!()
+            }
+
+            /// Captures the standard error of the program, like
+            /// [`StateBuilder::capture_stdout`].
+            pub fn capture_stderr(&mut self) -> Self {
+                x!()
+            }
+
+            /// Replaces the clocks of the program, so that the time it
+            /// reads is reproducible. The time is given either with
+            /// the following keyword arguments:
+            ///
+            /// * `fixed`, the initial time, as a `Time` or as an
+            ///   `Integer` of nanoseconds,
+            /// * `step`, the number of seconds the time advances each
+            ///   time it's read, as an `Integer` or a `Float`. It
+            ///   defaults to 0,
+            ///
+            /// or by a block receiving the name of the clock, i.e.
+            /// `:realtime`, `:monotonic`, `:process_cputime` or
+            /// `:thread_cputime`, and returning the time the same way.
+            ///
+            /// An `ArgumentError` is raised if the time is before the
+            /// Unix epoch, if the step is negative, or if either
+            /// overflows 64 bits of nanoseconds.
+            ///
+            /// All the clocks share the same time, and the
+            /// environments created by this builder share the same
+            /// clock.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// Wasmer::Wasi::StateBuilder.new("program")
+            ///   .clock(fixed: Time.utc(2021, 11, 1), step: 0.001)
+            /// ```
+            pub fn clock(&mut self, fixed: Option<Any>, step: Option<Any>) -> Self {
+                x!()
+            }
+
+            /// Replaces the random generator of the program, so that
+            /// the random bytes it reads are reproducible. The bytes
+            /// are derived from the `Integer` `seed`, and are the same
+            /// on all the platforms, or they are returned by a block
+            /// receiving the number of bytes to produce.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// Wasmer::Wasi::StateBuilder.new("program")
+            ///   .random_seed(42)
+            ///
+            /// Wasmer::Wasi::StateBuilder.new("program")
+            ///   .random_seed { |length| "\0" * length }
+            /// ```
+            pub fn random_seed(&mut self, seed: Option<Integer>) -> Self {
+                x!()
+            }
+
+            /// Mounts a [`MemFS`] at `path` in the program's
+            /// filesystem. The program reads and writes the in-memory
+            /// filesystem, never the host's disk, and the files it
+            /// writes can be read back from the [`MemFS`] afterwards.
+            ///
+            /// A single [`MemFS`] can be mounted, and it can't be
+            /// combined with the preopened or mapped host directories.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// fs = Wasmer::Wasi::MemFS.new
+            /// fs.write "/in/data.csv", "a,b,c"
+            ///
+            /// environment = Wasmer::Wasi::StateBuilder.new("program")
+            ///   .mount("/", fs)
+            ///   .finalize
+            /// ```
+            pub fn mount(&mut self, path: String, fs: MemFS) -> Self {
+                x!()
+            }
+
+            /// Describes the current configuration of the state
+            /// builder, as a `Hash` with the following keys:
+            ///
+            /// * `:program_name`, a `String`,
+            /// * `:arguments`, an `Array` of `String`s, without the
+            ///   program name,
+            /// * `:environments`, a `Hash` of `String`s,
+            /// * `:directories`, a `Hash` of the names seen by the
+            ///   program to the host paths of the preopened or mapped
+            ///   directories, or to `"/"` for a mounted [`MemFS`],
+            /// * `:mounted`, whether a [`MemFS`] is mounted,
+            /// * `:stdin`, one of `:inherit`, `:string`, `:io` or
+            ///   `:block`,
+            /// * `:stdout` and `:stderr`, one of `:inherit`,
+            ///   `:captured` or `:streamed`,
+            /// * `:clock`, one of `:fixed`, `:block` or `nil`,
+            /// * `:random`, one of `:seeded`, `:block` or `nil`.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// Wasmer::Wasi::StateBuilder.new("program")
+            ///   .argument("--verbose")
+            ///   .capture_stdout
+            ///   .to_h
+            /// # => {program_name: "program", arguments: ["--verbose"], stdout: :captured, …}
+            /// ```
+            pub fn to_h(&self) -> Hash<Symbol, Any> {
                 x!()
             }
 
@@ -1486,15 +2976,193 @@ pub mod Wasmer {
             /// according to the WASI version.
             ///
             /// Use the [`Version`] enum to use a specific WASI
-            /// version, or use [`get_version`] to read the WASI
-            /// version from a [`Module`].
+            /// version, or give a [`Module`] to use the WASI version
+            /// it imports, as detected by [`get_version`].
+            ///
+            /// Without a version, or when none is detected in the
+            /// module, the import object registers the namespaces of
+            /// all the versions, i.e. both `wasi_unstable` and
+            /// `wasi_snapshot_preview1`, so that the module picks the
+            /// one it imports.
+            ///
+            /// The `extend` keyword argument combines the WASI imports
+            /// with another [`ImportObject`], e.g. the host functions
+            /// of the `env` namespace, see [`ImportObject::merge`].
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// store = Wasmer::Store.new
+            /// environment = Wasmer::Wasi::StateBuilder.new("program").finalize
+            ///
+            /// instance = Wasmer::Instance.new module_, environment.generate_import_object(store)
+            /// instance = Wasmer::Instance.new module_, environment.generate_import_object(store, module_)
+            ///
+            /// host = Wasmer::ImportObject.new
+            /// host.register "env", {log: log_function}
+            ///
+            /// instance = Wasmer::Instance.new module_, environment.generate_import_object(store, extend: host)
+            /// ```
             pub fn generate_import_object(
                 &self,
                 store: Store,
-                wasi_version: Version,
+                wasi_version: Option<Any>,
+                extend: Option<ImportObject>,
             ) -> ImportObject {
                 x!()
             }
+
+            /// Returns the standard output written by the program
+            /// since the last call, as a binary `String`, or `nil` if
+            /// it isn't captured (see [`StateBuilder::capture_stdout`]).
+            /// The output streamed to a block isn't buffered, so the
+            /// `String` is empty.
+            pub fn stdout(&self) -> Option<String> {
+                x!()
+            }
+
+            /// Returns the standard error written by the program, like
+            /// [`Environment::stdout`].
+            pub fn stderr(&self) -> Option<String> {
+                x!()
+            }
+
+            /// Returns the memory of the last instance created with
+            /// an import object generated by this environment, or
+            /// `nil` if there is none yet.
+            ///
+            /// The WASI functions bind themselves to the memory
+            /// exported as `memory` when the instance is created, and
+            /// so does the environment. An import object can thus be
+            /// used by a single instance: to reuse the environment,
+            /// generate a new import object for each instance. The
+            /// instances share the arguments, the environment
+            /// variables, the file descriptors and the captured
+            /// outputs.
+            ///
+            /// # Example
+            ///
+            /// ```rust,ignore
+            /// environment = Wasmer::Wasi::StateBuilder.new("program").finalize
+            ///
+            /// 2.times do
+            ///   import_object = environment.generate_import_object store, Wasmer::Wasi::Version::SNAPSHOT1
+            ///   Wasmer::Instance.new(module_, import_object).exports._start.()
+            /// end
+            /// ```
+            pub fn memory(&self) -> Option<Memory> {
+                x!()
+            }
+
+            /// Returns the arguments of the program, including the
+            /// program name.
+            pub fn arguments(&self) -> Array<String> {
+                x!()
+            }
+
+            /// Returns the environment variables of the program.
+            pub fn environments(&self) -> Hash<String, String> {
+                x!()
+            }
+
+            /// Returns the open file descriptors of the program, as
+            /// `Hash`es with the following keys:
+            ///
+            /// * `:fd`, the file descriptor number,
+            /// * `:name`, the name of the file or of the directory,
+            /// * `:type`, e.g. `:file`, `:directory` or
+            ///   `:character_device` for the standard streams,
+            /// * `:preopened`, whether it's a preopened directory,
+            /// * `:read`, `:write` and `:create`, the rights of the
+            ///   program on it.
+            ///
+            /// The state can't be read while the program is using
+            /// it, e.g. from a block receiving its output: a
+            /// `RuntimeError` is raised in this case.
+            pub fn file_descriptors(&self) -> Array<Hash<Symbol, Any>> {
+                x!()
+            }
+
+            /// Reads the file at `path`, as seen by the program, i.e.
+            /// in a preopened or mapped directory, or in a mounted
+            /// [`MemFS`]. It returns a binary `String`, and raises an
+            /// `IOError` if the file can't be read.
+            pub fn read_file(&self, path: String) -> String {
+                x!()
+            }
+        }
+
+        /// An in-memory filesystem, populated and read from Ruby, and
+        /// mounted into a program with [`StateBuilder::mount`].
+        ///
+        /// Paths are absolute; relative paths are resolved from
+        /// `/`. Failures raise an `IOError`.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// fs = Wasmer::Wasi::MemFS.new
+        /// fs.write "/in/data.csv", "a,b,c"
+        ///
+        /// assert { fs.directory? "/in" }
+        /// assert { fs.entries("/in") == ["data.csv"] }
+        /// assert { fs.read("/in/data.csv") == "a,b,c" }
+        /// # "#); }
+        /// ```
+        pub struct MemFS;
+
+        impl MemFS {
+            /// Creates an empty filesystem.
+            pub fn new() -> Self {
+                x!()
+            }
+
+            /// Writes `bytes` into the file at `path`, replacing its
+            /// content. The missing parent directories are created.
+            pub fn write(&self, path: String, bytes: String) {
+                x!()
+            }
+
+            /// Reads the file at `path`, as a binary `String`.
+            pub fn read(&self, path: String) -> String {
+                x!()
+            }
+
+            /// Creates the directory at `path`, and its missing
+            /// parents.
+            pub fn mkdir(&self, path: String) {
+                x!()
+            }
+
+            /// Returns the sorted names of the entries of the
+            /// directory at `path`.
+            pub fn entries(&self, path: String) -> Array<String> {
+                x!()
+            }
+
+            /// Checks whether `path` exists. In Ruby, this method is
+            /// named `exist?`.
+            pub fn exists(&self, path: String) -> Boolean {
+                x!()
+            }
+
+            /// Checks whether `path` is a file. In Ruby, this method
+            /// is named `file?`.
+            pub fn is_file(&self, path: String) -> Boolean {
+                x!()
+            }
+
+            /// Checks whether `path` is a directory. In Ruby, this
+            /// method is named `directory?`.
+            pub fn is_directory(&self, path: String) -> Boolean {
+                x!()
+            }
+
+            /// Removes the file or the empty directory at `path`.
+            pub fn remove(&self, path: String) {
+                x!()
+            }
         }
 
         /// Detect the version of WASI being used based on the import
@@ -1508,5 +3176,125 @@ pub mod Wasmer {
         pub fn get_version(module: Module, strict: Boolean) -> Version {
             x!()
         }
+
+        /// Describes how a [`Module`] uses WASI, as a `Hash` with the
+        /// following keys:
+        ///
+        /// * `:version`, the detected version, `:snapshot0` or
+        ///   `:snapshot1`, or `nil` if the module doesn't import WASI
+        ///   (see [`get_version`] with a non-strict detection),
+        /// * `:functions`, the names of the WASI functions the module
+        ///   imports from the namespace of its version,
+        /// * `:unsupported`, the [`ImportType`]s of the imports from
+        ///   the WASI namespaces that
+        ///   [`Environment::generate_import_object`] doesn't provide,
+        ///   i.e. the unknown WASI functions.
+        ///
+        /// The imports of the other namespaces, e.g. the host
+        /// functions of `env`, are ignored; they need another
+        /// [`ImportObject`] for the module to be instantiated.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # fn main() { rutie_test::test_ruby!(r#"
+        /// module_ = Wasmer::Module.new Wasmer::Store.new, (<<~WAST)
+        /// (module
+        ///   (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+        ///   (import "wasi_snapshot_preview1" "fd_teleport" (func))
+        ///   (import "env" "log" (func)))
+        /// WAST
+        ///
+        /// detection = Wasmer::Wasi.detect module_
+        ///
+        /// assert { detection[:version] == :snapshot1 }
+        /// assert { detection[:functions] == ["fd_write"] }
+        /// assert { detection[:unsupported].map(&:name) == ["fd_teleport"] }
+        /// # "#); }
+        /// ```
+        pub fn detect(module: Module) -> Hash<Symbol, Any> {
+            x!()
+        }
+
+        /// Runs the WASI program `module`, i.e. calls its `_start`
+        /// function, and returns its [`Output`]. It's a shortcut for
+        /// [`StateBuilder`], [`Environment::generate_import_object`],
+        /// [`Instance`] and the call to `_start`, with the following
+        /// keyword arguments:
+        ///
+        /// * `args`, an `Array` of arguments,
+        /// * `env`, a `Hash` of environment variables,
+        /// * `stdin`, the standard input, as a `String` or an
+        ///   `IO`-like object (see [`StateBuilder::stdin`]),
+        /// * `dirs`, an `Array` of directories to preopen, or a `Hash`
+        ///   mapping aliases to directories,
+        /// * `permissions`, a `Hash` restricting the permissions on
+        ///   all the `dirs`, with the `read`, `write` and `create`
+        ///   keys, like [`StateBuilder::preopen_directory`].
+        ///
+        /// The standard output and error are captured. A program
+        /// exiting with `proc_exit` doesn't raise: its exit code is
+        /// returned, and it is 0 if the program returns from `_start`.
+        ///
+        /// # Example
+        ///
+        /// ```rust,ignore
+        /// output = Wasmer::Wasi.run module, args: ["--verbose"], env: {"LANG" => "C"}
+        ///
+        /// puts output.stdout if output.success?
+        ///
+        /// output = Wasmer::Wasi.run module, dirs: ["inputs"], permissions: {write: false}
+        /// ```
+        pub fn run(
+            module: Module,
+            args: Option<Array<String>>,
+            env: Option<Hash<String, String>>,
+            stdin: Option<Any>,
+            dirs: Option<Any>,
+            permissions: Option<Hash<Symbol, Boolean>>,
+        ) -> Output {
+            x!()
+        }
+
+        /// The outcome of a program ran by [`run`].
+        pub struct Output;
+
+        impl Output {
+            /// Returns the exit code of the program.
+            pub fn exit_code(&self) -> Integer {
+                x!()
+            }
+
+            /// Returns the standard output, as a binary `String`.
+            pub fn stdout(&self) -> String {
+                x!()
+            }
+
+            /// Returns the standard error, as a binary `String`.
+            pub fn stderr(&self) -> String {
+                x!()
+            }
+
+            /// Checks whether the exit code is 0. In Ruby, this method
+            /// is named `success?`.
+            pub fn is_success(&self) -> Boolean {
+                x!()
+            }
+        }
+
+        /// Raised when a WASI program exits with a non-zero code
+        /// with `proc_exit`, in the middle of a call to an exported
+        /// function. Exiting with the code 0 isn't an error: the
+        /// function returns `nil`.
+        ///
+        /// It is a subclass of `RuntimeError`.
+        pub struct ExitError;
+
+        impl ExitError {
+            /// Returns the exit code of the program.
+            pub fn code(&self) -> Integer {
+                x!()
+            }
+        }
     }
 }
@@ -1,6 +1,6 @@
 //! Functions to handle error or exception correctly.
 
-use rutie::{AnyException, Exception, VM};
+use rutie::{AnyException, AnyObject, Exception, Integer, Module, NilClass, Object, RString, VM};
 
 pub type RubyResult<T> = Result<T, AnyException>;
 
@@ -40,6 +40,7 @@ macro_rules! declare_error {
 
 declare_error!(
     ArgumentError,
+    IOError,
     IndexError,
     NameError,
     RuntimeError,
@@ -53,3 +54,119 @@ where
 {
     AnyException::new(Type::name(), Some(error.to_string().as_ref()))
 }
+
+/// Creates an exception declared by this extension, e.g.
+/// `Wasmer::CompileError`, with some attributes that are readable
+/// from Ruby (see the `init` function to learn how they are
+/// declared).
+pub fn to_wasmer_err<Error>(
+    class_path: &str,
+    error: Error,
+    attributes: &[(&str, AnyObject)],
+) -> AnyException
+where
+    Error: ToString,
+{
+    let mut path = class_path.split("::").collect::<Vec<_>>();
+    let class_name = path.pop().expect("The class path must not be empty");
+    let module = path
+        .iter()
+        .skip(1)
+        .fold(Module::from_existing(path[0]), |module, nested_module| {
+            module.get_nested_module(nested_module)
+        });
+
+    let mut exception = module
+        .get_nested_class(class_name)
+        .new_instance(&[RString::new_utf8(&error.to_string()).to_any_object()]);
+
+    for (name, value) in attributes {
+        exception.instance_variable_set(&format!("@{}", name), value.clone());
+    }
+
+    unsafe { exception.to::<AnyException>() }
+}
+
+/// Creates a `Wasmer::CompileError` from a module that has failed to
+/// compile, with the given `message`. The offset is `nil` when the
+/// error isn't located in the module, e.g. when a feature isn't
+/// supported by the compiler.
+pub fn to_compile_err<Message>(message: Message, error: &wasmer::CompileError) -> AnyException
+where
+    Message: ToString,
+{
+    let (offset, reason) = match error {
+        wasmer::CompileError::Wasm(wasmer::WasmError::InvalidWebAssembly { message, offset }) => (
+            Integer::new(*offset as i64).to_any_object(),
+            message.clone(),
+        ),
+        wasmer::CompileError::Wasm(error) => (NilClass::new().to_any_object(), error.to_string()),
+        error => (NilClass::new().to_any_object(), error.to_string()),
+    };
+
+    to_wasmer_err(
+        "Wasmer::CompileError",
+        message,
+        &[
+            ("offset", offset),
+            ("reason", RString::new_utf8(&reason).to_any_object()),
+        ],
+    )
+}
+
+/// Reads the exit code of a WASI program that has called
+/// `proc_exit`, or gives the error back if it's another trap.
+pub fn to_exit_code(error: wasmer::RuntimeError) -> Result<u32, wasmer::RuntimeError> {
+    if !error.is::<wasmer_wasi::WasiError>() {
+        return Err(error);
+    }
+
+    match error.downcast::<wasmer_wasi::WasiError>() {
+        Ok(wasmer_wasi::WasiError::Exit(code)) => Ok(code),
+        Ok(error) => Err(wasmer::RuntimeError::new(error.to_string())),
+        Err(error) => Err(error),
+    }
+}
+
+/// Creates a `Wasmer::Wasi::ExitError` for a WASI program that has
+/// exited with a non-zero `code`.
+pub fn to_exit_err(code: u32) -> AnyException {
+    to_wasmer_err(
+        "Wasmer::Wasi::ExitError",
+        format!("The WASI program has exited with the code {}", code),
+        &[("code", Integer::from(code).to_any_object())],
+    )
+}
+
+/// Creates a `RuntimeError` from a WebAssembly trap. The message
+/// contains the WebAssembly backtrace, where the functions are named
+/// after the `name` custom section, or after their index, e.g.
+/// `func[3]`, when they are unnamed.
+///
+/// If a WASI program has exited with `proc_exit`, a
+/// `Wasmer::Wasi::ExitError` is created instead.
+pub fn to_trap_err(error: wasmer::RuntimeError) -> AnyException {
+    let error = match to_exit_code(error) {
+        Ok(code) => return to_exit_err(code),
+        Err(error) => error,
+    };
+
+    let mut message = format!("RuntimeError: {}", error.message());
+
+    for frame in error.trace() {
+        let function_name = match frame.function_name() {
+            Some(name) => name.to_string(),
+            None => format!("func[{}]", frame.func_index()),
+        };
+
+        message.push_str(&format!(
+            "\n    at {} ({}[{}]:0x{:x})",
+            function_name,
+            frame.module_name(),
+            frame.func_index(),
+            frame.module_offset()
+        ));
+    }
+
+    to_ruby_err::<RuntimeError, _>(message)
+}
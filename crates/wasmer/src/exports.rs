@@ -1,18 +1,44 @@
 use crate::{
     error::{to_ruby_err, RuntimeError},
+    externals::function::FunctionNames,
+    gc::Roots,
     prelude::*,
 };
 use rutie::{Boolean, Fixnum, Symbol};
-use std::convert::TryInto;
+use std::{collections::HashMap, convert::TryInto};
 
-#[rubyclass(module = "Wasmer")]
+#[rubyclass(module = "Wasmer", mark)]
 pub struct Exports {
     inner: wasmer::Exports,
+    function_names: HashMap<String, String>,
+    names: FunctionNames,
+    roots: Roots,
+}
+
+impl Mark for Exports {
+    fn mark(&self) {
+        self.roots.mark();
+    }
 }
 
 impl Exports {
-    pub fn new(inner: wasmer::Exports) -> Self {
-        Self { inner }
+    /// Creates the exports of an instance. `function_names` maps the
+    /// names of the exported functions to their names in the module,
+    /// `names` names the functions read from its tables, globals or
+    /// returned by its functions, and `roots` are the Ruby objects
+    /// held by the instance, which the exports keep alive.
+    pub fn new(
+        inner: wasmer::Exports,
+        function_names: HashMap<String, String>,
+        names: FunctionNames,
+        roots: Roots,
+    ) -> Self {
+        Self {
+            inner,
+            function_names,
+            names,
+            roots,
+        }
     }
 
     pub(crate) fn inner(&self) -> &wasmer::Exports {
@@ -84,17 +110,38 @@ pub(crate) mod ruby_exports_extra {
 
             Ok(match exports.inner().get_extern(extern_name) {
                 Some(wasmer::Extern::Function(function)) => {
-                    Function::ruby_new(Function::raw_new(function.clone())).to_any_object()
+                    Function::ruby_new(match exports.function_names.get(extern_name) {
+                        Some(name) => Function::raw_new_with_name(
+                            function.clone(),
+                            name.clone(),
+                            exports.names.clone(),
+                            exports.roots.clone(),
+                        ),
+                        None => Function::raw_new_with_names(
+                            function.clone(),
+                            &exports.names,
+                            &exports.roots,
+                        ),
+                    })
+                    .to_any_object()
                 }
                 Some(wasmer::Extern::Memory(memory)) => {
                     Memory::ruby_new(Memory::raw_new(memory.clone())).to_any_object()
                 }
                 Some(wasmer::Extern::Global(global)) => {
-                    Global::ruby_new(Global::raw_new(global.clone())).to_any_object()
-                }
-                Some(wasmer::Extern::Table(table)) => {
-                    Table::ruby_new(Table::raw_new(table.clone())).to_any_object()
+                    Global::ruby_new(Global::raw_new_with_names(
+                        global.clone(),
+                        exports.names.clone(),
+                        exports.roots.clone(),
+                    ))
+                    .to_any_object()
                 }
+                Some(wasmer::Extern::Table(table)) => Table::ruby_new(Table::raw_new_with_names(
+                    table.clone(),
+                    exports.names.clone(),
+                    exports.roots.clone(),
+                ))
+                .to_any_object(),
                 None => {
                     return Err(to_ruby_err::<NameError, _>(format!(
                         "Export `{}` does not exist",
@@ -1,12 +1,14 @@
 use crate::{
     error::{to_ruby_err, TypeError},
+    gc::Roots,
     prelude::*,
     store::Store,
     types::FunctionType,
     values::{to_ruby_object, to_wasm_value},
 };
-use rutie::{util::is_method, AnyObject, Array, Object, Proc, Symbol};
-use std::sync::Arc;
+use rutie::{util::is_method, AnyObject, Array, NilClass, Object, Proc, RString, Symbol};
+use std::{collections::HashMap, sync::Arc};
+use wasmer::Exportable;
 
 #[derive(Clone)]
 struct Callable(Arc<dyn Fn(&[AnyObject]) -> AnyObject>);
@@ -14,19 +16,97 @@ struct Callable(Arc<dyn Fn(&[AnyObject]) -> AnyObject>);
 unsafe impl Send for Callable {}
 unsafe impl Sync for Callable {}
 
-#[rubyclass(module = "Wasmer")]
+/// The names of the functions defined by a module, indexed by the
+/// address of their compiled body. They name the functions whose
+/// index isn't known, e.g. the ones read from a table.
+#[derive(Clone, Default)]
+pub(crate) struct FunctionNames(Option<Arc<HashMap<usize, String>>>);
+
+impl FunctionNames {
+    pub(crate) fn new(names: HashMap<usize, String>) -> Self {
+        Self(Some(Arc::new(names)))
+    }
+
+    /// Returns the name of `function`, if it's defined by the module.
+    fn get(&self, function: &wasmer::Function) -> Option<String> {
+        let names = self.0.as_ref()?;
+
+        match function.to_export() {
+            wasmer::Export::Function(function) => {
+                names.get(&(function.vm_function.address as usize)).cloned()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[rubyclass(module = "Wasmer", mark)]
 pub struct Function {
     inner: wasmer::Function,
+    name: Option<String>,
+    names: FunctionNames,
+    /// The Ruby objects held by the instance of the function, e.g.
+    /// by its WASI imports.
+    roots: Roots,
+}
+
+impl Mark for Function {
+    fn mark(&self) {
+        self.roots.mark();
+    }
 }
 
 impl Function {
-    pub fn raw_new(inner: wasmer::Function) -> Self {
-        Self { inner }
+    /// Creates a host function, which keeps the `roots` of its
+    /// store alive.
+    pub fn raw_new(inner: wasmer::Function, roots: &Roots) -> Self {
+        Self {
+            inner,
+            name: None,
+            names: FunctionNames::default(),
+            roots: roots.clone(),
+        }
+    }
+
+    /// Creates a function that is named after the `name` custom
+    /// section of its module. The functions it returns are named
+    /// after `names`, and `roots` are kept alive.
+    pub(crate) fn raw_new_with_name(
+        inner: wasmer::Function,
+        name: String,
+        names: FunctionNames,
+        roots: Roots,
+    ) -> Self {
+        Self {
+            inner,
+            name: Some(name),
+            names,
+            roots,
+        }
+    }
+
+    /// Creates a function that is named after `names`, if it's
+    /// defined by their module, and keeps `roots` alive.
+    pub(crate) fn raw_new_with_names(
+        inner: wasmer::Function,
+        names: &FunctionNames,
+        roots: &Roots,
+    ) -> Self {
+        Self {
+            name: names.get(&inner),
+            inner,
+            names: names.clone(),
+            roots: roots.clone(),
+        }
     }
 
     pub(crate) fn inner(&self) -> &wasmer::Function {
         &self.inner
     }
+
+    pub(crate) fn roots(&self) -> &Roots {
+        &self.roots
+    }
 }
 
 #[rubymethods]
@@ -56,11 +136,13 @@ impl Function {
         struct Environment {
             ruby_callable: Callable,
             result_types: Vec<wasmer::Type>,
+            roots: Roots,
         }
 
         let environment = Environment {
             ruby_callable: function,
             result_types: function_type.results().to_vec(),
+            roots: store.roots().clone(),
         };
 
         let host_function = wasmer::Function::new_with_env(
@@ -70,7 +152,12 @@ impl Function {
             |environment,
              arguments: &[wasmer::Value]|
              -> Result<Vec<wasmer::Value>, wasmer::RuntimeError> {
-                let arguments = arguments.iter().map(to_ruby_object).collect::<Vec<_>>();
+                let names = FunctionNames::default();
+                let roots = &environment.roots;
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| to_ruby_object(argument, &names, roots))
+                    .collect::<Vec<_>>();
 
                 let ruby_callable = &environment.ruby_callable.0;
                 let results = ruby_callable(&arguments);
@@ -82,11 +169,11 @@ impl Function {
                     results
                         .into_iter()
                         .zip(result_types)
-                        .map(|(value, ty)| to_wasm_value((&value, *ty)))
+                        .map(|(value, ty)| to_wasm_value((&value, *ty), roots))
                         .collect::<RubyResult<_>>()
                         .map_err(|error| wasmer::RuntimeError::new(error.to_string()))?
                 } else if !results.is_nil() && has_result_types {
-                    vec![to_wasm_value((&results, result_types[0]))
+                    vec![to_wasm_value((&results, result_types[0]), roots)
                         .map_err(|error| wasmer::RuntimeError::new(error.to_string()))?]
                 } else {
                     Vec::new()
@@ -94,19 +181,57 @@ impl Function {
             },
         );
 
-        Ok(Function::ruby_new(Function {
-            inner: host_function,
-        }))
+        Ok(Function::ruby_new(Function::raw_new(
+            host_function,
+            store.roots(),
+        )))
     }
 
     pub fn r#type(&self) -> RubyResult<AnyObject> {
         Ok(FunctionType::ruby_new(self.inner().ty().into()))
     }
+
+    pub fn name(&self) -> RubyResult<AnyObject> {
+        Ok(match &self.name {
+            Some(name) => RString::new_utf8(name).to_any_object(),
+            None => NilClass::new().to_any_object(),
+        })
+    }
+
+    pub fn inspect(&self) -> RubyResult<RString> {
+        let function_type = self.inner().ty();
+        let types =
+            |types: &[wasmer::Type]| types.iter().map(type_name).collect::<Vec<_>>().join(", ");
+        let signature = format!(
+            "({}) -> ({})",
+            types(function_type.params()),
+            types(function_type.results())
+        );
+
+        Ok(RString::new_utf8(&match &self.name {
+            Some(name) => format!("#<Wasmer::Function {}: {}>", name, signature),
+            None => format!("#<Wasmer::Function {}>", signature),
+        }))
+    }
+}
+
+/// Returns the name of a type, as written in the WebAssembly text
+/// format.
+fn type_name(ty: &wasmer::Type) -> &'static str {
+    match ty {
+        wasmer::Type::I32 => "i32",
+        wasmer::Type::I64 => "i64",
+        wasmer::Type::F32 => "f32",
+        wasmer::Type::F64 => "f64",
+        wasmer::Type::V128 => "v128",
+        wasmer::Type::ExternRef => "externref",
+        wasmer::Type::FuncRef => "funcref",
+    }
 }
 
 pub(crate) mod ruby_function_extra {
     use crate::{
-        error::{to_ruby_err, unwrap_or_raise, RubyResult, RuntimeError},
+        error::{to_exit_code, to_exit_err, to_trap_err, unwrap_or_raise, RubyResult},
         values::{to_ruby_object, to_wasm_value},
     };
     use rutie::{
@@ -136,21 +261,25 @@ pub(crate) mod ruby_function_extra {
             let arguments: Vec<wasmer::Value> = Array::from(arguments)
                 .into_iter()
                 .zip(function.inner().ty().params())
-                .map(|(value, ty)| to_wasm_value((&value, *ty)))
+                .map(|(value, ty)| to_wasm_value((&value, *ty), &function.roots))
                 .collect::<RubyResult<_>>()?;
 
-            let results = function
-                .inner()
-                .call(&arguments)
-                .map(<[_]>::into_vec)
-                .map_err(to_ruby_err::<RuntimeError, _>)?;
+            let results = match function.inner().call(&arguments) {
+                Ok(results) => results.into_vec(),
+                // A WASI program exiting with the code 0 succeeded.
+                Err(error) => match to_exit_code(error) {
+                    Ok(0) => Vec::new(),
+                    Ok(code) => return Err(to_exit_err(code)),
+                    Err(error) => return Err(to_trap_err(error)),
+                },
+            };
 
             Ok(match results.len() {
                 0 => NilClass::new().to_any_object(),
-                1 => to_ruby_object(&results[0]),
+                1 => to_ruby_object(&results[0], &function.names, &function.roots),
                 _ => results
                     .iter()
-                    .map(to_ruby_object)
+                    .map(|result| to_ruby_object(result, &function.names, &function.roots))
                     .collect::<Array>()
                     .to_any_object(),
             })
@@ -1,5 +1,7 @@
 use crate::{
     error::{to_ruby_err, RuntimeError},
+    externals::function::FunctionNames,
+    gc::Roots,
     prelude::*,
     store::Store,
     types::GlobalType,
@@ -7,14 +9,48 @@ use crate::{
 };
 use rutie::{AnyObject, Boolean, NilClass};
 
-#[rubyclass(module = "Wasmer")]
+#[rubyclass(module = "Wasmer", mark)]
 pub struct Global {
     inner: wasmer::Global,
+    names: FunctionNames,
+    /// The Ruby objects held by the store or the instance of the
+    /// global, e.g. the ones of its `externref`s.
+    roots: Roots,
+}
+
+impl Mark for Global {
+    fn mark(&self) {
+        self.roots.mark();
+    }
 }
 
 impl Global {
-    pub fn raw_new(inner: wasmer::Global) -> Self {
-        Self { inner }
+    /// Creates a global which keeps the `roots` of its store alive.
+    pub fn raw_new(inner: wasmer::Global, roots: &Roots) -> Self {
+        Self {
+            inner,
+            names: FunctionNames::default(),
+            roots: roots.clone(),
+        }
+    }
+
+    /// Creates a global whose `funcref` value is named after `names`,
+    /// if its function is defined by their module, and which keeps
+    /// `roots` alive.
+    pub(crate) fn raw_new_with_names(
+        inner: wasmer::Global,
+        names: FunctionNames,
+        roots: Roots,
+    ) -> Self {
+        Self {
+            inner,
+            names,
+            roots,
+        }
+    }
+
+    pub(crate) fn roots(&self) -> &Roots {
+        &self.roots
     }
 
     pub(crate) fn inner(&self) -> &wasmer::Global {
@@ -25,11 +61,14 @@ impl Global {
 #[rubymethods]
 impl Global {
     pub fn new(store: &Store, value: &Value, mutable: &Boolean) -> RubyResult<AnyObject> {
-        Ok(Global::ruby_new(Global::raw_new(if mutable.to_bool() {
-            wasmer::Global::new_mut(store.inner(), value.inner().clone())
-        } else {
-            wasmer::Global::new(store.inner(), value.inner().clone())
-        })))
+        Ok(Global::ruby_new(Global::raw_new(
+            if mutable.to_bool() {
+                wasmer::Global::new_mut(store.inner(), value.inner().clone())
+            } else {
+                wasmer::Global::new(store.inner(), value.inner().clone())
+            },
+            store.roots(),
+        )))
     }
 
     pub fn mutable(&self) -> RubyResult<Boolean> {
@@ -37,7 +76,7 @@ impl Global {
     }
 
     pub fn get_value(&self) -> RubyResult<AnyObject> {
-        Ok(to_ruby_object(&self.inner.get()))
+        Ok(to_ruby_object(&self.inner.get(), &self.names, &self.roots))
     }
 
     pub fn set_value(&self, value: &AnyObject) -> RubyResult<NilClass> {
@@ -50,7 +89,7 @@ impl Global {
         }
 
         self.inner()
-            .set(to_wasm_value((value, ty.ty))?)
+            .set(to_wasm_value((value, ty.ty), &self.roots)?)
             .map_err(to_ruby_err::<RuntimeError, _>)?;
 
         Ok(NilClass::new())
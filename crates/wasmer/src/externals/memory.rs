@@ -1,12 +1,19 @@
 use crate::{
-    error::{to_ruby_err, ArgumentError, RuntimeError},
-    memory::views::{Int16Array, Int32Array, Int8Array, Uint16Array, Uint32Array, Uint8Array},
+    error::{to_ruby_err, ArgumentError, IndexError, RuntimeError, TypeError},
+    memory::{
+        atomics,
+        views::{Int16Array, Int32Array, Int8Array, Uint16Array, Uint32Array, Uint8Array},
+    },
     prelude::*,
     store::Store,
     types::MemoryType,
 };
-use rutie::{AnyObject, Fixnum, Integer, Object};
-use std::convert::{TryFrom, TryInto};
+use rutie::{AnyObject, Fixnum, Float, Integer, NilClass, Object, Symbol, Thread};
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 #[rubyclass(module = "Wasmer")]
 pub struct Memory {
@@ -35,9 +42,55 @@ fn unwrap_offset(offset: &AnyObject) -> RubyResult<usize> {
     })
 }
 
+fn unwrap_atomic_offset(offset: &Integer) -> RubyResult<usize> {
+    offset
+        .to_u64()
+        .try_into()
+        .map_err(to_ruby_err::<ArgumentError, _>)
+}
+
+fn unwrap_timeout(timeout: Option<&AnyObject>) -> RubyResult<Option<Duration>> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return Ok(None),
+    };
+
+    let seconds = if let Ok(seconds) = timeout.try_convert_to::<Float>() {
+        seconds.to_f64()
+    } else if let Ok(seconds) = timeout.try_convert_to::<Integer>() {
+        seconds.to_i64() as f64
+    } else {
+        return Err(to_ruby_err::<TypeError, _>(
+            "The timeout must be a number of seconds (`Integer` or `Float`)",
+        ));
+    };
+
+    if seconds < 0.0 || !seconds.is_finite() {
+        return Err(to_ruby_err::<ArgumentError, _>(
+            "The timeout must be a positive number of seconds",
+        ));
+    }
+
+    Ok(Some(Duration::from_secs_f64(seconds)))
+}
+
 #[rubymethods]
 impl Memory {
     pub fn new(store: &Store, memory_type: &MemoryType) -> RubyResult<AnyObject> {
+        if memory_type.shared {
+            if !store.features().threads {
+                return Err(to_ruby_err::<RuntimeError, _>(
+                    "Shared memories require the threads feature, use `Store.new(threads: true)`",
+                ));
+            }
+
+            if memory_type.maximum.is_none() {
+                return Err(to_ruby_err::<ArgumentError, _>(
+                    "Shared memories must have a maximum size",
+                ));
+            }
+        }
+
         Ok(Memory::ruby_new(Memory::raw_new(
             wasmer::Memory::new(store.inner(), memory_type.into())
                 .map_err(to_ruby_err::<RuntimeError, _>)?,
@@ -114,4 +167,62 @@ impl Memory {
             unwrap_offset(offset)?,
         )))
     }
+
+    pub fn atomic_load(&self, offset: &Integer) -> RubyResult<Integer> {
+        let cell = atomics::cell(self.inner(), unwrap_atomic_offset(offset)?)
+            .map_err(to_ruby_err::<IndexError, _>)?;
+
+        Ok(Integer::new(cell.load(Ordering::SeqCst).into()))
+    }
+
+    pub fn atomic_store(&self, offset: &Integer, value: &Integer) -> RubyResult<NilClass> {
+        let value = u32::try_from(value.to_u64()).map_err(to_ruby_err::<ArgumentError, _>)?;
+        let cell = atomics::cell(self.inner(), unwrap_atomic_offset(offset)?)
+            .map_err(to_ruby_err::<IndexError, _>)?;
+
+        cell.store(value, Ordering::SeqCst);
+
+        Ok(NilClass::new())
+    }
+
+    pub fn atomic_wait(
+        &self,
+        offset: &Integer,
+        expected: &Integer,
+        timeout: Option<&AnyObject>,
+    ) -> RubyResult<Symbol> {
+        if !self.inner().ty().shared {
+            return Err(to_ruby_err::<RuntimeError, _>(
+                "`Memory#atomic_wait` can only be used on a shared memory",
+            ));
+        }
+
+        let expected = u32::try_from(expected.to_u64()).map_err(to_ruby_err::<ArgumentError, _>)?;
+        let timeout = unwrap_timeout(timeout)?;
+        let cell = atomics::cell(self.inner(), unwrap_atomic_offset(offset)?)
+            .map_err(to_ruby_err::<IndexError, _>)?;
+        let interrupted = AtomicBool::new(false);
+
+        // Release the GVL so that other Ruby threads can run, and
+        // notify us, while we are waiting.
+        let result = Thread::call_without_gvl(
+            || atomics::wait(cell, expected, timeout, &interrupted),
+            Some(|| atomics::interrupt(cell, &interrupted)),
+        );
+
+        Ok(Symbol::new(result.name()))
+    }
+
+    pub fn atomic_notify(&self, offset: &Integer, count: Option<&Integer>) -> RubyResult<Integer> {
+        let count = match count {
+            Some(count) => {
+                u32::try_from(count.to_u64()).map_err(to_ruby_err::<ArgumentError, _>)?
+            }
+            None => u32::MAX,
+        };
+        let cell = atomics::cell(self.inner(), unwrap_atomic_offset(offset)?)
+            .map_err(to_ruby_err::<IndexError, _>)?;
+
+        Ok(Integer::new(atomics::notify(cell, count).into()))
+    }
 }
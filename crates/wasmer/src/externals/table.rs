@@ -1,25 +1,100 @@
 use crate::{
-    error::{to_ruby_err, RuntimeError},
+    error::{to_ruby_err, IndexError, RuntimeError},
+    externals::function::FunctionNames,
+    gc::Roots,
     prelude::*,
     store::Store,
     types::TableType,
-    values::Value,
+    values::{to_ruby_object, to_wasm_value, RubyValue},
 };
-use rutie::AnyObject;
+use rutie::{AnyObject, Integer, NilClass, Object, Symbol, VM};
+use std::convert::TryFrom;
 
-#[rubyclass(module = "Wasmer")]
+#[rubyclass(module = "Wasmer", mark)]
 pub struct Table {
     inner: wasmer::Table,
+    names: FunctionNames,
+    /// The Ruby objects held by the store or the instance of the
+    /// table, e.g. the ones of its `externref`s.
+    roots: Roots,
+}
+
+impl Mark for Table {
+    fn mark(&self) {
+        self.roots.mark();
+    }
 }
 
 impl Table {
-    pub fn raw_new(inner: wasmer::Table) -> Self {
-        Self { inner }
+    /// Creates a table which keeps the `roots` of its store alive.
+    pub fn raw_new(inner: wasmer::Table, roots: &Roots) -> Self {
+        Self {
+            inner,
+            names: FunctionNames::default(),
+            roots: roots.clone(),
+        }
+    }
+
+    /// Creates a table whose functions are named after `names`, if
+    /// they are defined by their module, and which keeps `roots`
+    /// alive.
+    pub(crate) fn raw_new_with_names(
+        inner: wasmer::Table,
+        names: FunctionNames,
+        roots: Roots,
+    ) -> Self {
+        Self {
+            inner,
+            names,
+            roots,
+        }
+    }
+
+    pub(crate) fn roots(&self) -> &Roots {
+        &self.roots
     }
 
     pub(crate) fn inner(&self) -> &wasmer::Table {
         &self.inner
     }
+
+    /// Checks that `index` is in the bounds of the table.
+    fn unwrap_index(&self, index: &Integer) -> RubyResult<u32> {
+        let size = self.inner().size();
+        let index = index.to_i64();
+
+        match u32::try_from(index) {
+            Ok(index) if index < size => Ok(index),
+            _ => Err(to_ruby_err::<IndexError, _>(format!(
+                "Out of bound: Index {} is out of the table of size {}",
+                index, size
+            ))),
+        }
+    }
+}
+
+/// Converts `value` to an element of a table of type `ty`. It can be
+/// a `Value`, or a Ruby object that is converted according to `ty`.
+/// `None` stands for a null reference. The object of an `externref`
+/// is held by `roots`.
+fn to_element(
+    value: Option<&AnyObject>,
+    ty: wasmer::Type,
+    roots: &Roots,
+) -> RubyResult<wasmer::Value> {
+    let value = match value {
+        Some(value) => value.clone(),
+        None => NilClass::new().to_any_object(),
+    };
+
+    match value.try_convert_to::<RubyValue>() {
+        Ok(value) => Ok(value.upcast().inner().clone()),
+        Err(_) => to_wasm_value((&value, ty), roots),
+    }
+}
+
+fn unwrap_u32(value: &Integer) -> RubyResult<u32> {
+    u32::try_from(value.to_i64()).map_err(to_ruby_err::<IndexError, _>)
 }
 
 #[rubymethods]
@@ -27,15 +102,90 @@ impl Table {
     pub fn new(
         store: &Store,
         table_type: &TableType,
-        initial_value: &Value,
+        initial_value: Option<&AnyObject>,
     ) -> RubyResult<AnyObject> {
+        let table_type: wasmer::TableType = table_type.into();
+        let initial_value = to_element(initial_value, table_type.ty, store.roots())?;
+
         Ok(Table::ruby_new(Table::raw_new(
-            wasmer::Table::new(
-                store.inner(),
-                table_type.into(),
-                initial_value.inner().clone(),
-            )
-            .map_err(to_ruby_err::<RuntimeError, _>)?,
+            wasmer::Table::new(store.inner(), table_type, initial_value)
+                .map_err(to_ruby_err::<RuntimeError, _>)?,
+            store.roots(),
         )))
     }
+
+    pub fn size(&self) -> RubyResult<Integer> {
+        Ok(Integer::new(self.inner().size().into()))
+    }
+
+    pub fn r#type(&self) -> RubyResult<AnyObject> {
+        Ok(TableType::ruby_new(self.inner().ty().into()))
+    }
+
+    pub fn get(&self, index: &Integer) -> RubyResult<AnyObject> {
+        let index = self.unwrap_index(index)?;
+
+        Ok(match self.inner().get(index) {
+            Some(value) => to_ruby_object(&value, &self.names, &self.roots),
+            None => NilClass::new().to_any_object(),
+        })
+    }
+
+    pub fn set(&self, index: &Integer, value: &AnyObject) -> RubyResult<NilClass> {
+        let index = self.unwrap_index(index)?;
+        let value = to_element(Some(value), self.inner().ty().ty, &self.roots)?;
+
+        self.inner()
+            .set(index, value)
+            .map_err(to_ruby_err::<RuntimeError, _>)?;
+
+        Ok(NilClass::new())
+    }
+
+    pub fn grow(&self, delta: &Integer, initial_value: Option<&AnyObject>) -> RubyResult<Integer> {
+        let delta = unwrap_u32(delta)?;
+        let initial_value = to_element(initial_value, self.inner().ty().ty, &self.roots)?;
+
+        Ok(Integer::new(
+            self.inner()
+                .grow(delta, initial_value)
+                .map_err(to_ruby_err::<RuntimeError, _>)?
+                .into(),
+        ))
+    }
+
+    pub fn each(&self) -> RubyResult<AnyObject> {
+        if !VM::is_block_given() {
+            return _ruby_self.protect_send("enum_for", &[Symbol::new("each").to_any_object()]);
+        }
+
+        let table = self.inner();
+
+        for index in 0..table.size() {
+            if let Some(value) = table.get(index) {
+                VM::yield_object(to_ruby_object(&value, &self.names, &self.roots));
+            }
+        }
+
+        Ok(_ruby_self.to_any_object())
+    }
+
+    pub fn copy(
+        &self,
+        destination_index: &Integer,
+        source: &Table,
+        source_index: &Integer,
+        length: &Integer,
+    ) -> RubyResult<NilClass> {
+        wasmer::Table::copy(
+            self.inner(),
+            unwrap_u32(destination_index)?,
+            source.inner(),
+            unwrap_u32(source_index)?,
+            unwrap_u32(length)?,
+        )
+        .map_err(to_ruby_err::<IndexError, _>)?;
+
+        Ok(NilClass::new())
+    }
 }
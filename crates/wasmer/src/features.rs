@@ -0,0 +1,196 @@
+use crate::{options, prelude::*};
+use rutie::{AnyObject, Boolean, Hash, Object, Symbol};
+use wasmer::wasmparser::{BinaryReaderError, Parser, ValidPayload, Validator, WasmFeatures};
+
+const FEATURE_NAMES: &[&str] = &[
+    "threads",
+    "reference_types",
+    "simd",
+    "bulk_memory",
+    "multi_value",
+    "tail_call",
+    "module_linking",
+    "multi_memory",
+    "memory64",
+];
+
+#[rubyclass(module = "Wasmer")]
+pub struct Features {
+    inner: wasmer::Features,
+}
+
+impl Features {
+    pub(crate) fn raw_new(inner: wasmer::Features) -> Self {
+        Self { inner }
+    }
+
+    pub(crate) fn inner(&self) -> &wasmer::Features {
+        &self.inner
+    }
+
+    fn pairs(&self) -> Vec<(&'static str, bool)> {
+        let features = self.inner();
+
+        vec![
+            ("threads", features.threads),
+            ("reference_types", features.reference_types),
+            ("simd", features.simd),
+            ("bulk_memory", features.bulk_memory),
+            ("multi_value", features.multi_value),
+            ("tail_call", features.tail_call),
+            ("module_linking", features.module_linking),
+            ("multi_memory", features.multi_memory),
+            ("memory64", features.memory64),
+        ]
+    }
+}
+
+/// Reads a `Features` object, as received from a keyword argument.
+pub(crate) fn unwrap_features(features: &AnyObject) -> RubyResult<wasmer::Features> {
+    Ok(features
+        .try_convert_to::<RubyFeatures>()?
+        .upcast()
+        .inner()
+        .clone())
+}
+
+fn validator(features: &wasmer::Features) -> Validator {
+    let mut validator = Validator::new();
+
+    validator.wasm_features(WasmFeatures {
+        threads: features.threads,
+        reference_types: features.reference_types,
+        simd: features.simd,
+        bulk_memory: features.bulk_memory,
+        multi_value: features.multi_value,
+        tail_call: features.tail_call,
+        module_linking: features.module_linking,
+        multi_memory: features.multi_memory,
+        memory64: features.memory64,
+        ..Default::default()
+    });
+
+    validator
+}
+
+/// Validates `bytes` against the given `features`, without compiling
+/// them.
+pub(crate) fn validate(features: &wasmer::Features, bytes: &[u8]) -> Result<(), BinaryReaderError> {
+    validator(features).validate_all(bytes)
+}
+
+/// Validates `bytes` against the given `features`, and collects the
+/// errors sorted by offset. An error in a section stops the
+/// validation, but function bodies are validated independently from
+/// each other, so that all the invalid functions are reported.
+pub(crate) fn validation_errors(
+    features: &wasmer::Features,
+    bytes: &[u8],
+) -> Vec<BinaryReaderError> {
+    let mut validator = validator(features);
+    let mut functions_to_validate = Vec::new();
+    let mut errors = Vec::new();
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload.and_then(|payload| validator.payload(&payload)) {
+            Ok(ValidPayload::Func(function_validator, body)) => {
+                functions_to_validate.push((function_validator, body))
+            }
+            Ok(_) => (),
+            Err(error) => {
+                errors.push(error);
+
+                break;
+            }
+        }
+    }
+
+    for (mut function_validator, body) in functions_to_validate {
+        if let Err(error) = function_validator.validate(&body) {
+            errors.push(error);
+        }
+    }
+
+    errors.sort_by_key(|error| error.offset());
+
+    errors
+}
+
+#[rubymethods]
+impl Features {
+    pub fn new(options: Option<&Hash>) -> RubyResult<AnyObject> {
+        options::check_keys(options, FEATURE_NAMES)?;
+
+        let mut features = wasmer::Features::default();
+
+        macro_rules! set {
+            ( $( $name:ident ),* ) => {
+                $(
+                    if let Some(enable) = options::get_bool(options, stringify!($name))? {
+                        features.$name = enable;
+                    }
+                )*
+            };
+        }
+
+        set!(
+            threads,
+            reference_types,
+            simd,
+            bulk_memory,
+            multi_value,
+            tail_call,
+            module_linking,
+            multi_memory,
+            memory64
+        );
+
+        Ok(Features::ruby_new(Features::raw_new(features)))
+    }
+
+    pub fn threads(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().threads))
+    }
+
+    pub fn reference_types(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().reference_types))
+    }
+
+    pub fn simd(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().simd))
+    }
+
+    pub fn bulk_memory(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().bulk_memory))
+    }
+
+    pub fn multi_value(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().multi_value))
+    }
+
+    pub fn tail_call(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().tail_call))
+    }
+
+    pub fn module_linking(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().module_linking))
+    }
+
+    pub fn multi_memory(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().multi_memory))
+    }
+
+    pub fn memory64(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner().memory64))
+    }
+
+    pub fn to_h(&self) -> RubyResult<Hash> {
+        let mut hash = Hash::new();
+
+        for (name, enabled) in self.pairs() {
+            hash.store(Symbol::new(name), Boolean::new(enabled));
+        }
+
+        Ok(hash)
+    }
+}
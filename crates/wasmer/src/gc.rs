@@ -0,0 +1,133 @@
+//! Keeps the Ruby objects held by Rust alive, since the garbage
+//! collector cannot see them.
+
+use rutie::{AnyObject, Object, GC};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The Ruby objects held by Rust on behalf of some Ruby objects, the
+/// owners of the registry, e.g. the blocks given to a WASI
+/// `StateBuilder`, which are held by the WASI state and thus by the
+/// `Environment`, the imports and the instance using it, or the
+/// objects of the `externref`s of a `Store`.
+///
+/// The owners mark the registry when the garbage collector marks
+/// them, see `#[rubyclass(mark)]`, so the objects live as long as one
+/// of their owners does. Unlike a global root, a cycle going through
+/// Rust, e.g. a block capturing its owner, is collected.
+#[derive(Clone, Default)]
+pub(crate) struct Roots(Arc<Mutex<Registry>>);
+
+#[derive(Default)]
+struct Registry {
+    next_id: usize,
+    objects: HashMap<usize, AnyObject>,
+    /// The registries marked along with this one, e.g. the ones of
+    /// the imports of an instance.
+    links: Vec<Roots>,
+}
+
+impl Roots {
+    /// Holds `object` until the returned `Held` is dropped.
+    pub(crate) fn hold(&self, object: &impl Object) -> Held {
+        let object = object.to_any_object();
+        let mut registry = self.0.lock().unwrap();
+        let id = registry.next_id;
+
+        registry.next_id += 1;
+        registry.objects.insert(id, object.clone());
+
+        Held {
+            id,
+            object,
+            roots: self.clone(),
+        }
+    }
+
+    /// Marks the objects of `other` whenever this registry is marked.
+    pub(crate) fn link(&self, other: &Roots) {
+        if Arc::ptr_eq(&self.0, &other.0) {
+            return;
+        }
+
+        let mut registry = self.0.lock().unwrap();
+
+        if !registry
+            .links
+            .iter()
+            .any(|link| Arc::ptr_eq(&link.0, &other.0))
+        {
+            registry.links.push(other.clone());
+        }
+    }
+
+    /// Creates a registry marking the objects of all of `others`.
+    pub(crate) fn linked<'a>(others: impl IntoIterator<Item = &'a Roots>) -> Self {
+        let roots = Self::default();
+
+        for other in others {
+            roots.link(other);
+        }
+
+        roots
+    }
+
+    /// Marks the held objects, and the ones of the linked registries.
+    pub(crate) fn mark(&self) {
+        self.mark_once(&mut Vec::new());
+    }
+
+    /// Marks the registry unless it's already `visited`, since the
+    /// links may form a cycle, e.g. when the exports of an instance
+    /// are registered in the imports it has been created with.
+    fn mark_once(&self, visited: &mut Vec<*const Mutex<Registry>>) {
+        let pointer = Arc::as_ptr(&self.0);
+
+        if visited.contains(&pointer) {
+            return;
+        }
+
+        visited.push(pointer);
+
+        let registry = self.0.lock().unwrap();
+
+        for object in registry.objects.values() {
+            GC::mark(object);
+        }
+
+        for link in &registry.links {
+            link.mark_once(visited);
+        }
+    }
+}
+
+/// A Ruby object held by a `Roots` registry, which releases it when
+/// the `Held` is dropped.
+pub(crate) struct Held {
+    id: usize,
+    object: AnyObject,
+    roots: Roots,
+}
+
+impl Held {
+    pub(crate) fn object(&self) -> AnyObject {
+        self.object.clone()
+    }
+}
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        self.roots.0.lock().unwrap().objects.remove(&self.id);
+    }
+}
+
+// SAFETY: Wasmer requires the data of an `externref`, and the state
+// of a WASI program, to be `Send` and `Sync`, but the instances and
+// the programs never leave the Ruby thread calling them. A `Held`,
+// and its registry, are thus only created, read and dropped while the
+// Global VM Lock is held.
+unsafe impl Send for Held {}
+unsafe impl Sync for Held {}
+unsafe impl Send for Registry {}
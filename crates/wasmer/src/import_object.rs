@@ -1,31 +1,151 @@
 use crate::{
-    error::{to_ruby_err, unwrap_or_raise, TypeError},
+    error::{to_ruby_err, unwrap_or_raise, RuntimeError, TypeError},
     externals::{function::RubyFunction, global::RubyGlobal, memory::RubyMemory, table::RubyTable},
+    gc::Roots,
     prelude::*,
+    wasi::MemorySlot,
 };
 use rutie::{AnyObject, Boolean, Hash, NilClass, Object, RString, Symbol};
+use std::{cell::Cell, collections::BTreeMap, rc::Rc};
 
-#[rubyclass(module = "Wasmer")]
+#[rubyclass(module = "Wasmer", mark)]
 pub struct ImportObject {
     inner: wasmer::ImportObject,
+    /// The slot receiving the memory of the instance, when the
+    /// imports have been generated by a WASI `Environment`.
+    wasi_memory: Option<MemorySlot>,
+    /// Whether the WASI imports are used by an instance, shared by
+    /// the import objects combining them.
+    instantiated: Rc<Cell<bool>>,
+    /// The Ruby objects held by the imports, e.g. by the WASI state.
+    roots: Roots,
+}
+
+impl Mark for ImportObject {
+    fn mark(&self) {
+        self.roots.mark();
+    }
 }
 
 impl ImportObject {
     pub(crate) fn raw_new(inner: wasmer::ImportObject) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            wasi_memory: None,
+            instantiated: Rc::new(Cell::new(false)),
+            roots: Roots::default(),
+        }
+    }
+
+    /// Creates an import object generated by a WASI `Environment`,
+    /// whose memory is bound to the instance using it, and whose
+    /// state holds the objects of `roots`.
+    pub(crate) fn raw_new_wasi(
+        inner: wasmer::ImportObject,
+        wasi_memory: MemorySlot,
+        roots: &Roots,
+    ) -> Self {
+        Self {
+            wasi_memory: Some(wasi_memory),
+            roots: Roots::linked(vec![roots]),
+            ..Self::raw_new(inner)
+        }
     }
 
     pub(crate) fn inner(&self) -> &wasmer::ImportObject {
         &self.inner
     }
+
+    pub(crate) fn roots(&self) -> &Roots {
+        &self.roots
+    }
+
+    /// Marks the import object as used to create an instance. The
+    /// WASI imports bind themselves to the memory of the first
+    /// instance using them, even if its creation fails afterwards,
+    /// so they can't be shared.
+    pub(crate) fn acquire(&self) -> RubyResult<()> {
+        if self.wasi_memory.is_some() && self.instantiated.replace(true) {
+            return Err(to_ruby_err::<RuntimeError, _>(
+                "The WASI imports are already used by another instance; call `Environment#generate_import_object` again to reuse the environment",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Combines this import object with `other` into a new one. A
+    /// name exported by both in the same namespace is a conflict.
+    pub(crate) fn merged(&self, other: &ImportObject) -> RubyResult<Self> {
+        if self.wasi_memory.is_some() && other.wasi_memory.is_some() {
+            return Err(to_ruby_err::<RuntimeError, _>(
+                "Cannot combine the WASI imports of two environments",
+            ));
+        }
+
+        let mut namespaces = BTreeMap::<String, wasmer::Exports>::new();
+        let mut conflicts = Vec::new();
+
+        for (namespace_name, name, export) in self
+            .inner
+            .externs_vec()
+            .into_iter()
+            .chain(other.inner.externs_vec())
+        {
+            let namespace = namespaces.entry(namespace_name.clone()).or_default();
+
+            if namespace.contains(name.as_str()) {
+                conflicts.push(format!("`{}`.`{}`", namespace_name, name));
+            } else {
+                namespace.insert(name, export);
+            }
+        }
+
+        if !conflicts.is_empty() {
+            conflicts.sort();
+
+            return Err(to_ruby_err::<RuntimeError, _>(format!(
+                "Cannot combine the import objects, since both provide {}",
+                conflicts.join(", ")
+            )));
+        }
+
+        let mut inner = wasmer::ImportObject::new();
+
+        for (namespace_name, namespace) in namespaces {
+            inner.register(namespace_name, namespace);
+        }
+
+        // The combined import object shares the WASI imports, and
+        // thus whether they are used, with the one providing them.
+        let wasi = if other.wasi_memory.is_some() {
+            other
+        } else {
+            self
+        };
+
+        Ok(Self {
+            inner,
+            wasi_memory: wasi.wasi_memory.clone(),
+            instantiated: wasi.instantiated.clone(),
+            roots: Roots::linked(vec![&self.roots, &other.roots]),
+        })
+    }
+
+    /// Binds the import object to the created `instance`.
+    pub(crate) fn bind(&self, instance: &wasmer::Instance) {
+        if let Some(wasi_memory) = &self.wasi_memory {
+            wasi_memory.bind(instance);
+        }
+    }
 }
 
 #[rubymethods]
 impl ImportObject {
     pub fn new() -> RubyResult<AnyObject> {
-        Ok(ImportObject::ruby_new(ImportObject {
-            inner: Default::default(),
-        }))
+        Ok(ImportObject::ruby_new(ImportObject::raw_new(
+            Default::default(),
+        )))
     }
 
     pub fn contains_namespace(&self, namespace_name: &RString) -> RubyResult<Boolean> {
@@ -51,13 +171,22 @@ impl ImportObject {
                 };
 
                 if let Ok(function) = value.try_convert_to::<RubyFunction>() {
-                    wasmer_namespace.insert(name, function.upcast().inner().clone());
+                    let function = function.upcast();
+
+                    self.roots.link(function.roots());
+                    wasmer_namespace.insert(name, function.inner().clone());
                 } else if let Ok(memory) = value.try_convert_to::<RubyMemory>() {
                     wasmer_namespace.insert(name, memory.upcast().inner().clone());
                 } else if let Ok(global) = value.try_convert_to::<RubyGlobal>() {
-                    wasmer_namespace.insert(name, global.upcast().inner().clone());
+                    let global = global.upcast();
+
+                    self.roots.link(global.roots());
+                    wasmer_namespace.insert(name, global.inner().clone());
                 } else if let Ok(table) = value.try_convert_to::<RubyTable>() {
-                    wasmer_namespace.insert(name, table.upcast().inner().clone());
+                    let table = table.upcast();
+
+                    self.roots.link(table.roots());
+                    wasmer_namespace.insert(name, table.inner().clone());
                 } else {
                     return Err(to_ruby_err::<TypeError, _>(format!(
                         "`ImportObject` cannot register the given type `{:?}` associated to `{:?}`",
@@ -75,4 +204,14 @@ impl ImportObject {
 
         Ok(NilClass::new())
     }
+
+    pub fn merge(&self, other: &ImportObject) -> RubyResult<AnyObject> {
+        Ok(ImportObject::ruby_new(self.merged(other)?))
+    }
+
+    pub fn extend(&mut self, other: &ImportObject) -> RubyResult<NilClass> {
+        *self = self.merged(other)?;
+
+        Ok(NilClass::new())
+    }
 }
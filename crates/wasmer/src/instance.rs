@@ -1,41 +1,83 @@
 use crate::{
-    error::{to_ruby_err, RuntimeError},
+    error::{to_ruby_err, to_trap_err, RuntimeError},
     exports::Exports,
+    gc::Roots,
     import_object::RubyImportObject,
     module::Module,
     prelude::*,
+    target,
 };
-use rutie::{AnyObject, Object};
+use rutie::{AnyObject, Object, GC};
 
-#[rubyclass(module = "Wasmer")]
+#[rubyclass(module = "Wasmer", mark)]
 pub struct Instance {
     _inner: wasmer::Instance,
     exports: AnyObject,
+    /// The Ruby objects held by the instance, through its store and
+    /// its imports.
+    roots: Roots,
+}
+
+impl Mark for Instance {
+    fn mark(&self) {
+        GC::mark(&self.exports);
+        self.roots.mark();
+    }
 }
 
 #[rubymethods]
 impl Instance {
     pub fn new(module: &Module, import_object: &AnyObject) -> RubyResult<AnyObject> {
+        target::check_host(module.store().target())?;
+
+        let function_names = module.exported_function_names();
+        let names = module.function_names_by_body();
+        let store_roots = module.store().roots().clone();
         let module = module.inner();
 
-        let instance = if import_object.is_nil() {
-            wasmer::Instance::new(&module, &wasmer::imports! {})
+        let import_object = if import_object.is_nil() {
+            None
         } else {
-            wasmer::Instance::new(
-                &module,
-                import_object
-                    .try_convert_to::<RubyImportObject>()?
-                    .upcast()
-                    .inner(),
-            )
+            Some(import_object.try_convert_to::<RubyImportObject>()?)
         };
 
-        let instance = instance.map_err(to_ruby_err::<RuntimeError, _>)?;
-        let exports = Exports::ruby_new(Exports::new(instance.exports.clone()));
+        let instance = match &import_object {
+            Some(import_object) => {
+                let import_object = import_object.upcast();
+                import_object.acquire()?;
+
+                wasmer::Instance::new(&module, import_object.inner())
+            }
+            None => wasmer::Instance::new(&module, &wasmer::imports! {}),
+        };
+
+        let instance = instance.map_err(|error| match error {
+            wasmer::InstantiationError::Start(error) => to_trap_err(error),
+            error => to_ruby_err::<RuntimeError, _>(error),
+        })?;
+
+        if let Some(import_object) = &import_object {
+            import_object.upcast().bind(&instance);
+        }
+
+        let roots = Roots::linked(
+            std::iter::once(&store_roots).chain(
+                import_object
+                    .iter()
+                    .map(|import_object| import_object.upcast().roots()),
+            ),
+        );
+        let exports = Exports::ruby_new(Exports::new(
+            instance.exports.clone(),
+            function_names,
+            names,
+            roots.clone(),
+        ));
 
         Ok(Instance::ruby_new(Instance {
             _inner: instance,
             exports,
+            roots,
         }))
     }
 
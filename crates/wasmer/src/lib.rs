@@ -15,20 +15,26 @@
 //!
 //! [Wasmer]: https://github.com/wasmerio/wasmer
 
+mod cache;
 #[cfg(doc)]
 mod doc;
 mod error;
 mod exports;
 mod externals;
+mod features;
+mod gc;
 mod import_object;
 mod instance;
 mod memory;
 mod module;
+mod options;
 mod prelude;
 mod store;
+mod target;
 mod types;
 mod values;
 mod wasi;
+mod wast;
 mod wat;
 
 #[cfg(doc)]
@@ -92,18 +98,56 @@ pub extern "C" fn init() {
         in wasmer_module
             class (store::ruby_store) Store {
                 def_self (new) "new";
+                def (features) "features";
+                def (target) "target";
+                def (threads) "threads?";
+            };
+
+            class (target::ruby_target) Target {
+                def_self (new) "new";
+                def (triple) "triple";
+                def (cpu_features) "cpu_features";
+                def (is_host) "host?";
+                def (to_s) "to_s";
+            };
+
+            class (features::ruby_features) Features {
+                def_self (new) "new";
+                def (threads) "threads?";
+                def (reference_types) "reference_types?";
+                def (simd) "simd?";
+                def (bulk_memory) "bulk_memory?";
+                def (multi_value) "multi_value?";
+                def (tail_call) "tail_call?";
+                def (module_linking) "module_linking?";
+                def (multi_memory) "multi_memory?";
+                def (memory64) "memory64?";
+                def (to_h) "to_h";
             };
 
             class (module::ruby_module) Module {
                 def_self (validate) "validate";
+                def_self (validate_or_raise) "validate!";
+                def_self (validation_errors) "validation_errors";
                 def_self (new) "new";
+                def_self (from_file) "from_file";
                 def (set_name) "name=";
                 def (get_name) "name";
                 def (exports) "exports";
                 def (imports) "imports";
+                def (custom_section_names) "custom_section_names";
                 def (custom_sections) "custom_sections";
+                def (producers) "producers";
+                def (target_features) "target_features";
+                def (source_mapping_url) "source_mapping_url";
+                def (dylink) "dylink";
                 def (serialize) "serialize";
+                def (serialize_to_file) "serialize_to_file";
                 def_self (deserialize) "deserialize";
+                def_self (deserialize_from_file) "deserialize_from_file";
+                def (to_wat) "to_wat";
+                def (info) "info";
+                def (function_names) "function_names";
             };
 
             class (instance::ruby_instance) Instance {
@@ -121,12 +165,16 @@ pub extern "C" fn init() {
                 def_self (new) "new";
                 def (contains_namespace) "contains_namespace?";
                 def (register) "register";
+                def (merge) "merge";
+                def (extend) "extend";
             };
 
             class (externals::function::ruby_function, externals::function::ruby_function_extra) Function {
                 def_self (new) "new";
                 def (call) "call";
                 def (r#type) "type";
+                def (name) "name";
+                def (inspect) "inspect";
             };
 
             class (externals::memory::ruby_memory) Memory {
@@ -141,6 +189,10 @@ pub extern "C" fn init() {
                 def (int16_view) "int16_view";
                 def (uint32_view) "uint32_view";
                 def (int32_view) "int32_view";
+                def (atomic_load) "atomic_load";
+                def (atomic_store) "atomic_store";
+                def (atomic_wait) "atomic_wait";
+                def (atomic_notify) "atomic_notify";
             };
 
             class (memory::views::ruby_uint8array) Uint8Array
@@ -211,8 +263,17 @@ pub extern "C" fn init() {
                 def (r#type) "type";
             };
 
-            class (externals::table::ruby_table) Table {
+            class (externals::table::ruby_table) Table
+            include Enumerable
+            {
                 def_self (new) "new";
+                def (size) "size";
+                def (r#type) "type";
+                def (get) "[]";
+                def (set) "[]=";
+                def (grow) "grow";
+                def (each) "each";
+                def (copy) "copy";
             };
 
             class (types) Type {
@@ -275,6 +336,51 @@ pub extern "C" fn init() {
             function (wat::wasm2wat) "wasm2wat";
     };
 
+    let runtime_error = Class::from_existing("RuntimeError");
+
+    wasmer_module
+        .define_nested_class("CompileError", Some(&runtime_error))
+        .define(|this| {
+            this.attr_reader("offset");
+            this.attr_reader("reason");
+        });
+
+    wasmer_module
+        .define_nested_class("DeserializeError", Some(&runtime_error))
+        .define(|this| {
+            this.attr_reader("reason");
+        });
+
+    wasmer_module
+        .define_nested_class("WatError", Some(&runtime_error))
+        .define(|this| {
+            this.attr_reader("line");
+            this.attr_reader("column");
+            this.attr_reader("path");
+            this.attr_reader("snippet");
+        });
+
+    let mut wasmer_cache_module = wasmer_module.define_nested_module("Cache");
+
+    ruby_define! {
+        in wasmer_cache_module
+            class (cache::ruby_filesystem) FileSystem {
+                def_self (new) "new";
+                def (load_or_compile) "load_or_compile";
+                def (directory) "directory";
+                def (size) "size";
+                def (stats) "stats";
+                def (clear) "clear";
+            };
+    };
+
+    let mut wasmer_wast_module = wasmer_module.define_nested_module("Wast");
+
+    ruby_define! {
+        in wasmer_wast_module
+            function (wast::run) "run";
+    };
+
     let mut wasmer_wasi_module = wasmer_module.define_nested_module("Wasi");
 
     ruby_define! {
@@ -289,19 +395,63 @@ pub extern "C" fn init() {
                 def_self (new) "new";
                 def (arguments) "arguments";
                 def (argument) "argument";
+                def (inherit_arguments) "inherit_arguments";
                 def (environments) "environments";
                 def (environment) "environment";
+                def (inherit_environment) "inherit_environment";
                 def (preopen_directories) "preopen_directories";
                 def (preopen_directory) "preopen_directory";
                 def (map_directories) "map_directories";
                 def (map_directory) "map_directory";
+                def (stdin) "stdin";
+                def (inherit_stdin) "inherit_stdin";
+                def (capture_stdout) "capture_stdout";
+                def (capture_stderr) "capture_stderr";
+                def (clock) "clock";
+                def (random_seed) "random_seed";
+                def (mount) "mount";
+                def (to_h) "to_h";
                 def (finalize) "finalize";
             };
 
             class (wasi::ruby_environment) Environment {
                 def (generate_import_object) "generate_import_object";
+                def (stdout) "stdout";
+                def (stderr) "stderr";
+                def (memory) "memory";
+                def (arguments) "arguments";
+                def (environments) "environments";
+                def (file_descriptors) "file_descriptors";
+                def (read_file) "read_file";
+            };
+
+            class (wasi::mem_fs::ruby_memfs) MemFS {
+                def_self (new) "new";
+                def (write) "write";
+                def (read) "read";
+                def (mkdir) "mkdir";
+                def (entries) "entries";
+                def (exists) "exist?";
+                def (is_file) "file?";
+                def (is_directory) "directory?";
+                def (remove) "remove";
+            };
+
+            class (wasi::run::ruby_output) Output {
+                def (exit_code) "exit_code";
+                def (stdout) "stdout";
+                def (stderr) "stderr";
+                def (is_success) "success?";
             };
 
             function (wasi::get_version) "get_version";
+            function (wasi::detect) "detect";
+            function (wasi::run::run) "run";
     };
+
+    wasmer_wasi_module
+        .define_nested_class("ExitError", Some(&runtime_error))
+        .define(|this| {
+            this.attr_reader("code");
+        });
 }
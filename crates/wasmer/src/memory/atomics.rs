@@ -0,0 +1,190 @@
+//! Host-side atomic operations over a memory, mirroring the
+//! `*.atomic.*`, `memory.atomic.wait32` and `memory.atomic.notify`
+//! instructions from the threads proposal.
+//!
+//! Waiters are parked in queues indexed by the absolute address they
+//! wait on, so that several `Memory` objects pointing to the same
+//! memory share the same queues.
+
+use crate::prelude::*;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+#[derive(Default)]
+struct Waiters {
+    waiting: usize,
+    notified: usize,
+}
+
+#[derive(Default)]
+struct Queue {
+    waiters: Mutex<Waiters>,
+    condvar: Condvar,
+}
+
+lazy_static! {
+    static ref QUEUES: Mutex<HashMap<usize, Arc<Queue>>> = Mutex::new(HashMap::new());
+}
+
+/// The outcome of [`wait`], equivalent to the values returned by
+/// `memory.atomic.wait32`.
+pub enum WaitResult {
+    Ok,
+    NotEqual,
+    TimedOut,
+}
+
+impl WaitResult {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::NotEqual => "not_equal",
+            Self::TimedOut => "timed_out",
+        }
+    }
+}
+
+/// Returns the 32-bit cell at `offset` (in bytes) in `memory`.
+///
+/// The offset must be aligned to 4 bytes and the cell must be in
+/// bounds.
+pub fn cell(memory: &wasmer::Memory, offset: usize) -> Result<&AtomicU32, String> {
+    if offset % 4 != 0 {
+        return Err(format!(
+            "Unaligned atomic access: offset {} is not a multiple of 4",
+            offset
+        ));
+    }
+
+    let data_size: usize = memory
+        .data_size()
+        .try_into()
+        .map_err(|_| "The memory is too large for this platform".to_string())?;
+
+    if offset.checked_add(4).map_or(true, |end| end > data_size) {
+        return Err(format!(
+            "Out of bound: Offset {} is larger than the memory size {}",
+            offset, data_size
+        ));
+    }
+
+    // SAFETY: the cell is in bounds and aligned, and memories
+    // allocated by Wasmer are never freed while a `wasmer::Memory`
+    // exists.
+    Ok(unsafe { &*(memory.data_ptr().add(offset) as *const AtomicU32) })
+}
+
+/// Blocks the current thread until the cell is notified, or until
+/// `timeout` has elapsed, or until `interrupted` is set. The cell is
+/// compared to `expected` first, and the thread does not block if
+/// they differ.
+///
+/// This function must be called without the Ruby GVL, see
+/// [`interrupt`] to unblock it.
+pub fn wait(
+    cell: &AtomicU32,
+    expected: u32,
+    timeout: Option<Duration>,
+    interrupted: &AtomicBool,
+) -> WaitResult {
+    let address = cell as *const AtomicU32 as usize;
+    let queue = QUEUES.lock().unwrap().entry(address).or_default().clone();
+
+    let result = {
+        let mut waiters = queue.waiters.lock().unwrap();
+
+        if cell.load(Ordering::SeqCst) != expected {
+            WaitResult::NotEqual
+        } else {
+            let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+            waiters.waiting += 1;
+
+            loop {
+                if waiters.notified > 0 {
+                    waiters.notified -= 1;
+                    waiters.waiting -= 1;
+
+                    break WaitResult::Ok;
+                }
+
+                let now = Instant::now();
+
+                if interrupted.load(Ordering::SeqCst)
+                    || deadline.map_or(false, |deadline| now >= deadline)
+                {
+                    waiters.waiting -= 1;
+
+                    break WaitResult::TimedOut;
+                }
+
+                waiters = match deadline {
+                    Some(deadline) => {
+                        queue
+                            .condvar
+                            .wait_timeout(waiters, deadline - now)
+                            .unwrap()
+                            .0
+                    }
+                    None => queue.condvar.wait(waiters).unwrap(),
+                };
+            }
+        }
+    };
+
+    drop(queue);
+    release(address);
+
+    result
+}
+
+/// Wakes up the waiters blocked on `cell` when the Ruby VM needs to
+/// interrupt them.
+pub fn interrupt(cell: &AtomicU32, interrupted: &AtomicBool) {
+    let address = cell as *const AtomicU32 as usize;
+    let queue = QUEUES.lock().unwrap().get(&address).cloned();
+
+    interrupted.store(true, Ordering::SeqCst);
+
+    if let Some(queue) = queue {
+        let _waiters = queue.waiters.lock().unwrap();
+        queue.condvar.notify_all();
+    }
+}
+
+/// Wakes up at most `count` waiters blocked on `cell`, and returns
+/// the number of woken waiters.
+pub fn notify(cell: &AtomicU32, count: u32) -> u32 {
+    let address = cell as *const AtomicU32 as usize;
+    let queue = match QUEUES.lock().unwrap().get(&address) {
+        Some(queue) => queue.clone(),
+        None => return 0,
+    };
+
+    let mut waiters = queue.waiters.lock().unwrap();
+    let woken = (waiters.waiting - waiters.notified).min(count as usize);
+
+    waiters.notified += woken;
+    queue.condvar.notify_all();
+
+    woken as u32
+}
+
+/// Drops the queue for `address` if nobody uses it anymore.
+fn release(address: usize) {
+    let mut queues = QUEUES.lock().unwrap();
+
+    if queues
+        .get(&address)
+        .map_or(false, |queue| Arc::strong_count(queue) == 1)
+    {
+        queues.remove(&address);
+    }
+}
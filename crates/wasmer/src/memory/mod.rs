@@ -1 +1,2 @@
+pub mod atomics;
 pub mod views;
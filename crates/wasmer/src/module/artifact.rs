@@ -0,0 +1,236 @@
+//! The format of the serialized modules.
+//!
+//! The engine deserializes the modules directly into executable
+//! memory, which is unsafe if the bytes have been produced by another
+//! engine, or have been modified. Thus, the serialized modules are
+//! wrapped in a header that records everything they depend on, and
+//! are followed by a checksum, or by a keyed hash when they are
+//! signed:
+//!
+//! ```text
+//! magic (8 bytes) | format version (u16) | flags (u8)
+//! | gem version | Wasmer version | engine | target | features
+//! | payload length (u64) | payload
+//! | digest (32 bytes)
+//! ```
+//!
+//! The strings are prefixed by their length (u32), and the integers
+//! are little-endian. The digest covers everything before it.
+//!
+//! The checksum only detects accidental corruption: anyone able to
+//! modify an unsigned module can recompute it. Only a keyed hash, with
+//! a key kept secret, detects tampering.
+
+use crate::{
+    error::{to_ruby_err, to_wasmer_err, RuntimeError},
+    prelude::*,
+    store::Store,
+};
+use rutie::{AnyException, Object, Symbol};
+use std::convert::TryInto;
+
+const MAGIC: &[u8; 8] = b"\0wasmrb\0";
+const FORMAT_VERSION: u16 = 1;
+const FLAG_SIGNED: u8 = 1;
+const DIGEST_SIZE: usize = 32;
+
+/// The context of the key derivation, see `blake3::derive_key`.
+const KEY_CONTEXT: &str = "wasmer-ruby 2021-11-01 serialized module signing key";
+
+/// Creates a `Wasmer::DeserializeError`. `reason` is one of
+/// `:malformed`, `:tampered`, `:unauthenticated` or `:incompatible`.
+fn to_deserialize_err<Error: ToString>(reason: &str, error: Error) -> AnyException {
+    to_wasmer_err(
+        "Wasmer::DeserializeError",
+        error,
+        &[("reason", Symbol::new(reason).to_any_object())],
+    )
+}
+
+/// What a serialized module depends on.
+fn header_fields(store: &Store) -> [(&'static str, String); 5] {
+    [
+        ("gem version", env!("CARGO_PKG_VERSION").to_string()),
+        ("Wasmer version", wasmer::VERSION.to_string()),
+        ("engine", store.engine_name().to_string()),
+        ("target", store.target_descriptor()),
+        ("features", format!("{:?}", store.features())),
+    ]
+}
+
+fn digest(data: &[u8], key: Option<&[u8]>) -> blake3::Hash {
+    match key {
+        Some(key) => blake3::keyed_hash(&blake3::derive_key(KEY_CONTEXT, key), data),
+        None => blake3::hash(data),
+    }
+}
+
+/// Serializes `module` compiled by `store`. If `key` is given, the
+/// serialized module is signed, and can only be deserialized with
+/// the same key.
+pub(crate) fn serialize(
+    store: &Store,
+    module: &wasmer::Module,
+    key: Option<&[u8]>,
+) -> RubyResult<Vec<u8>> {
+    let payload = module.serialize().map_err(to_ruby_err::<RuntimeError, _>)?;
+    let mut bytes = Vec::with_capacity(payload.len() + 256);
+
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.push(if key.is_some() { FLAG_SIGNED } else { 0 });
+
+    for (_, value) in header_fields(store).iter() {
+        bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(value.as_bytes());
+    }
+
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+
+    let digest = digest(&bytes, key);
+    bytes.extend_from_slice(digest.as_bytes());
+
+    Ok(bytes)
+}
+
+/// A cursor over a serialized module.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, size: usize) -> RubyResult<&'a [u8]> {
+        if size > self.bytes.len() {
+            return Err(to_deserialize_err(
+                "malformed",
+                "The serialized module is truncated",
+            ));
+        }
+
+        let (read, rest) = self.bytes.split_at(size);
+        self.bytes = rest;
+
+        Ok(read)
+    }
+
+    fn read_u32(&mut self) -> RubyResult<u32> {
+        Ok(u32::from_le_bytes(self.read(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> RubyResult<u64> {
+        Ok(u64::from_le_bytes(self.read(8)?.try_into().unwrap()))
+    }
+
+    fn read_string(&mut self) -> RubyResult<&'a str> {
+        let size = self.read_u32()? as usize;
+
+        std::str::from_utf8(self.read(size)?).map_err(|_| {
+            to_deserialize_err("malformed", "The serialized module has an invalid header")
+        })
+    }
+}
+
+/// Deserializes a module serialized by `serialize`, after checking
+/// that it's signed with `key` if given, that it hasn't been modified
+/// since, and that it's compatible with `store`.
+///
+/// Without a key, the module must come from a trusted source, since
+/// its checksum doesn't protect it against tampering.
+pub(crate) fn deserialize(
+    store: &Store,
+    bytes: &[u8],
+    key: Option<&[u8]>,
+) -> RubyResult<wasmer::Module> {
+    if bytes.len() < MAGIC.len() + 3 + DIGEST_SIZE || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(to_deserialize_err(
+            "malformed",
+            "The bytes are not a module serialized by `Module#serialize`",
+        ));
+    }
+
+    let (data, expected_digest) = bytes.split_at(bytes.len() - DIGEST_SIZE);
+    let mut reader = Reader {
+        bytes: &data[MAGIC.len()..],
+    };
+
+    let format_version = u16::from_le_bytes(reader.read(2)?.try_into().unwrap());
+
+    if format_version != FORMAT_VERSION {
+        return Err(to_deserialize_err(
+            "incompatible",
+            format!(
+                "The serialized module has the format version {}, but only the version {} is supported",
+                format_version, FORMAT_VERSION
+            ),
+        ));
+    }
+
+    let signed = reader.read(1)?[0] & FLAG_SIGNED != 0;
+
+    match (signed, key) {
+        (true, None) => {
+            return Err(to_deserialize_err(
+                "unauthenticated",
+                "The serialized module is signed, but no key has been given",
+            ))
+        }
+        (false, Some(_)) => {
+            return Err(to_deserialize_err(
+                "unauthenticated",
+                "The serialized module isn't signed, but a key has been given",
+            ))
+        }
+        _ => (),
+    }
+
+    let expected_digest: [u8; DIGEST_SIZE] = expected_digest.try_into().unwrap();
+
+    // `blake3::Hash` compares in constant time.
+    if digest(data, key) != blake3::Hash::from(expected_digest) {
+        return Err(if signed {
+            to_deserialize_err(
+                "tampered",
+                "The signature of the serialized module doesn't match: it has been tampered with, or signed with another key",
+            )
+        } else {
+            to_deserialize_err(
+                "tampered",
+                "The checksum of the serialized module doesn't match: it has been corrupted",
+            )
+        });
+    }
+
+    for (name, expected) in header_fields(store).iter() {
+        let value = reader.read_string()?;
+
+        if value != expected {
+            return Err(to_deserialize_err(
+                "incompatible",
+                format!(
+                    "The module has been serialized with the {} `{}`, but the store uses `{}`",
+                    name, value, expected
+                ),
+            ));
+        }
+    }
+
+    let payload_size = reader.read_u64()? as usize;
+    let payload = reader.read(payload_size)?;
+
+    if !reader.bytes.is_empty() {
+        return Err(to_deserialize_err(
+            "malformed",
+            "The serialized module has trailing bytes",
+        ));
+    }
+
+    // SAFETY: The payload has been produced by
+    // `wasmer::Module::serialize` with the same versions, engine,
+    // target and features, and hasn't been corrupted since. If it's
+    // signed, it hasn't been tampered with either; otherwise, its
+    // source is trusted by the caller, as documented by
+    // `Module.deserialize`.
+    unsafe { wasmer::Module::deserialize(store.inner(), payload) }
+        .map_err(|error| to_deserialize_err("malformed", error))
+}
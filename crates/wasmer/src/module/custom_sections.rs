@@ -0,0 +1,195 @@
+//! Decoders for the well-known custom sections produced by the
+//! toolchains, see
+//! <https://github.com/WebAssembly/tool-conventions>.
+
+use crate::{
+    error::{to_ruby_err, RuntimeError},
+    prelude::*,
+};
+use rutie::{AnyException, AnyObject, Array, Hash, Integer, NilClass, Object, RString, Symbol};
+use wasmer::wasmparser::{BinaryReader, BinaryReaderError, ProducersSectionReader};
+
+fn to_malformed_err(name: &str, error: BinaryReaderError) -> AnyException {
+    to_ruby_err::<RuntimeError, _>(format!(
+        "Malformed `{}` custom section: {}",
+        name,
+        error.message()
+    ))
+}
+
+/// Decodes the `producers` section, which lists the languages, the
+/// tools and the SDKs that have produced the module, as a `Hash`
+/// from fields to `Hash`es of names and versions, e.g. `{"language"
+/// => {"Rust" => ""}, "processed-by" => {"rustc" => "1.56.0"}}`.
+pub(crate) fn producers(data: &[u8]) -> RubyResult<Hash> {
+    let read = || -> Result<Hash, BinaryReaderError> {
+        let mut hash = Hash::new();
+
+        for field in ProducersSectionReader::new(data, 0)? {
+            let field = field?;
+            let mut values = Hash::new();
+            let mut reader = field.get_producer_field_values_reader()?;
+
+            for _ in 0..reader.get_count() {
+                let value = reader.read()?;
+
+                values.store(
+                    RString::new_utf8(value.name),
+                    RString::new_utf8(value.version),
+                );
+            }
+
+            hash.store(RString::new_utf8(field.name), values);
+        }
+
+        Ok(hash)
+    };
+
+    read().map_err(|error| to_malformed_err("producers", error))
+}
+
+/// Decodes the `target_features` section, which lists the features
+/// used by the module, as a `Hash` from feature names to their
+/// policy: `:used` (prefix `+`), `:disallowed` (prefix `-`) or
+/// `:required` (prefix `=`).
+pub(crate) fn target_features(data: &[u8]) -> RubyResult<Hash> {
+    let read = || -> Result<Vec<(u32, &str)>, BinaryReaderError> {
+        let mut reader = BinaryReader::new(data);
+        let count = reader.read_var_u32()?;
+        let mut features = Vec::with_capacity(count as usize);
+
+        for _ in 0..count {
+            features.push((reader.read_u8()?, reader.read_string()?));
+        }
+
+        Ok(features)
+    };
+
+    let mut hash = Hash::new();
+
+    for (prefix, name) in read().map_err(|error| to_malformed_err("target_features", error))? {
+        let policy = match prefix as u8 {
+            b'+' => "used",
+            b'-' => "disallowed",
+            b'=' => "required",
+            prefix => {
+                return Err(to_ruby_err::<RuntimeError, _>(format!(
+                    "Malformed `target_features` custom section: the feature `{}` has an unknown prefix 0x{:02x}",
+                    name, prefix
+                )));
+            }
+        };
+
+        hash.store(RString::new_utf8(name), Symbol::new(policy));
+    }
+
+    Ok(hash)
+}
+
+/// Decodes the `sourceMappingURL` section, which holds the URL of the
+/// source map of the module.
+pub(crate) fn source_mapping_url(data: &[u8]) -> RubyResult<RString> {
+    BinaryReader::new(data)
+        .read_string()
+        .map(RString::new_utf8)
+        .map_err(|error| to_malformed_err("sourceMappingURL", error))
+}
+
+const WASM_DYLINK_MEM_INFO: u8 = 1;
+const WASM_DYLINK_NEEDED: u8 = 2;
+const WASM_DYLINK_EXPORT_INFO: u8 = 3;
+const WASM_DYLINK_IMPORT_INFO: u8 = 4;
+
+/// Decodes the `dylink.0` section, which describes a module meant to
+/// be dynamically linked, as a `Hash` with the `:memory_size`,
+/// `:memory_alignment`, `:table_size` and `:table_alignment` it
+/// requires, the `:needed` dynamic libraries, and the flags of its
+/// `:exports` and `:imports`.
+pub(crate) fn dylink(data: &[u8]) -> RubyResult<Hash> {
+    let read = || -> Result<Hash, BinaryReaderError> {
+        let mut reader = BinaryReader::new(data);
+        let mut memory_info = [0; 4];
+        let mut needed = Array::new();
+        let mut exports = Hash::new();
+        let mut imports = Array::new();
+
+        while !reader.eof() {
+            let id = reader.read_u8()? as u8;
+            let size = reader.read_var_u32()? as usize;
+            let mut subsection = BinaryReader::new(reader.read_bytes(size)?);
+
+            match id {
+                WASM_DYLINK_MEM_INFO => {
+                    for value in memory_info.iter_mut() {
+                        *value = subsection.read_var_u32()?;
+                    }
+                }
+
+                WASM_DYLINK_NEEDED => {
+                    for _ in 0..subsection.read_var_u32()? {
+                        needed.push(RString::new_utf8(subsection.read_string()?));
+                    }
+                }
+
+                WASM_DYLINK_EXPORT_INFO => {
+                    for _ in 0..subsection.read_var_u32()? {
+                        let name = subsection.read_string()?;
+                        let flags = subsection.read_var_u32()?;
+
+                        exports.store(RString::new_utf8(name), Integer::from(flags));
+                    }
+                }
+
+                WASM_DYLINK_IMPORT_INFO => {
+                    for _ in 0..subsection.read_var_u32()? {
+                        let mut import = Hash::new();
+                        import.store(
+                            Symbol::new("module"),
+                            RString::new_utf8(subsection.read_string()?),
+                        );
+                        import.store(
+                            Symbol::new("name"),
+                            RString::new_utf8(subsection.read_string()?),
+                        );
+                        import.store(
+                            Symbol::new("flags"),
+                            Integer::from(subsection.read_var_u32()?),
+                        );
+
+                        imports.push(import);
+                    }
+                }
+
+                // Unknown subsections are skipped, as the linkers do.
+                _ => (),
+            }
+        }
+
+        let mut hash = Hash::new();
+        hash.store(Symbol::new("memory_size"), Integer::from(memory_info[0]));
+        hash.store(
+            Symbol::new("memory_alignment"),
+            Integer::from(memory_info[1]),
+        );
+        hash.store(Symbol::new("table_size"), Integer::from(memory_info[2]));
+        hash.store(
+            Symbol::new("table_alignment"),
+            Integer::from(memory_info[3]),
+        );
+        hash.store(Symbol::new("needed"), needed);
+        hash.store(Symbol::new("exports"), exports);
+        hash.store(Symbol::new("imports"), imports);
+
+        Ok(hash)
+    };
+
+    read().map_err(|error| to_malformed_err("dylink.0", error))
+}
+
+/// Returns `object`, or `nil` if the custom section is absent.
+pub(crate) fn or_nil<T: Object>(object: Option<RubyResult<T>>) -> RubyResult<AnyObject> {
+    Ok(match object {
+        Some(object) => object?.to_any_object(),
+        None => NilClass::new().to_any_object(),
+    })
+}
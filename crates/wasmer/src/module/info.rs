@@ -0,0 +1,393 @@
+use crate::{
+    error::{to_ruby_err, RuntimeError},
+    module::names::Names,
+    prelude::*,
+    types::{FunctionType, GlobalType, MemoryType, TableType},
+};
+use rutie::{AnyObject, Array, Boolean, Hash, Integer, NilClass, Object, RString, Symbol};
+use wasmer::wasmparser::{
+    self, BinaryReaderError, DataKind, ElementKind, ImportSectionEntryType, InitExpr, Operator,
+    Parser, Payload, TypeDef,
+};
+
+/// A function defined by the module, i.e. not imported.
+struct Function {
+    index: u32,
+    type_index: u32,
+    locals: u32,
+}
+
+/// A data segment.
+struct Data {
+    memory: Option<u32>,
+    offset: Option<i64>,
+    size: usize,
+}
+
+/// An element segment.
+struct Element {
+    mode: &'static str,
+    table: Option<u32>,
+    offset: Option<i64>,
+    ty: wasmparser::Type,
+    size: u32,
+}
+
+/// The structure of a module, as read from its bytes.
+#[derive(Default)]
+pub(crate) struct Info {
+    types: Vec<Option<wasmparser::FuncType>>,
+    functions: Vec<Function>,
+    tables: Vec<wasmparser::TableType>,
+    memories: Vec<wasmparser::MemoryType>,
+    globals: Vec<wasmparser::GlobalType>,
+    start: Option<u32>,
+    elements: Vec<Element>,
+    data: Vec<Data>,
+}
+
+/// Evaluates the offset of an active segment. It's `None` if it
+/// isn't a constant, e.g. if it's read from an imported global.
+fn offset(init_expr: &InitExpr) -> Result<Option<i64>, BinaryReaderError> {
+    Ok(match init_expr.get_operators_reader().read()? {
+        Operator::I32Const { value } => Some(value as u32 as i64),
+        Operator::I64Const { value } => Some(value),
+        _ => None,
+    })
+}
+
+/// Reads the structure of the module compiled from `bytes`, and the
+/// names declared by its `name` custom section.
+pub(crate) fn read(bytes: &[u8]) -> Result<(Info, Names), BinaryReaderError> {
+    let mut info = Info::default();
+    let mut names = Names::default();
+    let mut number_of_imported_functions = 0;
+    let mut code_index = 0;
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload? {
+            Payload::TypeSection(reader) => {
+                for type_definition in reader {
+                    info.types.push(match type_definition? {
+                        TypeDef::Func(function_type) => Some(function_type),
+                        _ => None,
+                    });
+                }
+            }
+
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let ImportSectionEntryType::Function(_) = import?.ty {
+                        number_of_imported_functions += 1;
+                    }
+                }
+            }
+
+            Payload::FunctionSection(reader) => {
+                for type_index in reader {
+                    let index = number_of_imported_functions + info.functions.len() as u32;
+
+                    info.functions.push(Function {
+                        index,
+                        type_index: type_index?,
+                        locals: 0,
+                    });
+                }
+            }
+
+            Payload::TableSection(reader) => {
+                for table_type in reader {
+                    info.tables.push(table_type?);
+                }
+            }
+
+            Payload::MemorySection(reader) => {
+                for memory_type in reader {
+                    info.memories.push(memory_type?);
+                }
+            }
+
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    info.globals.push(global?.ty);
+                }
+            }
+
+            Payload::StartSection { func, .. } => info.start = Some(func),
+
+            Payload::ElementSection(reader) => {
+                for element in reader {
+                    let element = element?;
+                    let size = element.items.get_items_reader()?.get_count();
+
+                    info.elements.push(match element.kind {
+                        ElementKind::Active {
+                            table_index,
+                            init_expr,
+                        } => Element {
+                            mode: "active",
+                            table: Some(table_index),
+                            offset: offset(&init_expr)?,
+                            ty: element.ty,
+                            size,
+                        },
+                        ElementKind::Passive => Element {
+                            mode: "passive",
+                            table: None,
+                            offset: None,
+                            ty: element.ty,
+                            size,
+                        },
+                        ElementKind::Declared => Element {
+                            mode: "declared",
+                            table: None,
+                            offset: None,
+                            ty: element.ty,
+                            size,
+                        },
+                    });
+                }
+            }
+
+            Payload::DataSection(reader) => {
+                for data in reader {
+                    let data = data?;
+
+                    info.data.push(match data.kind {
+                        DataKind::Active {
+                            memory_index,
+                            init_expr,
+                        } => Data {
+                            memory: Some(memory_index),
+                            offset: offset(&init_expr)?,
+                            size: data.data.len(),
+                        },
+                        DataKind::Passive => Data {
+                            memory: None,
+                            offset: None,
+                            size: data.data.len(),
+                        },
+                    });
+                }
+            }
+
+            Payload::CodeSectionEntry(body) => {
+                let mut reader = body.get_locals_reader()?;
+                let mut locals = 0;
+
+                for _ in 0..reader.get_count() {
+                    locals += reader.read()?.0;
+                }
+
+                if let Some(function) = info.functions.get_mut(code_index) {
+                    function.locals = locals;
+                }
+
+                code_index += 1;
+            }
+
+            Payload::CustomSection {
+                name: "name",
+                data,
+                data_offset,
+                ..
+            } => {
+                // A malformed name section is ignored, as the engine
+                // does.
+                names = Names::read_section(data, data_offset).unwrap_or_default();
+            }
+
+            _ => (),
+        }
+    }
+
+    Ok((info, names))
+}
+
+fn to_type(ty: wasmparser::Type) -> RubyResult<wasmer::Type> {
+    Ok(match ty {
+        wasmparser::Type::I32 => wasmer::Type::I32,
+        wasmparser::Type::I64 => wasmer::Type::I64,
+        wasmparser::Type::F32 => wasmer::Type::F32,
+        wasmparser::Type::F64 => wasmer::Type::F64,
+        wasmparser::Type::V128 => wasmer::Type::V128,
+        wasmparser::Type::FuncRef => wasmer::Type::FuncRef,
+        wasmparser::Type::ExternRef => wasmer::Type::ExternRef,
+        ty => {
+            return Err(to_ruby_err::<TypeError, _>(format!(
+                "Type `{:?}` is not supported",
+                ty
+            )))
+        }
+    })
+}
+
+fn to_types(types: &[wasmparser::Type]) -> RubyResult<Vec<wasmer::Type>> {
+    types.iter().copied().map(to_type).collect()
+}
+
+fn to_integer<T: Into<i64>>(value: Option<T>) -> AnyObject {
+    match value {
+        Some(value) => Integer::new(value.into()).to_any_object(),
+        None => NilClass::new().to_any_object(),
+    }
+}
+
+impl Info {
+    /// Represents the structure of the module as a Ruby `Hash`, see
+    /// `Module#info`.
+    pub(crate) fn to_hash(&self, names: &Names) -> RubyResult<Hash> {
+        let mut hash = Hash::new();
+
+        let mut functions = Array::with_capacity(self.functions.len());
+
+        for function in &self.functions {
+            let function_type = match self.types.get(function.type_index as usize) {
+                Some(Some(function_type)) => wasmer::FunctionType::new(
+                    to_types(&function_type.params)?,
+                    to_types(&function_type.returns)?,
+                ),
+                _ => {
+                    return Err(to_ruby_err::<RuntimeError, _>(format!(
+                        "Function {} has an invalid type index",
+                        function.index
+                    )))
+                }
+            };
+
+            let mut entry = Hash::new();
+            entry.store(Symbol::new("index"), Integer::from(function.index));
+            entry.store(
+                Symbol::new("name"),
+                match names.functions.get(&function.index) {
+                    Some(name) => RString::new_utf8(name).to_any_object(),
+                    None => NilClass::new().to_any_object(),
+                },
+            );
+            entry.store(
+                Symbol::new("type"),
+                FunctionType::ruby_new(FunctionType::from(&function_type)),
+            );
+            entry.store(Symbol::new("locals"), Integer::from(function.locals));
+
+            let mut local_names = Hash::new();
+
+            if let Some(locals) = names.locals.get(&function.index) {
+                for (index, name) in locals {
+                    local_names.store(Integer::from(*index), RString::new_utf8(name));
+                }
+            }
+
+            entry.store(Symbol::new("local_names"), local_names);
+
+            functions.push(entry);
+        }
+
+        hash.store(Symbol::new("functions"), functions);
+
+        let mut tables = Array::with_capacity(self.tables.len());
+
+        for table in &self.tables {
+            let table_type = wasmer::TableType::new(
+                to_type(table.element_type)?,
+                table.limits.initial,
+                table.limits.maximum,
+            );
+
+            tables.push(TableType::ruby_new(TableType::from(&table_type)));
+        }
+
+        hash.store(Symbol::new("tables"), tables);
+
+        let mut memories = Array::with_capacity(self.memories.len());
+
+        for memory in &self.memories {
+            match memory {
+                wasmparser::MemoryType::M32 { limits, shared } => {
+                    let memory_type =
+                        wasmer::MemoryType::new(limits.initial, limits.maximum, *shared);
+
+                    memories.push(MemoryType::ruby_new(MemoryType::from(memory_type)));
+                }
+
+                // `MemoryType` only has 32-bit limits, a 64-bit memory
+                // is described by a `Hash` instead.
+                wasmparser::MemoryType::M64 { limits, shared } => {
+                    let mut entry = Hash::new();
+                    // The limits are at most 2^48 pages, so they fit in
+                    // an `i64`.
+                    entry.store(Symbol::new("minimum"), Integer::new(limits.initial as i64));
+                    entry.store(
+                        Symbol::new("maximum"),
+                        to_integer(limits.maximum.map(|maximum| maximum as i64)),
+                    );
+                    entry.store(Symbol::new("shared"), Boolean::new(*shared));
+                    entry.store(Symbol::new("memory64"), Boolean::new(true));
+
+                    memories.push(entry);
+                }
+            }
+        }
+
+        hash.store(Symbol::new("memories"), memories);
+
+        let mut globals = Array::with_capacity(self.globals.len());
+
+        for global in &self.globals {
+            let global_type = wasmer::GlobalType::new(
+                to_type(global.content_type)?,
+                if global.mutable {
+                    wasmer::Mutability::Var
+                } else {
+                    wasmer::Mutability::Const
+                },
+            );
+
+            globals.push(GlobalType::ruby_new(GlobalType::from(&global_type)));
+        }
+
+        hash.store(Symbol::new("globals"), globals);
+        hash.store(Symbol::new("start"), to_integer(self.start));
+
+        let mut elements = Array::with_capacity(self.elements.len());
+
+        for element in &self.elements {
+            let mut entry = Hash::new();
+            entry.store(Symbol::new("mode"), Symbol::new(element.mode));
+            entry.store(Symbol::new("table"), to_integer(element.table));
+            entry.store(Symbol::new("offset"), to_integer(element.offset));
+            entry.store(
+                Symbol::new("type"),
+                crate::types::Type::from(&to_type(element.ty)?).to_integer(),
+            );
+            entry.store(Symbol::new("size"), Integer::from(element.size));
+
+            elements.push(entry);
+        }
+
+        hash.store(Symbol::new("elements"), elements);
+
+        let mut data = Array::with_capacity(self.data.len());
+
+        for segment in &self.data {
+            let mut entry = Hash::new();
+            entry.store(
+                Symbol::new("mode"),
+                Symbol::new(if segment.memory.is_some() {
+                    "active"
+                } else {
+                    "passive"
+                }),
+            );
+            entry.store(Symbol::new("memory"), to_integer(segment.memory));
+            entry.store(Symbol::new("offset"), to_integer(segment.offset));
+            entry.store(Symbol::new("size"), Integer::new(segment.size as i64));
+
+            data.push(entry);
+        }
+
+        hash.store(Symbol::new("data"), data);
+
+        Ok(hash)
+    }
+}
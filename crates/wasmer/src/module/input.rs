@@ -0,0 +1,58 @@
+//! Reads the bytes given to the `Module` methods, which can be a
+//! `String`, or any object responding to `read` like an `IO`.
+
+use crate::{
+    error::{to_ruby_err, TypeError},
+    prelude::*,
+};
+use rutie::{AnyObject, Integer, Object, RString};
+
+/// The size of the chunks read from an `IO`-like object.
+const CHUNK_SIZE: i64 = 64 * 1024;
+
+/// Reads the bytes of `input`. A `String` is read as is, frozen or
+/// not, whatever its encoding. Any other object responding to `read`,
+/// e.g. a `File`, a `StringIO` or a `Tempfile`, is read by chunks
+/// until the end.
+pub(crate) fn read(input: &AnyObject) -> RubyResult<Vec<u8>> {
+    if let Ok(string) = input.try_convert_to::<RString>() {
+        return Ok(string.to_bytes_unchecked().to_vec());
+    }
+
+    if !input.respond_to("read") {
+        return Err(to_ruby_err::<TypeError, _>(format!(
+            "Expected a `String` or an `IO`-like object responding to `read`, received `{:?}`",
+            input.ty()
+        )));
+    }
+
+    let mut bytes = Vec::new();
+    let chunk_size = Integer::new(CHUNK_SIZE).to_any_object();
+
+    loop {
+        let chunk = input.protect_send("read", &[chunk_size.clone()])?;
+
+        // `read(length)` returns `nil` at the end of the input.
+        if chunk.is_nil() {
+            break;
+        }
+
+        let chunk = chunk.try_convert_to::<RString>().map_err(|_| {
+            to_ruby_err::<TypeError, _>(format!(
+                "Expected `read` to return a `String` or `nil`, received `{:?}`",
+                chunk.ty()
+            ))
+        })?;
+
+        let chunk = chunk.to_bytes_unchecked();
+
+        // Some objects return an empty string instead of `nil`.
+        if chunk.is_empty() {
+            break;
+        }
+
+        bytes.extend_from_slice(chunk);
+    }
+
+    Ok(bytes)
+}
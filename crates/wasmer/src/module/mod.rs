@@ -0,0 +1,472 @@
+use crate::{
+    error::{to_compile_err, to_ruby_err, to_wasmer_err, IOError, RuntimeError},
+    externals::function::FunctionNames,
+    features, options,
+    prelude::*,
+    store::Store,
+    types::{ExportType, ImportType},
+    wat,
+};
+use info::Info;
+use names::Names;
+use rutie::{
+    AnyException, AnyObject, Array, Boolean, Encoding, Hash, Integer, NilClass, Object, RString,
+    Symbol,
+};
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    convert::TryFrom,
+    fs,
+};
+
+pub(crate) mod artifact;
+mod custom_sections;
+mod info;
+mod input;
+mod names;
+
+#[rubyclass(module = "Wasmer")]
+pub struct Module {
+    inner: wasmer::Module,
+    store: Store,
+    names: Names,
+    names_by_body: RefCell<Option<FunctionNames>>,
+    info: RefCell<Option<(Info, Names)>>,
+    bytes: RefCell<Option<Vec<u8>>>,
+    keep_bytes: bool,
+}
+
+impl Module {
+    /// Creates a module compiled from `bytes`. The bytes are held
+    /// until the structure of the module is read, on the first call
+    /// to `Module#info`, and then released unless `keep_bytes` is
+    /// true, to disassemble the module with `Module#to_wat`.
+    pub(crate) fn raw_new(
+        inner: wasmer::Module,
+        store: &Store,
+        bytes: Vec<u8>,
+        keep_bytes: bool,
+    ) -> Self {
+        Self {
+            names: Names::from_engine(&inner),
+            names_by_body: RefCell::new(None),
+            inner,
+            store: store.clone(),
+            info: RefCell::new(None),
+            bytes: RefCell::new(Some(bytes)),
+            keep_bytes,
+        }
+    }
+
+    /// Creates a deserialized module, whose WebAssembly bytes aren't
+    /// available. Only the function names, kept by the engine, are.
+    pub(crate) fn raw_deserialized(inner: wasmer::Module, store: &Store) -> Self {
+        Self {
+            names: Names::from_engine(&inner),
+            names_by_body: RefCell::new(None),
+            inner,
+            store: store.clone(),
+            info: RefCell::new(None),
+            bytes: RefCell::new(None),
+            keep_bytes: false,
+        }
+    }
+
+    pub(crate) fn inner(&self) -> &wasmer::Module {
+        &self.inner
+    }
+
+    pub(crate) fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Tells whether the module has been deserialized, i.e. whether
+    /// it has never had WebAssembly bytes.
+    fn is_deserialized(&self) -> bool {
+        self.bytes.borrow().is_none() && self.info.borrow().is_none()
+    }
+
+    /// Returns the WebAssembly bytes the module has been compiled
+    /// from. They are only kept with the `keep_bytes` keyword
+    /// argument, and never if the module has been deserialized.
+    fn bytes(&self) -> RubyResult<Ref<'_, [u8]>> {
+        if self.is_deserialized() {
+            return Err(deserialized_error());
+        }
+
+        if !self.keep_bytes {
+            return Err(to_ruby_err::<RuntimeError, _>(
+                "The WebAssembly bytes of the module aren't kept, create it with `keep_bytes: true`",
+            ));
+        }
+
+        Ok(Ref::map(self.bytes.borrow(), |bytes| {
+            bytes.as_deref().unwrap_or_default()
+        }))
+    }
+
+    /// Returns the structure of the module and the names declared by
+    /// its `name` custom section. They are read on the first call,
+    /// after which the bytes are released unless they are kept.
+    fn read_info(&self) -> RubyResult<Ref<'_, (Info, Names)>> {
+        if self.info.borrow().is_none() {
+            let info = match &*self.bytes.borrow() {
+                Some(bytes) => info::read(bytes).map_err(to_ruby_err::<RuntimeError, _>)?,
+                None => return Err(deserialized_error()),
+            };
+
+            *self.info.borrow_mut() = Some(info);
+
+            if !self.keep_bytes {
+                *self.bytes.borrow_mut() = None;
+            }
+        }
+
+        Ok(Ref::map(self.info.borrow(), |info| {
+            info.as_ref()
+                .expect("The structure of the module has just been read")
+        }))
+    }
+
+    /// Returns the names of the exported functions, indexed by their
+    /// export names.
+    pub(crate) fn exported_function_names(&self) -> HashMap<String, String> {
+        self.inner()
+            .info()
+            .exports
+            .iter()
+            .filter_map(|(export_name, index)| match index {
+                wasmer::ExportIndex::Function(index) => {
+                    Some((export_name.clone(), self.names.function(index.as_u32())))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the names of the functions defined by the module,
+    /// indexed by the address of their compiled body, so that the
+    /// functions read from a table are named like the exported ones.
+    /// They are collected on the first call.
+    pub(crate) fn function_names_by_body(&self) -> FunctionNames {
+        self.names_by_body
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let info = self.inner().info();
+
+                FunctionNames::new(
+                    self.inner()
+                        .artifact()
+                        .finished_functions()
+                        .iter()
+                        .map(|(local_index, body)| {
+                            (
+                                body.0 as usize,
+                                self.names.function(info.func_index(local_index).as_u32()),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .clone()
+    }
+
+    /// Returns the payload of the custom section `name`. If there are
+    /// several sections with this name, the last one wins.
+    fn custom_section(&self, name: &str) -> Option<Box<[u8]>> {
+        self.inner().custom_sections(name).last()
+    }
+
+    fn inner_mut(&mut self) -> &mut wasmer::Module {
+        &mut self.inner
+    }
+}
+
+fn deserialized_error() -> AnyException {
+    to_ruby_err::<RuntimeError, _>(
+        "The WebAssembly bytes of the module aren't available, because it has been deserialized",
+    )
+}
+
+/// Reads the `keep_bytes` keyword argument of `Module.new` and
+/// `Module.from_file`.
+fn unwrap_keep_bytes(options: Option<&Hash>) -> RubyResult<bool> {
+    options::check_keys(options, &["keep_bytes"])?;
+
+    Ok(options::get_bool(options, "keep_bytes")?.unwrap_or(false))
+}
+
+fn unwrap_validation_features(
+    store: &Store,
+    options: Option<&Hash>,
+) -> RubyResult<wasmer::Features> {
+    options::check_keys(options, &["features"])?;
+
+    Ok(match options::get(options, "features") {
+        Some(object) => features::unwrap_features(&object)?,
+        None => store.features().clone(),
+    })
+}
+
+fn to_compile_error(error: &wasmer::wasmparser::BinaryReaderError) -> AnyException {
+    to_wasmer_err(
+        "Wasmer::CompileError",
+        error,
+        &[
+            (
+                "offset",
+                Integer::new(error.offset() as i64).to_any_object(),
+            ),
+            ("reason", RString::new_utf8(error.message()).to_any_object()),
+        ],
+    )
+}
+
+#[rubymethods]
+impl Module {
+    pub fn new(store: &Store, bytes: &AnyObject, options: Option<&Hash>) -> RubyResult<AnyObject> {
+        let keep_bytes = unwrap_keep_bytes(options)?;
+        let bytes = input::read(bytes)?;
+        let bytes = wat::to_binary(&bytes, None)?.into_owned();
+        let module = wasmer::Module::new(store.inner(), &bytes)
+            .map_err(|error| to_compile_err(&error, &error))?;
+
+        Ok(Module::ruby_new(Module::raw_new(
+            module, store, bytes, keep_bytes,
+        )))
+    }
+
+    pub fn from_file(
+        store: &Store,
+        path: &RString,
+        options: Option<&Hash>,
+    ) -> RubyResult<AnyObject> {
+        let keep_bytes = unwrap_keep_bytes(options)?;
+        let path = path.to_str();
+        let bytes = fs::read(path).map_err(|error| {
+            to_ruby_err::<IOError, _>(format!("Failed to read `{}`: {}", path, error))
+        })?;
+        let bytes = wat::to_binary(&bytes, Some(path))?.into_owned();
+        let module = wasmer::Module::new(store.inner(), &bytes).map_err(|error| {
+            to_compile_err(format!("Failed to compile `{}`: {}", path, error), &error)
+        })?;
+
+        Ok(Module::ruby_new(Module::raw_new(
+            module, store, bytes, keep_bytes,
+        )))
+    }
+
+    pub fn validate(
+        store: &Store,
+        bytes: &AnyObject,
+        options: Option<&Hash>,
+    ) -> RubyResult<Boolean> {
+        let features = unwrap_validation_features(store, options)?;
+        let bytes = input::read(bytes)?;
+
+        // Malformed WAT is as invalid as a malformed binary.
+        Ok(Boolean::new(match wat::to_binary(&bytes, None) {
+            Ok(bytes) => features::validate(&features, &bytes).is_ok(),
+            Err(_) => false,
+        }))
+    }
+
+    pub fn validate_or_raise(
+        store: &Store,
+        bytes: &AnyObject,
+        options: Option<&Hash>,
+    ) -> RubyResult<NilClass> {
+        let features = unwrap_validation_features(store, options)?;
+        let bytes = input::read(bytes)?;
+        let bytes = wat::to_binary(&bytes, None)?;
+
+        features::validate(&features, &bytes).map_err(|error| to_compile_error(&error))?;
+
+        Ok(NilClass::new())
+    }
+
+    pub fn validation_errors(
+        store: &Store,
+        bytes: &AnyObject,
+        options: Option<&Hash>,
+    ) -> RubyResult<Array> {
+        let features = unwrap_validation_features(store, options)?;
+        let bytes = input::read(bytes)?;
+        let bytes = wat::to_binary(&bytes, None)?;
+
+        Ok(features::validation_errors(&features, &bytes)
+            .iter()
+            .map(|error| {
+                let mut hash = Hash::new();
+                hash.store(Symbol::new("offset"), Integer::new(error.offset() as i64));
+                hash.store(Symbol::new("reason"), RString::new_utf8(error.message()));
+
+                hash.to_any_object()
+            })
+            .collect())
+    }
+
+    pub fn get_name(&self) -> RubyResult<AnyObject> {
+        Ok(self.inner().name().map_or_else(
+            || NilClass::new().to_any_object(),
+            |name| RString::new_utf8(name).to_any_object(),
+        ))
+    }
+
+    pub fn set_name(&mut self, name: &RString) -> RubyResult<NilClass> {
+        self.inner_mut().set_name(name.to_str());
+
+        Ok(NilClass::new())
+    }
+
+    pub fn exports(&self) -> RubyResult<Array> {
+        let exports = self.inner.exports();
+        let mut array = Array::with_capacity(exports.len());
+
+        for export_type in exports.map(|export_type| ExportType::try_from(export_type)) {
+            array.push(ExportType::ruby_new(export_type?));
+        }
+
+        Ok(array)
+    }
+
+    pub fn imports(&self) -> RubyResult<Array> {
+        let imports = self.inner.imports();
+        let mut array = Array::with_capacity(imports.len());
+
+        for import_type in imports.map(|import_type| ImportType::try_from(import_type)) {
+            array.push(ImportType::ruby_new(import_type?));
+        }
+
+        Ok(array)
+    }
+
+    pub fn custom_section_names(&self) -> RubyResult<Array> {
+        Ok(self
+            .inner()
+            .info()
+            .custom_sections
+            .keys()
+            .map(|name| RString::new_utf8(name).to_any_object())
+            .collect())
+    }
+
+    pub fn custom_sections(&self, name: &RString) -> RubyResult<Array> {
+        let binary = Encoding::find("ASCII-8BIT")?;
+
+        Ok(self
+            .inner()
+            .custom_sections(name.to_str())
+            .map(|custom_section| RString::from_bytes(&custom_section, &binary).to_any_object())
+            .collect())
+    }
+
+    pub fn producers(&self) -> RubyResult<AnyObject> {
+        custom_sections::or_nil(
+            self.custom_section("producers")
+                .map(|data| custom_sections::producers(&data)),
+        )
+    }
+
+    pub fn target_features(&self) -> RubyResult<AnyObject> {
+        custom_sections::or_nil(
+            self.custom_section("target_features")
+                .map(|data| custom_sections::target_features(&data)),
+        )
+    }
+
+    pub fn source_mapping_url(&self) -> RubyResult<AnyObject> {
+        custom_sections::or_nil(
+            self.custom_section("sourceMappingURL")
+                .map(|data| custom_sections::source_mapping_url(&data)),
+        )
+    }
+
+    pub fn dylink(&self) -> RubyResult<AnyObject> {
+        custom_sections::or_nil(
+            self.custom_section("dylink.0")
+                .map(|data| custom_sections::dylink(&data)),
+        )
+    }
+
+    pub fn serialize(&self, options: Option<&Hash>) -> RubyResult<RString> {
+        options::check_keys(options, &["key"])?;
+
+        let key = options::get_bytes(options, "key")?;
+
+        Ok(RString::from_bytes(
+            &artifact::serialize(&self.store, self.inner(), key.as_deref())?,
+            &Encoding::find("ASCII-8BIT")?,
+        ))
+    }
+
+    pub fn serialize_to_file(
+        &self,
+        path: &RString,
+        options: Option<&Hash>,
+    ) -> RubyResult<NilClass> {
+        options::check_keys(options, &["key"])?;
+
+        let key = options::get_bytes(options, "key")?;
+        let path = path.to_str();
+
+        fs::write(
+            path,
+            artifact::serialize(&self.store, self.inner(), key.as_deref())?,
+        )
+        .map_err(|error| {
+            to_ruby_err::<IOError, _>(format!("Failed to write `{}`: {}", path, error))
+        })?;
+
+        Ok(NilClass::new())
+    }
+
+    pub fn deserialize(
+        store: &Store,
+        bytes: &AnyObject,
+        options: Option<&Hash>,
+    ) -> RubyResult<AnyObject> {
+        options::check_keys(options, &["key"])?;
+
+        let key = options::get_bytes(options, "key")?;
+        let bytes = input::read(bytes)?;
+        let module = artifact::deserialize(store, &bytes, key.as_deref())?;
+
+        Ok(Module::ruby_new(Module::raw_deserialized(module, store)))
+    }
+
+    pub fn deserialize_from_file(
+        store: &Store,
+        path: &RString,
+        options: Option<&Hash>,
+    ) -> RubyResult<AnyObject> {
+        options::check_keys(options, &["key"])?;
+
+        let key = options::get_bytes(options, "key")?;
+        let path = path.to_str();
+        let bytes = fs::read(path).map_err(|error| {
+            to_ruby_err::<IOError, _>(format!("Failed to read `{}`: {}", path, error))
+        })?;
+        let module = artifact::deserialize(store, &bytes, key.as_deref())?;
+
+        Ok(Module::ruby_new(Module::raw_deserialized(module, store)))
+    }
+
+    pub fn function_names(&self) -> RubyResult<Array> {
+        Ok((0..self.inner().info().functions.len() as u32)
+            .map(|index| RString::new_utf8(&self.names.function(index)).to_any_object())
+            .collect())
+    }
+
+    pub fn to_wat(&self, options: Option<&Hash>) -> RubyResult<RString> {
+        Ok(RString::new_utf8(&wat::print(&self.bytes()?, options)?))
+    }
+
+    pub fn info(&self) -> RubyResult<Hash> {
+        let info = self.read_info()?;
+        let (info, names) = &*info;
+
+        info.to_hash(names)
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use wasmer::wasmparser::{BinaryReaderError, Name, NameSectionReader, NamingReader};
+
+/// The function and local names declared by the `name` custom
+/// section of a module. The module name is read by the engine, see
+/// `Module#name`.
+#[derive(Default)]
+pub(crate) struct Names {
+    pub(crate) functions: HashMap<u32, String>,
+    pub(crate) locals: HashMap<u32, HashMap<u32, String>>,
+}
+
+impl Names {
+    /// Collects the function names the engine has read from the
+    /// `name` custom section when compiling the module. The local
+    /// names are left aside, see `Module#info`.
+    pub(crate) fn from_engine(module: &wasmer::Module) -> Self {
+        Self {
+            functions: module
+                .info()
+                .function_names
+                .iter()
+                .map(|(index, name)| (index.as_u32(), name.clone()))
+                .collect(),
+            locals: HashMap::new(),
+        }
+    }
+
+    /// Reads the content of a `name` custom section.
+    pub(crate) fn read_section(data: &[u8], data_offset: usize) -> Result<Self, BinaryReaderError> {
+        let mut names = Self::default();
+
+        for name in NameSectionReader::new(data, data_offset)? {
+            match name? {
+                Name::Function(function_names) => {
+                    names.functions = read_map(function_names.get_map()?)?;
+                }
+
+                Name::Local(local_names) => {
+                    let mut reader = local_names.get_function_local_reader()?;
+
+                    for _ in 0..reader.get_count() {
+                        let function = reader.read()?;
+
+                        names
+                            .locals
+                            .insert(function.func_index, read_map(function.get_map()?)?);
+                    }
+                }
+
+                Name::Module(_) | Name::Unknown { .. } => (),
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Returns the name of the function at `index`, or `func[index]`
+    /// if it's unnamed.
+    pub(crate) fn function(&self, index: u32) -> String {
+        match self.functions.get(&index) {
+            Some(name) => name.clone(),
+            None => format!("func[{}]", index),
+        }
+    }
+}
+
+fn read_map(mut reader: NamingReader) -> Result<HashMap<u32, String>, BinaryReaderError> {
+    let mut map = HashMap::new();
+
+    for _ in 0..reader.get_count() {
+        let naming = reader.read()?;
+
+        map.insert(naming.index, naming.name.to_string());
+    }
+
+    Ok(map)
+}
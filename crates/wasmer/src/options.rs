@@ -0,0 +1,79 @@
+//! Functions to read options, i.e. keyword arguments, which Ruby
+//! passes as a trailing `Hash` to the methods.
+
+use crate::error::{to_ruby_err, ArgumentError, RubyResult};
+use rutie::{AnyObject, Boolean, Hash, Integer, Object, RString, Symbol};
+
+/// Checks that `options` only contains known keys, so that a typo
+/// doesn't silently get ignored.
+pub(crate) fn check_keys(options: Option<&Hash>, known_keys: &[&str]) -> RubyResult<()> {
+    let options = match options {
+        Some(options) => options,
+        None => return Ok(()),
+    };
+
+    let mut unknown_keys = Vec::new();
+
+    options.each(|key, _value| {
+        match key.try_convert_to::<Symbol>() {
+            Ok(key) if known_keys.contains(&key.to_str()) => (),
+            Ok(key) => unknown_keys.push(format!(":{}", key.to_str())),
+            Err(_) => unknown_keys.push(format!("{:?}", key.ty())),
+        };
+    });
+
+    if unknown_keys.is_empty() {
+        Ok(())
+    } else {
+        Err(to_ruby_err::<ArgumentError, _>(format!(
+            "unknown keyword{}: {}",
+            if unknown_keys.len() > 1 { "s" } else { "" },
+            unknown_keys.join(", ")
+        )))
+    }
+}
+
+/// Reads the option named `key`. It returns `None` if the option is
+/// absent or `nil`.
+pub(crate) fn get(options: Option<&Hash>, key: &str) -> Option<AnyObject> {
+    options
+        .map(|options| options.at(&Symbol::new(key)))
+        .filter(|value| !value.is_nil())
+}
+
+/// Reads the boolean option named `key`. It returns `None` if the
+/// option is absent or `nil`.
+pub(crate) fn get_bool(options: Option<&Hash>, key: &str) -> RubyResult<Option<bool>> {
+    get(options, key)
+        .map(|value| Ok(value.try_convert_to::<Boolean>()?.to_bool()))
+        .transpose()
+}
+
+/// Reads the string option named `key`. It returns `None` if the
+/// option is absent or `nil`.
+pub(crate) fn get_string(options: Option<&Hash>, key: &str) -> RubyResult<Option<String>> {
+    get(options, key)
+        .map(|value| Ok(value.try_convert_to::<RString>()?.to_string()))
+        .transpose()
+}
+
+/// Reads the option named `key` as the bytes of a string, which
+/// can be binary. It returns `None` if the option is absent or `nil`.
+pub(crate) fn get_bytes(options: Option<&Hash>, key: &str) -> RubyResult<Option<Vec<u8>>> {
+    get(options, key)
+        .map(|value| {
+            Ok(value
+                .try_convert_to::<RString>()?
+                .to_bytes_unchecked()
+                .to_vec())
+        })
+        .transpose()
+}
+
+/// Reads the integer option named `key`. It returns `None` if the
+/// option is absent or `nil`.
+pub(crate) fn get_integer(options: Option<&Hash>, key: &str) -> RubyResult<Option<i64>> {
+    get(options, key)
+        .map(|value| Ok(value.try_convert_to::<Integer>()?.to_i64()))
+        .transpose()
+}
@@ -1,3 +1,3 @@
 pub use crate::error::RubyResult;
 pub use lazy_static::lazy_static;
-pub use rutie_derive::{rubyclass, rubyfunction, rubymethods, ClassInfo, UpcastRubyClass};
+pub use rutie_derive::{rubyclass, rubyfunction, rubymethods, ClassInfo, Mark, UpcastRubyClass};
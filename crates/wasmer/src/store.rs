@@ -1,22 +1,123 @@
-use crate::prelude::*;
-use rutie::AnyObject;
+use crate::{
+    features::{self, Features},
+    gc::Roots,
+    options,
+    prelude::*,
+    target::{self, Target},
+};
+use rutie::{AnyObject, Boolean, Hash};
 
-#[rubyclass(module = "Wasmer")]
+#[rubyclass(module = "Wasmer", mark)]
+#[derive(Clone)]
 pub struct Store {
     inner: wasmer::Store,
+    features: wasmer::Features,
+    target: wasmer::Target,
+    /// The Ruby objects referenced by WebAssembly, i.e. by the
+    /// `externref`s of the store.
+    roots: Roots,
+}
+
+impl Mark for Store {
+    fn mark(&self) {
+        self.roots.mark();
+    }
 }
 
 impl Store {
     pub(crate) fn inner(&self) -> &wasmer::Store {
         &self.inner
     }
+
+    pub(crate) fn roots(&self) -> &Roots {
+        &self.roots
+    }
+
+    pub(crate) fn features(&self) -> &wasmer::Features {
+        &self.features
+    }
+
+    /// Names the compiler and the engine of the store.
+    pub(crate) fn engine_name(&self) -> &'static str {
+        "cranelift universal"
+    }
+
+    pub(crate) fn target(&self) -> &wasmer::Target {
+        &self.target
+    }
+
+    /// Describes the target the store compiles for.
+    pub(crate) fn target_descriptor(&self) -> String {
+        target::describe(self.target())
+    }
+
+    /// Describes the compiler, the engine, the target and the
+    /// features of the store, i.e. everything a compiled module
+    /// depends on.
+    pub(crate) fn engine_descriptor(&self) -> String {
+        format!(
+            "{} {} {:?}",
+            self.engine_name(),
+            self.target_descriptor(),
+            self.features()
+        )
+    }
 }
 
 #[rubymethods]
 impl Store {
-    pub fn new() -> RubyResult<AnyObject> {
+    pub fn new(options: Option<&Hash>) -> RubyResult<AnyObject> {
+        options::check_keys(options, &["features", "threads", "target"])?;
+
+        let mut features = match options::get(options, "features") {
+            Some(object) => features::unwrap_features(&object)?,
+            None => wasmer::Features::default(),
+        };
+
+        if let Some(threads) = options::get_bool(options, "threads")? {
+            features.threads(threads);
+        }
+
+        let target = match options::get(options, "target") {
+            Some(object) => target::unwrap_target(&object)?,
+            None => wasmer::Target::default(),
+        };
+
+        // Keep the default store when nothing is customized;
+        // otherwise, configure the default compiler and engine
+        // (Cranelift and Universal) with the features and the
+        // target.
+        let inner =
+            if features == wasmer::Features::default() && target == wasmer::Target::default() {
+                wasmer::Store::default()
+            } else {
+                let engine = wasmer::Universal::new(wasmer::Cranelift::default())
+                    .target(target.clone())
+                    .features(features.clone())
+                    .engine();
+
+                wasmer::Store::new(&engine)
+            };
+
         Ok(Store::ruby_new(Store {
-            inner: Default::default(),
+            inner,
+            features,
+            target,
+            roots: Roots::default(),
         }))
     }
+
+    pub fn features(&self) -> RubyResult<AnyObject> {
+        Ok(Features::ruby_new(Features::raw_new(
+            self.features().clone(),
+        )))
+    }
+
+    pub fn target(&self) -> RubyResult<AnyObject> {
+        Ok(Target::ruby_new(Target::raw_new(self.target().clone())))
+    }
+
+    pub fn threads(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.features().threads))
+    }
 }
@@ -0,0 +1,135 @@
+use crate::{
+    error::{to_ruby_err, ArgumentError, RuntimeError},
+    options,
+    prelude::*,
+};
+use rutie::{AnyObject, Array, Boolean, Hash, Object, RString};
+use std::str::FromStr;
+
+#[rubyclass(module = "Wasmer")]
+pub struct Target {
+    inner: wasmer::Target,
+}
+
+impl Target {
+    pub(crate) fn raw_new(inner: wasmer::Target) -> Self {
+        Self { inner }
+    }
+
+    pub(crate) fn inner(&self) -> &wasmer::Target {
+        &self.inner
+    }
+}
+
+/// Reads a `Target` object, as received from a keyword argument.
+pub(crate) fn unwrap_target(target: &AnyObject) -> RubyResult<wasmer::Target> {
+    Ok(target
+        .try_convert_to::<RubyTarget>()?
+        .upcast()
+        .inner()
+        .clone())
+}
+
+/// Tells whether the code compiled for `target` can run on the host,
+/// i.e. whether the triples are the same and the host supports all
+/// the CPU features of `target`.
+pub(crate) fn is_host(target: &wasmer::Target) -> bool {
+    target.triple() == &wasmer::Triple::host()
+        && target
+            .cpu_features()
+            .is_subset(wasmer::CpuFeature::for_host())
+}
+
+/// Checks that the code compiled for `target` can run on the host.
+pub(crate) fn check_host(target: &wasmer::Target) -> RubyResult<()> {
+    if is_host(target) {
+        Ok(())
+    } else {
+        Err(to_ruby_err::<RuntimeError, _>(format!(
+            "The module has been compiled for the target `{}`, which can't run on this host (`{}`); it can only be serialized",
+            describe(target),
+            describe(&wasmer::Target::default())
+        )))
+    }
+}
+
+/// Describes a target by its triple and its CPU features, e.g.
+/// `x86_64-unknown-linux-gnu (sse2, sse3)`.
+pub(crate) fn describe(target: &wasmer::Target) -> String {
+    format!(
+        "{} ({})",
+        target.triple(),
+        target
+            .cpu_features()
+            .iter()
+            .map(|feature| feature.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+#[rubymethods]
+impl Target {
+    pub fn new(options: Option<&Hash>) -> RubyResult<AnyObject> {
+        options::check_keys(options, &["triple", "cpu_features"])?;
+
+        let triple = match options::get_string(options, "triple")? {
+            Some(triple) => wasmer::Triple::from_str(&triple).map_err(|error| {
+                to_ruby_err::<ArgumentError, _>(format!(
+                    "Invalid target triple `{}`: {}",
+                    triple, error
+                ))
+            })?,
+            None => wasmer::Triple::host(),
+        };
+
+        let cpu_features = match options::get(options, "cpu_features") {
+            Some(names) => {
+                let mut cpu_features = wasmer::CpuFeature::set();
+
+                for name in names.try_convert_to::<Array>()? {
+                    let name = name.try_convert_to::<RString>()?;
+
+                    cpu_features.insert(wasmer::CpuFeature::from_str(name.to_str()).map_err(
+                        |_| {
+                            to_ruby_err::<ArgumentError, _>(format!(
+                                "Unknown CPU feature `{}`",
+                                name.to_str()
+                            ))
+                        },
+                    )?);
+                }
+
+                cpu_features
+            }
+            None if triple == wasmer::Triple::host() => wasmer::CpuFeature::for_host(),
+            None => wasmer::CpuFeature::set(),
+        };
+
+        Ok(Target::ruby_new(Target::raw_new(wasmer::Target::new(
+            triple,
+            cpu_features,
+        ))))
+    }
+
+    pub fn triple(&self) -> RubyResult<RString> {
+        Ok(RString::new_utf8(&self.inner().triple().to_string()))
+    }
+
+    pub fn cpu_features(&self) -> RubyResult<Array> {
+        Ok(self
+            .inner()
+            .cpu_features()
+            .iter()
+            .map(|feature| RString::new_utf8(&feature.to_string()).to_any_object())
+            .collect())
+    }
+
+    pub fn is_host(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(is_host(self.inner())))
+    }
+
+    pub fn to_s(&self) -> RubyResult<RString> {
+        Ok(RString::new_utf8(&describe(self.inner())))
+    }
+}
@@ -18,7 +18,7 @@ pub enum Type {
 }
 
 impl Type {
-    fn to_integer(&self) -> Integer {
+    pub(crate) fn to_integer(&self) -> Integer {
         match self {
             Self::I32 => Integer::new(1),
             Self::I64 => Integer::new(2),
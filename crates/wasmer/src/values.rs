@@ -1,11 +1,18 @@
 use crate::{
     error::{to_ruby_err, RubyResult, TypeError},
+    externals::function::{Function, FunctionNames, RubyFunction},
+    gc::{Held, Roots},
     prelude::*,
 };
-use rutie::{AnyObject, Fixnum, Float, Object};
+use rutie::{AnyObject, Fixnum, Float, NilClass, Object};
 use std::convert::TryInto;
 
-pub(crate) fn to_wasm_value((any, ty): (&AnyObject, wasmer::Type)) -> RubyResult<wasmer::Value> {
+/// Converts `any` to a WebAssembly value of type `ty`. The object of
+/// an `externref` is held by `roots`.
+pub(crate) fn to_wasm_value(
+    (any, ty): (&AnyObject, wasmer::Type),
+    roots: &Roots,
+) -> RubyResult<wasmer::Value> {
     Ok(match ty {
         wasmer::Type::I32 => wasmer::Value::I32(
             any.try_convert_to::<Fixnum>()?
@@ -21,16 +28,52 @@ pub(crate) fn to_wasm_value((any, ty): (&AnyObject, wasmer::Type)) -> RubyResult
         ),
         wasmer::Type::F32 => wasmer::Value::F32(any.try_convert_to::<Float>()?.to_f64() as _),
         wasmer::Type::F64 => wasmer::Value::F64(any.try_convert_to::<Float>()?.to_f64()),
+        wasmer::Type::FuncRef => {
+            if any.is_nil() {
+                wasmer::Value::FuncRef(None)
+            } else if let Ok(function) = any.try_convert_to::<RubyFunction>() {
+                wasmer::Value::FuncRef(Some(function.upcast().inner().clone()))
+            } else {
+                return Err(to_ruby_err::<TypeError, _>(
+                    "A `funcref` must be either a `Function` or `nil`",
+                ));
+            }
+        }
+        wasmer::Type::ExternRef => {
+            if any.is_nil() {
+                wasmer::Value::ExternRef(wasmer::ExternRef::null())
+            } else {
+                // The object is kept alive by the owners of `roots`
+                // until the last reference held by WebAssembly is
+                // dropped.
+                wasmer::Value::ExternRef(wasmer::ExternRef::new(roots.hold(any)))
+            }
+        }
         _ => unimplemented!(),
     })
 }
 
-pub(crate) fn to_ruby_object(value: &wasmer::Value) -> AnyObject {
+/// Converts `value` to a Ruby object. A `funcref` is named after
+/// `names`, if its function is defined by their module, and keeps
+/// `roots` alive.
+pub(crate) fn to_ruby_object(
+    value: &wasmer::Value,
+    names: &FunctionNames,
+    roots: &Roots,
+) -> AnyObject {
     match value {
         wasmer::Value::I32(value) => Fixnum::new((*value).into()).to_any_object(),
         wasmer::Value::I64(value) => Fixnum::new(*value).to_any_object(),
         wasmer::Value::F32(value) => Float::new((*value).into()).to_any_object(),
         wasmer::Value::F64(value) => Float::new(*value).to_any_object(),
+        wasmer::Value::FuncRef(Some(function)) => {
+            Function::ruby_new(Function::raw_new_with_names(function.clone(), names, roots))
+        }
+        wasmer::Value::FuncRef(None) => NilClass::new().to_any_object(),
+        wasmer::Value::ExternRef(reference) => match reference.downcast::<Held>() {
+            Some(held) => held.object(),
+            None => NilClass::new().to_any_object(),
+        },
         _ => unimplemented!(),
     }
 }
@@ -0,0 +1,242 @@
+//! Clocks replacing the host's ones, so that the time read by a
+//! program is reproducible.
+
+use super::stdio::Rooted;
+use crate::{
+    error::{to_ruby_err, ArgumentError, TypeError},
+    gc::Roots,
+    prelude::*,
+};
+use rutie::{AnyObject, Float, Integer, Object, Symbol};
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+};
+use wasmer::{LazyInit, WasmPtr, WasmerEnv};
+use wasmer_wasi::types::{
+    __wasi_clockid_t, __wasi_errno_t, __wasi_timestamp_t, __WASI_CLOCK_MONOTONIC,
+    __WASI_CLOCK_PROCESS_CPUTIME_ID, __WASI_CLOCK_REALTIME, __WASI_CLOCK_THREAD_CPUTIME_ID,
+    __WASI_EFAULT, __WASI_EINVAL, __WASI_ESUCCESS,
+};
+
+const NANOSECONDS_PER_SECOND: f64 = 1_000_000_000.0;
+
+/// Where the time comes from.
+enum Source {
+    /// A time starting at `now`, and advancing by `step` each time
+    /// it's read. All the clocks share it.
+    Fixed { now: u64, step: u64 },
+    /// A block receiving the clock name, and returning the time.
+    Block(Rooted),
+}
+
+/// A clock given to the program instead of the host's ones. It's
+/// shared by all the instances using the same `Environment`.
+#[derive(Clone)]
+pub(crate) struct Clock {
+    source: Arc<Mutex<Source>>,
+}
+
+/// Reads a time, given as a `Time`, or as an `Integer` of
+/// nanoseconds. Times before the Unix epoch, or too far in the
+/// future to be represented in nanoseconds, raise an
+/// `ArgumentError`.
+fn to_nanoseconds(time: &AnyObject) -> RubyResult<u64> {
+    let invalid_time = || {
+        to_ruby_err::<ArgumentError, _>("The time must be between the Unix epoch and the year 2554")
+    };
+
+    if let Ok(nanoseconds) = time.try_convert_to::<Integer>() {
+        return u64::try_from(nanoseconds.to_i64()).map_err(|_| invalid_time());
+    }
+
+    if time.respond_to("to_i") && time.respond_to("nsec") {
+        let seconds = time
+            .protect_send("to_i", &[])?
+            .try_convert_to::<Integer>()?
+            .to_i64();
+        let nanoseconds = time
+            .protect_send("nsec", &[])?
+            .try_convert_to::<Integer>()?
+            .to_i64();
+
+        return u64::try_from(seconds)
+            .ok()
+            .zip(u64::try_from(nanoseconds).ok())
+            .and_then(|(seconds, nanoseconds)| {
+                seconds
+                    .checked_mul(NANOSECONDS_PER_SECOND as u64)?
+                    .checked_add(nanoseconds)
+            })
+            .ok_or_else(invalid_time);
+    }
+
+    Err(to_ruby_err::<TypeError, _>(format!(
+        "Expected a `Time` or an `Integer` of nanoseconds, received `{:?}`",
+        time.ty()
+    )))
+}
+
+/// Reads a duration, given in seconds as an `Integer` or a `Float`,
+/// and returns it in nanoseconds. Negative durations, or durations
+/// too long to be represented in nanoseconds, raise an
+/// `ArgumentError`.
+fn to_duration(seconds: &AnyObject) -> RubyResult<u64> {
+    let invalid_duration = || {
+        to_ruby_err::<ArgumentError, _>(
+            "The duration must be a positive number of seconds, less than 584 years",
+        )
+    };
+
+    if let Ok(seconds) = seconds.try_convert_to::<Integer>() {
+        return u64::try_from(seconds.to_i64())
+            .ok()
+            .and_then(|seconds| seconds.checked_mul(NANOSECONDS_PER_SECOND as u64))
+            .ok_or_else(invalid_duration);
+    }
+
+    if let Ok(seconds) = seconds.try_convert_to::<Float>() {
+        let nanoseconds = (seconds.to_f64() * NANOSECONDS_PER_SECOND).round();
+
+        // `u64::MAX` isn't representable as a `f64`, which rounds it
+        // up to 2^64.
+        return if (0.0..u64::MAX as f64).contains(&nanoseconds) {
+            Ok(nanoseconds as u64)
+        } else {
+            Err(invalid_duration())
+        };
+    }
+
+    Err(to_ruby_err::<TypeError, _>(format!(
+        "Expected a number of seconds, received `{:?}`",
+        seconds.ty()
+    )))
+}
+
+fn clock_name(clock_id: __wasi_clockid_t) -> Option<&'static str> {
+    Some(match clock_id {
+        __WASI_CLOCK_REALTIME => "realtime",
+        __WASI_CLOCK_MONOTONIC => "monotonic",
+        __WASI_CLOCK_PROCESS_CPUTIME_ID => "process_cputime",
+        __WASI_CLOCK_THREAD_CPUTIME_ID => "thread_cputime",
+        _ => return None,
+    })
+}
+
+impl Clock {
+    /// Creates a clock starting at `now`, and advancing by `step`
+    /// seconds each time it's read.
+    pub(crate) fn fixed(now: &AnyObject, step: Option<&AnyObject>) -> RubyResult<Self> {
+        Ok(Self::new(Source::Fixed {
+            now: to_nanoseconds(now)?,
+            step: step.map(to_duration).transpose()?.unwrap_or(0),
+        }))
+    }
+
+    /// Creates a clock calling `block` with the clock name, e.g.
+    /// `:realtime`, each time it's read.
+    pub(crate) fn from_block(block: rutie::Proc, roots: &Roots) -> Self {
+        Self::new(Source::Block(Rooted::new(block.to_any_object(), roots)))
+    }
+
+    /// Names where the time comes from, i.e. `fixed` or `block`.
+    pub(crate) fn kind(&self) -> &'static str {
+        match &*self.source.lock().unwrap() {
+            Source::Fixed { .. } => "fixed",
+            Source::Block(_) => "block",
+        }
+    }
+
+    fn new(source: Source) -> Self {
+        Self {
+            source: Arc::new(Mutex::new(source)),
+        }
+    }
+
+    fn now(&self, clock_name: &str) -> Result<u64, wasmer::RuntimeError> {
+        match &mut *self.source.lock().unwrap() {
+            Source::Fixed { now, step } => {
+                let time = *now;
+                *now = now.saturating_add(*step);
+
+                Ok(time)
+            }
+            Source::Block(block) => {
+                let time = block
+                    .send("call", &[Symbol::new(clock_name).to_any_object()])
+                    .map_err(|error| wasmer::RuntimeError::new(error.to_string()))?;
+
+                to_nanoseconds(&time).map_err(|error| {
+                    wasmer::RuntimeError::new(format!("The clock block failed: {}", error))
+                })
+            }
+        }
+    }
+
+    /// Creates the `clock_res_get` and `clock_time_get` functions
+    /// reading this clock.
+    pub(crate) fn imports(&self, store: &wasmer::Store) -> Vec<(&'static str, wasmer::Function)> {
+        let env = ClockEnv {
+            clock: self.clone(),
+            memory: LazyInit::new(),
+        };
+
+        vec![
+            (
+                "clock_res_get",
+                wasmer::Function::new_native_with_env(store, env.clone(), clock_res_get),
+            ),
+            (
+                "clock_time_get",
+                wasmer::Function::new_native_with_env(store, env, clock_time_get),
+            ),
+        ]
+    }
+}
+
+#[derive(Clone, WasmerEnv)]
+struct ClockEnv {
+    clock: Clock,
+    #[wasmer(export)]
+    memory: LazyInit<wasmer::Memory>,
+}
+
+fn clock_res_get(
+    env: &ClockEnv,
+    clock_id: __wasi_clockid_t,
+    resolution: WasmPtr<__wasi_timestamp_t>,
+) -> __wasi_errno_t {
+    if clock_name(clock_id).is_none() {
+        return __WASI_EINVAL;
+    }
+
+    match env.memory_ref().and_then(|memory| resolution.deref(memory)) {
+        Some(resolution) => {
+            resolution.set(1);
+
+            __WASI_ESUCCESS
+        }
+        None => __WASI_EFAULT,
+    }
+}
+
+fn clock_time_get(
+    env: &ClockEnv,
+    clock_id: __wasi_clockid_t,
+    _precision: __wasi_timestamp_t,
+    time: WasmPtr<__wasi_timestamp_t>,
+) -> Result<__wasi_errno_t, wasmer::RuntimeError> {
+    let clock_name = match clock_name(clock_id) {
+        Some(clock_name) => clock_name,
+        None => return Ok(__WASI_EINVAL),
+    };
+
+    let time = match env.memory_ref().and_then(|memory| time.deref(memory)) {
+        Some(time) => time,
+        None => return Ok(__WASI_EFAULT),
+    };
+
+    time.set(env.clock.now(clock_name)?);
+
+    Ok(__WASI_ESUCCESS)
+}
@@ -0,0 +1,174 @@
+//! An in-memory filesystem, which can be populated and read from Ruby,
+//! and mounted into the WASI programs, so that they never touch the
+//! host's disk.
+
+use crate::{
+    error::{to_ruby_err, IOError},
+    prelude::*,
+    wasi::stdio::to_binary_string,
+};
+use rutie::{AnyException, AnyObject, Array, Boolean, NilClass, Object, RString};
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+use wasmer_vfs::{mem_fs, FileSystem, FsError};
+
+#[rubyclass(module = "Wasmer::Wasi")]
+pub struct MemFS {
+    inner: mem_fs::FileSystem,
+}
+
+impl MemFS {
+    /// Returns the filesystem. Clones share the same content.
+    pub(crate) fn inner(&self) -> &mem_fs::FileSystem {
+        &self.inner
+    }
+
+    /// Creates the directory at `path`, and its missing parents.
+    fn create_dir_all(&self, path: &Path) -> Result<(), FsError> {
+        for ancestor in path.ancestors().collect::<Vec<_>>().into_iter().rev() {
+            if ancestor == Path::new("/") || ancestor.as_os_str().is_empty() {
+                continue;
+            }
+
+            match self.inner.create_dir(ancestor) {
+                Ok(()) | Err(FsError::AlreadyExists) => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_path(path: &RString) -> PathBuf {
+    Path::new("/").join(path.to_str())
+}
+
+fn to_fs_err(action: &str, path: &Path, error: FsError) -> AnyException {
+    to_ruby_err::<IOError, _>(format!(
+        "Failed to {} `{}`: {}",
+        action,
+        path.display(),
+        error
+    ))
+}
+
+#[rubymethods]
+impl MemFS {
+    pub fn new() -> RubyResult<AnyObject> {
+        Ok(MemFS::ruby_new(MemFS {
+            inner: mem_fs::FileSystem::default(),
+        }))
+    }
+
+    pub fn write(&self, path: &RString, bytes: &RString) -> RubyResult<NilClass> {
+        let path = to_path(path);
+
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)
+                .map_err(|error| to_fs_err("create", parent, error))?;
+        }
+
+        let mut file = self
+            .inner
+            .new_open_options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|error| to_fs_err("write", &path, error))?;
+
+        file.write_all(bytes.to_bytes_unchecked())
+            .map_err(|error| to_fs_err("write", &path, error.into()))?;
+
+        Ok(NilClass::new())
+    }
+
+    pub fn read(&self, path: &RString) -> RubyResult<RString> {
+        let path = to_path(path);
+        let mut bytes = Vec::new();
+
+        self.inner
+            .new_open_options()
+            .read(true)
+            .open(&path)
+            .and_then(|mut file| file.read_to_end(&mut bytes).map_err(Into::into))
+            .map_err(|error| to_fs_err("read", &path, error))?;
+
+        to_binary_string(&bytes)
+    }
+
+    pub fn mkdir(&self, path: &RString) -> RubyResult<NilClass> {
+        let path = to_path(path);
+
+        self.create_dir_all(&path)
+            .map_err(|error| to_fs_err("create", &path, error))?;
+
+        Ok(NilClass::new())
+    }
+
+    pub fn entries(&self, path: &RString) -> RubyResult<Array> {
+        let path = to_path(path);
+        let mut entries = self
+            .inner
+            .read_dir(&path)
+            .map_err(|error| to_fs_err("read", &path, error))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                entry
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .collect::<Vec<_>>();
+
+        entries.sort();
+
+        Ok(entries
+            .iter()
+            .map(|name| RString::new_utf8(name).to_any_object())
+            .collect())
+    }
+
+    pub fn exists(&self, path: &RString) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.inner.metadata(&to_path(path)).is_ok()))
+    }
+
+    pub fn is_file(&self, path: &RString) -> RubyResult<Boolean> {
+        Ok(Boolean::new(
+            self.inner
+                .metadata(&to_path(path))
+                .map(|metadata| metadata.is_file())
+                .unwrap_or(false),
+        ))
+    }
+
+    pub fn is_directory(&self, path: &RString) -> RubyResult<Boolean> {
+        Ok(Boolean::new(
+            self.inner
+                .metadata(&to_path(path))
+                .map(|metadata| metadata.is_dir())
+                .unwrap_or(false),
+        ))
+    }
+
+    pub fn remove(&self, path: &RString) -> RubyResult<NilClass> {
+        let path = to_path(path);
+        let is_directory = self
+            .inner
+            .metadata(&path)
+            .map_err(|error| to_fs_err("remove", &path, error))?
+            .is_dir();
+
+        if is_directory {
+            self.inner.remove_dir(&path)
+        } else {
+            self.inner.remove_file(&path)
+        }
+        .map_err(|error| to_fs_err("remove", &path, error))?;
+
+        Ok(NilClass::new())
+    }
+}
@@ -0,0 +1,1086 @@
+use crate::{
+    error::{to_ruby_err, unwrap_or_raise, ArgumentError, IOError, RuntimeError, TypeError},
+    externals::memory::Memory,
+    gc::Roots,
+    import_object::{ImportObject, RubyImportObject},
+    module::{Module, RubyModule},
+    options,
+    prelude::*,
+    store::Store,
+    types::ImportType,
+};
+use clock::Clock;
+use mem_fs::MemFS;
+use random::Random;
+use rutie::{
+    AnyObject, Array, Boolean, Class, Hash, Integer, NilClass, Object, RString, Symbol, VM,
+};
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+    sync::MutexGuard,
+};
+use stdio::{CapturedOutput, Input};
+
+mod clock;
+pub(crate) mod mem_fs;
+mod random;
+pub(crate) mod run;
+mod stdio;
+
+#[derive(Debug, Copy, Clone)]
+#[repr(u8)]
+pub enum Version {
+    Latest = 1,
+    Snapshot0 = 2,
+    Snapshot1 = 3,
+}
+
+impl Version {
+    fn to_integer(&self) -> Integer {
+        match self {
+            Self::Latest => Integer::new(1),
+            Self::Snapshot0 => Integer::new(2),
+            Self::Snapshot1 => Integer::new(3),
+        }
+    }
+
+    /// Returns the namespace of the WASI imports.
+    fn namespace_name(&self) -> &'static str {
+        match self {
+            Self::Snapshot0 => "wasi_unstable",
+            Self::Latest | Self::Snapshot1 => "wasi_snapshot_preview1",
+        }
+    }
+
+    /// Returns the name of the version, as used by `Wasi.detect`.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Snapshot0 => "snapshot0",
+            Self::Latest | Self::Snapshot1 => "snapshot1",
+        }
+    }
+}
+
+impl From<&wasmer_wasi::WasiVersion> for Version {
+    fn from(value: &wasmer_wasi::WasiVersion) -> Self {
+        match value {
+            wasmer_wasi::WasiVersion::Latest => Self::Latest,
+            wasmer_wasi::WasiVersion::Snapshot0 => Self::Snapshot0,
+            wasmer_wasi::WasiVersion::Snapshot1 => Self::Snapshot1,
+        }
+    }
+}
+
+impl Into<wasmer_wasi::WasiVersion> for Version {
+    fn into(self) -> wasmer_wasi::WasiVersion {
+        match self {
+            Self::Latest => wasmer_wasi::WasiVersion::Latest,
+            Self::Snapshot0 => wasmer_wasi::WasiVersion::Snapshot0,
+            Self::Snapshot1 => wasmer_wasi::WasiVersion::Snapshot1,
+        }
+    }
+}
+
+impl TryFrom<&Integer> for Version {
+    type Error = &'static str;
+
+    fn try_from(value: &Integer) -> Result<Self, Self::Error> {
+        Ok(match value.to_i32() {
+            1 => Version::Latest,
+            2 => Version::Snapshot0,
+            3 => Version::Snapshot1,
+            _ => return Err("Unrecognized WASI version"),
+        })
+    }
+}
+
+#[rubyclass(module = "Wasmer::Wasi", mark)]
+pub struct StateBuilder {
+    inner: wasmer_wasi::WasiStateBuilder,
+    program_name: String,
+    arguments: Vec<String>,
+    environments: Vec<(String, String)>,
+    stdin: &'static str,
+    stdout: Option<CapturedOutput>,
+    stderr: Option<CapturedOutput>,
+    fs: Option<wasmer_vfs::mem_fs::FileSystem>,
+    has_host_directories: bool,
+    directories: Vec<Directory>,
+    clock: Option<Clock>,
+    random: Option<Random>,
+    /// The objects given to the builder and held by the state, e.g.
+    /// the blocks capturing the outputs.
+    roots: Roots,
+}
+
+impl Mark for StateBuilder {
+    fn mark(&self) {
+        self.roots.mark();
+    }
+}
+
+impl StateBuilder {
+    fn add_arguments(&mut self, arguments: Vec<String>) -> RubyResult<()> {
+        for argument in arguments.iter() {
+            check_argument(argument)?;
+        }
+
+        self.inner.args(&arguments);
+        self.arguments.extend(arguments);
+
+        Ok(())
+    }
+
+    fn add_environments(&mut self, environments: Vec<(String, String)>) -> RubyResult<()> {
+        for (key, value) in environments.iter() {
+            check_environment(key, value)?;
+        }
+
+        self.inner.envs(environments.clone());
+        self.environments.extend(environments);
+
+        Ok(())
+    }
+
+    /// Records that a host directory is preopened. The host
+    /// directories and a `MemFS` are exclusive, since the WASI state
+    /// has a single filesystem.
+    fn add_host_directory(&mut self) -> RubyResult<()> {
+        if self.fs.is_some() {
+            return Err(to_ruby_err::<RuntimeError, _>(
+                "Host directories cannot be preopened along with a mounted `MemFS`",
+            ));
+        }
+
+        self.has_host_directories = true;
+
+        Ok(())
+    }
+}
+
+/// The permissions granted to the program on a preopened directory.
+pub(crate) struct Permissions {
+    read: bool,
+    write: bool,
+    create: bool,
+}
+
+impl Permissions {
+    /// Reads the `read:`, `write:` and `create:` options. The
+    /// directory is fully accessible by default, and `create:`
+    /// defaults to `write:`, so that `write: false` is enough to make
+    /// it read-only.
+    pub(crate) fn from_options(options: Option<&Hash>) -> RubyResult<Self> {
+        options::check_keys(options, &["read", "write", "create"])?;
+
+        let read = options::get_bool(options, "read")?.unwrap_or(true);
+        let write = options::get_bool(options, "write")?.unwrap_or(true);
+        let create = options::get_bool(options, "create")?.unwrap_or(write);
+
+        if create && !write {
+            return Err(to_ruby_err::<ArgumentError, _>(
+                "A directory cannot grant `create` without `write`",
+            ));
+        }
+
+        if !read && !write {
+            return Err(to_ruby_err::<ArgumentError, _>(
+                "A directory must grant at least one of `read`, `write` or `create`",
+            ));
+        }
+
+        Ok(Self {
+            read,
+            write,
+            create,
+        })
+    }
+
+    /// Preopens `directory` with these permissions, under `alias` if
+    /// any.
+    pub(crate) fn preopen(
+        &self,
+        state: &mut wasmer_wasi::WasiStateBuilder,
+        directory: &Path,
+        alias: Option<&str>,
+    ) -> RubyResult<()> {
+        state
+            .preopen(|preopen| {
+                let preopen = preopen
+                    .directory(directory)
+                    .read(self.read)
+                    .write(self.write)
+                    .create(self.create);
+
+                match alias {
+                    Some(alias) => preopen.alias(alias),
+                    None => preopen,
+                }
+            })
+            .map_err(to_ruby_err::<RuntimeError, _>)?;
+
+        Ok(())
+    }
+}
+
+/// Reads the standard input given as a `String`, or as an `IO`-like
+/// object responding to `read`, which is held by `roots`.
+fn to_input(input: &AnyObject, roots: &Roots) -> RubyResult<Input> {
+    match input.try_convert_to::<RString>() {
+        Ok(string) => Ok(Input::from_bytes(string.to_bytes_unchecked())),
+        Err(_) if input.respond_to("read") => Ok(Input::from_io(input.clone(), roots)),
+        Err(_) => Err(to_ruby_err::<TypeError, _>(format!(
+            "The standard input must be a `String`, an `IO`-like object responding to `read`, or a block, received `{:?}`",
+            input.ty()
+        ))),
+    }
+}
+
+/// Checks that an argument can be given to the program, i.e. that
+/// it doesn't contain a NUL byte.
+fn check_argument(argument: &str) -> RubyResult<()> {
+    if argument.contains('\0') {
+        return Err(to_ruby_err::<ArgumentError, _>(format!(
+            "The argument {:?} contains a NUL byte",
+            argument
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that an environment variable can be given to the program,
+/// i.e. that its key isn't empty and contains neither `=` nor a NUL
+/// byte, and that its value doesn't contain a NUL byte.
+fn check_environment(key: &str, value: &str) -> RubyResult<()> {
+    if key.is_empty() || key.contains('=') || key.contains('\0') {
+        return Err(to_ruby_err::<ArgumentError, _>(format!(
+            "The environment variable key {:?} must not be empty, nor contain `=` or a NUL byte",
+            key
+        )));
+    }
+
+    if value.contains('\0') {
+        return Err(to_ruby_err::<ArgumentError, _>(format!(
+            "The value of the environment variable `{}` contains a NUL byte",
+            key
+        )));
+    }
+
+    Ok(())
+}
+
+/// Reads an `Array` of `String`s.
+fn to_strings(array: &AnyObject) -> RubyResult<Vec<String>> {
+    array
+        .try_convert_to::<Array>()?
+        .into_iter()
+        .map(|string| Ok(string.try_convert_to::<RString>()?.to_string()))
+        .collect()
+}
+
+/// Reads a `Hash` of `String`s.
+fn to_string_pairs(hash: &AnyObject) -> RubyResult<Vec<(String, String)>> {
+    let hash = hash.try_convert_to::<Hash>()?;
+    let mut pairs = Vec::with_capacity(hash.length());
+
+    hash.each(|key, value| {
+        unwrap_or_raise(|| {
+            pairs.push((
+                key.try_convert_to::<RString>()?.to_string(),
+                value.try_convert_to::<RString>()?.to_string(),
+            ));
+
+            Ok(())
+        });
+    });
+
+    Ok(pairs)
+}
+
+/// Returns the block given to the current method, if any.
+fn block() -> Option<rutie::Proc> {
+    if VM::is_block_given() {
+        Some(VM::block_proc())
+    } else {
+        None
+    }
+}
+
+#[rubymethods]
+impl StateBuilder {
+    pub fn new(program_name: &RString) -> RubyResult<AnyObject> {
+        Ok(StateBuilder::ruby_new(StateBuilder {
+            inner: wasmer_wasi::WasiState::new(program_name.to_str()),
+            program_name: program_name.to_string(),
+            arguments: Vec::new(),
+            environments: Vec::new(),
+            stdin: "inherit",
+            stdout: None,
+            stderr: None,
+            fs: None,
+            has_host_directories: false,
+            directories: Vec::new(),
+            clock: None,
+            random: None,
+            roots: Roots::default(),
+        }))
+    }
+
+    pub fn arguments(&mut self, arguments: &Array) -> RubyResult<RubyStateBuilder> {
+        self.add_arguments(to_strings(&arguments.to_any_object())?)?;
+
+        Ok(_ruby_self)
+    }
+
+    pub fn argument(&mut self, argument: &RString) -> RubyResult<RubyStateBuilder> {
+        self.add_arguments(vec![argument.to_string()])?;
+
+        Ok(_ruby_self)
+    }
+
+    pub fn inherit_arguments(&mut self) -> RubyResult<RubyStateBuilder> {
+        let arguments = Class::from_existing("Object").const_get("ARGV");
+
+        self.add_arguments(to_strings(&arguments)?)?;
+
+        Ok(_ruby_self)
+    }
+
+    pub fn environments(&mut self, environments: &Hash) -> RubyResult<RubyStateBuilder> {
+        self.add_environments(to_string_pairs(&environments.to_any_object())?)?;
+
+        Ok(_ruby_self)
+    }
+
+    pub fn environment(&mut self, key: &RString, value: &RString) -> RubyResult<RubyStateBuilder> {
+        self.add_environments(vec![(key.to_string(), value.to_string())])?;
+
+        Ok(_ruby_self)
+    }
+
+    pub fn inherit_environment(&mut self, options: Option<&Hash>) -> RubyResult<RubyStateBuilder> {
+        options::check_keys(options, &["only", "except"])?;
+
+        let only = options::get(options, "only")
+            .map(|only| to_strings(&only))
+            .transpose()?;
+        let except = options::get(options, "except")
+            .map(|except| to_strings(&except))
+            .transpose()?
+            .unwrap_or_default();
+
+        let environments = Class::from_existing("Object")
+            .const_get("ENV")
+            .protect_send("to_h", &[])?;
+        let mut environments = to_string_pairs(&environments)?
+            .into_iter()
+            .filter(|(key, _)| match &only {
+                Some(only) => only.contains(key),
+                None => true,
+            })
+            .filter(|(key, _)| !except.contains(key))
+            .collect::<Vec<_>>();
+
+        // `ENV` isn't ordered.
+        environments.sort();
+
+        self.add_environments(environments)?;
+
+        Ok(_ruby_self)
+    }
+
+    pub fn preopen_directories(
+        &mut self,
+        preopen_directories: &Array,
+        options: Option<&Hash>,
+    ) -> RubyResult<RubyStateBuilder> {
+        let permissions = Permissions::from_options(options)?;
+
+        self.add_host_directory()?;
+
+        let paths = unsafe { preopen_directories.to_any_object().to::<Array>() }
+            .into_iter()
+            .map(|directory| {
+                Ok(PathBuf::from(
+                    directory.try_convert_to::<RString>()?.to_string(),
+                ))
+            })
+            .collect::<RubyResult<Vec<_>>>()?;
+
+        for path in &paths {
+            permissions.preopen(&mut self.inner, path, None)?;
+        }
+
+        self.directories
+            .extend(paths.into_iter().map(|path| Directory {
+                name: path.to_string_lossy().into_owned(),
+                path,
+            }));
+
+        Ok(_ruby_self)
+    }
+
+    pub fn preopen_directory(
+        &mut self,
+        preopen_directory: &RString,
+        options: Option<&Hash>,
+    ) -> RubyResult<RubyStateBuilder> {
+        let permissions = Permissions::from_options(options)?;
+
+        self.add_host_directory()?;
+
+        permissions.preopen(&mut self.inner, Path::new(preopen_directory.to_str()), None)?;
+
+        self.directories.push(Directory {
+            name: preopen_directory.to_string(),
+            path: PathBuf::from(preopen_directory.to_str()),
+        });
+
+        Ok(_ruby_self)
+    }
+
+    pub fn map_directories(
+        &mut self,
+        map_directories: &Hash,
+        options: Option<&Hash>,
+    ) -> RubyResult<RubyStateBuilder> {
+        let permissions = Permissions::from_options(options)?;
+
+        self.add_host_directory()?;
+
+        let mut map_directory_pairs = Vec::with_capacity(map_directories.length());
+
+        map_directories.each(|key, value| {
+            unwrap_or_raise(|| {
+                map_directory_pairs.push((
+                    key.try_convert_to::<RString>()?.to_string(),
+                    PathBuf::from(value.try_convert_to::<RString>()?.to_str()),
+                ));
+
+                Ok(())
+            });
+        });
+
+        for (alias, path) in &map_directory_pairs {
+            permissions.preopen(&mut self.inner, path, Some(alias.as_str()))?;
+        }
+
+        self.directories.extend(
+            map_directory_pairs
+                .into_iter()
+                .map(|(name, path)| Directory { name, path }),
+        );
+
+        Ok(_ruby_self)
+    }
+
+    pub fn map_directory(
+        &mut self,
+        alias: &RString,
+        directory: &RString,
+        options: Option<&Hash>,
+    ) -> RubyResult<RubyStateBuilder> {
+        let permissions = Permissions::from_options(options)?;
+
+        self.add_host_directory()?;
+
+        permissions.preopen(
+            &mut self.inner,
+            Path::new(directory.to_str()),
+            Some(alias.to_str()),
+        )?;
+
+        self.directories.push(Directory {
+            name: alias.to_string(),
+            path: PathBuf::from(directory.to_str()),
+        });
+
+        Ok(_ruby_self)
+    }
+
+    pub fn stdin(&mut self, input: Option<&AnyObject>) -> RubyResult<RubyStateBuilder> {
+        let (stdin, kind) = match (input, block()) {
+            (Some(input), None) if input.try_convert_to::<RString>().is_ok() => {
+                (to_input(input, &self.roots)?, "string")
+            }
+            (Some(input), None) => (to_input(input, &self.roots)?, "io"),
+            (None, Some(block)) => (Input::from_block(block, &self.roots), "block"),
+            _ => {
+                return Err(to_ruby_err::<ArgumentError, _>(
+                    "The standard input must be given either as an argument or as a block",
+                ))
+            }
+        };
+
+        self.inner.stdin(Box::new(stdin));
+        self.stdin = kind;
+
+        Ok(_ruby_self)
+    }
+
+    pub fn inherit_stdin(&mut self) -> RubyResult<RubyStateBuilder> {
+        self.inner.stdin(Box::new(wasmer_wasi::Stdin::default()));
+        self.stdin = "inherit";
+
+        Ok(_ruby_self)
+    }
+
+    pub fn capture_stdout(&mut self) -> RubyResult<RubyStateBuilder> {
+        self.stdout = Some(CapturedOutput::new(block(), &self.roots));
+
+        Ok(_ruby_self)
+    }
+
+    pub fn capture_stderr(&mut self) -> RubyResult<RubyStateBuilder> {
+        self.stderr = Some(CapturedOutput::new(block(), &self.roots));
+
+        Ok(_ruby_self)
+    }
+
+    pub fn clock(&mut self, options: Option<&Hash>) -> RubyResult<RubyStateBuilder> {
+        options::check_keys(options, &["fixed", "step"])?;
+
+        let clock = match (options::get(options, "fixed"), block()) {
+            (Some(now), None) => Clock::fixed(&now, options::get(options, "step").as_ref())?,
+            (None, Some(block)) if options::get(options, "step").is_none() => {
+                Clock::from_block(block, &self.roots)
+            }
+            _ => {
+                return Err(to_ruby_err::<ArgumentError, _>(
+                    "The clock must be given either with `fixed:` (and `step:`) or as a block",
+                ))
+            }
+        };
+
+        self.clock = Some(clock);
+
+        Ok(_ruby_self)
+    }
+
+    pub fn random_seed(&mut self, seed: Option<&Integer>) -> RubyResult<RubyStateBuilder> {
+        let random = match (seed, block()) {
+            (Some(seed), None) => Random::seeded(seed.to_i64()),
+            (None, Some(block)) => Random::from_block(block, &self.roots),
+            _ => {
+                return Err(to_ruby_err::<ArgumentError, _>(
+                    "The random seed must be given either as an argument or as a block",
+                ))
+            }
+        };
+
+        self.random = Some(random);
+
+        Ok(_ruby_self)
+    }
+
+    pub fn mount(&mut self, path: &RString, fs: &MemFS) -> RubyResult<RubyStateBuilder> {
+        if self.has_host_directories {
+            return Err(to_ruby_err::<RuntimeError, _>(
+                "A `MemFS` cannot be mounted along with preopened host directories",
+            ));
+        }
+
+        if self.fs.is_some() {
+            return Err(to_ruby_err::<RuntimeError, _>(
+                "A `MemFS` has already been mounted",
+            ));
+        }
+
+        let alias = path.to_str();
+
+        self.inner
+            .preopen(|preopen| {
+                preopen.directory("/").read(true).write(true).create(true);
+
+                if alias.trim_start_matches('/').is_empty() {
+                    preopen
+                } else {
+                    preopen.alias(alias)
+                }
+            })
+            .map_err(to_ruby_err::<RuntimeError, _>)?;
+
+        self.fs = Some(fs.inner().clone());
+        self.directories.push(Directory {
+            name: alias.to_string(),
+            path: PathBuf::from("/"),
+        });
+
+        Ok(_ruby_self)
+    }
+
+    pub fn to_h(&self) -> RubyResult<Hash> {
+        let mut hash = Hash::new();
+
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|argument| RString::new_utf8(argument).to_any_object())
+            .collect::<Array>();
+
+        let mut environments = Hash::new();
+
+        for (key, value) in self.environments.iter() {
+            environments.store(RString::new_utf8(key), RString::new_utf8(value));
+        }
+
+        let mut directories = Hash::new();
+
+        for directory in self.directories.iter() {
+            directories.store(
+                RString::new_utf8(&directory.name),
+                RString::new_utf8(&directory.path.to_string_lossy()),
+            );
+        }
+
+        let describe_output = |output: &Option<CapturedOutput>| match output {
+            Some(output) if output.is_streamed() => Symbol::new("streamed"),
+            Some(_) => Symbol::new("captured"),
+            None => Symbol::new("inherit"),
+        };
+
+        let to_symbol = |name: Option<&str>| match name {
+            Some(name) => Symbol::new(name).to_any_object(),
+            None => NilClass::new().to_any_object(),
+        };
+
+        hash.store(
+            Symbol::new("program_name"),
+            RString::new_utf8(&self.program_name),
+        );
+        hash.store(Symbol::new("arguments"), arguments);
+        hash.store(Symbol::new("environments"), environments);
+        hash.store(Symbol::new("directories"), directories);
+        hash.store(Symbol::new("mounted"), Boolean::new(self.fs.is_some()));
+        hash.store(Symbol::new("stdin"), Symbol::new(self.stdin));
+        hash.store(Symbol::new("stdout"), describe_output(&self.stdout));
+        hash.store(Symbol::new("stderr"), describe_output(&self.stderr));
+        hash.store(
+            Symbol::new("clock"),
+            to_symbol(self.clock.as_ref().map(Clock::kind)),
+        );
+        hash.store(
+            Symbol::new("random"),
+            to_symbol(self.random.as_ref().map(Random::kind)),
+        );
+
+        Ok(hash)
+    }
+
+    pub fn finalize(&mut self) -> RubyResult<AnyObject> {
+        // Building the state resets the filesystem and the standard
+        // outputs, so they are set again each time.
+        if let Some(fs) = &self.fs {
+            self.inner.set_fs(Box::new(fs.clone()));
+        }
+
+        if let Some(stdout) = &self.stdout {
+            self.inner.stdout(stdout.to_file());
+        }
+
+        if let Some(stderr) = &self.stderr {
+            self.inner.stderr(stderr.to_file());
+        }
+
+        let inner = self
+            .inner
+            .finalize()
+            .map_err(to_ruby_err::<RuntimeError, _>)?;
+
+        // The standard input has been taken by the state.
+        self.stdin = "inherit";
+
+        Ok(Environment::ruby_new(Environment {
+            inner,
+            stdout: self.stdout.clone(),
+            stderr: self.stderr.clone(),
+            memory: MemorySlot::default(),
+            directories: self.directories.clone(),
+            clock: self.clock.clone(),
+            random: self.random.clone(),
+            roots: self.roots.clone(),
+        }))
+    }
+}
+
+/// The memory of the last instance created with the imports of an
+/// `Environment`.
+#[derive(Clone, Default)]
+pub(crate) struct MemorySlot(Rc<RefCell<Option<wasmer::Memory>>>);
+
+impl MemorySlot {
+    /// Binds the slot to the memory exported as `memory` by
+    /// `instance`, which is the one the WASI functions use.
+    pub(crate) fn bind(&self, instance: &wasmer::Instance) {
+        if let Ok(memory) = instance.exports.get_memory("memory") {
+            *self.0.borrow_mut() = Some(memory.clone());
+        }
+    }
+}
+
+#[rubyclass(module = "Wasmer::Wasi", mark)]
+pub struct Environment {
+    inner: wasmer_wasi::WasiEnv,
+    stdout: Option<CapturedOutput>,
+    stderr: Option<CapturedOutput>,
+    memory: MemorySlot,
+    directories: Vec<Directory>,
+    clock: Option<Clock>,
+    random: Option<Random>,
+    /// The roots of the builder, since the state holds its objects.
+    roots: Roots,
+}
+
+impl Mark for Environment {
+    fn mark(&self) {
+        self.roots.mark();
+    }
+}
+
+impl Environment {
+    /// Locks the WASI state. The state is locked by the WASI
+    /// functions while they run, e.g. while writing into a captured
+    /// output streamed to a block, in which case an error is raised
+    /// instead of waiting forever.
+    fn state(&self) -> RubyResult<MutexGuard<wasmer_wasi::WasiState>> {
+        self.inner.state.try_lock().map_err(|_| {
+            to_ruby_err::<RuntimeError, _>(
+                "The WASI state cannot be read while the program is using it",
+            )
+        })
+    }
+}
+
+/// Takes the bytes captured by `output`, or returns `nil` if the
+/// stream isn't captured.
+fn take_output(output: &Option<CapturedOutput>) -> RubyResult<AnyObject> {
+    Ok(match output {
+        Some(output) => stdio::to_binary_string(&output.take())?.to_any_object(),
+        None => NilClass::new().to_any_object(),
+    })
+}
+
+fn to_string(bytes: &[u8]) -> RString {
+    RString::new_utf8(&String::from_utf8_lossy(bytes))
+}
+
+/// Names the type of a file of the WASI filesystem.
+fn file_type_name(file_type: wasmer_wasi::types::__wasi_filetype_t) -> &'static str {
+    use wasmer_wasi::types::*;
+
+    match file_type {
+        __WASI_FILETYPE_DIRECTORY => "directory",
+        __WASI_FILETYPE_REGULAR_FILE => "file",
+        __WASI_FILETYPE_SYMBOLIC_LINK => "symlink",
+        __WASI_FILETYPE_CHARACTER_DEVICE => "character_device",
+        __WASI_FILETYPE_BLOCK_DEVICE => "block_device",
+        __WASI_FILETYPE_SOCKET_DGRAM | __WASI_FILETYPE_SOCKET_STREAM => "socket",
+        _ => "unknown",
+    }
+}
+
+/// A directory preopened for the program: its name as seen by the
+/// program, and its path in the filesystem backing the WASI state.
+#[derive(Clone)]
+struct Directory {
+    name: String,
+    path: PathBuf,
+}
+
+/// Resolves `path`, as seen by the program, to a path of the
+/// filesystem backing the WASI state, through the preopened
+/// directory with the longest matching name.
+fn resolve_path(directories: &[Directory], path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let path = path.strip_prefix("/").unwrap_or(path);
+
+    if path
+        .components()
+        .any(|component| component == Component::ParentDir)
+    {
+        return None;
+    }
+
+    directories
+        .iter()
+        .filter_map(|directory| {
+            let name = directory.name.trim_start_matches('/');
+            let relative_path = path.strip_prefix(name).ok()?;
+
+            Some((name.len(), directory.path.join(relative_path)))
+        })
+        .max_by_key(|(name_length, _)| *name_length)
+        .map(|(_, path)| path)
+}
+
+impl Environment {
+    /// Creates the WASI functions of `version`.
+    fn namespace(&self, store: &wasmer::Store, version: Version) -> wasmer::Exports {
+        let namespace_name = version.namespace_name();
+        let mut namespace =
+            wasmer_wasi::generate_import_object_from_env(store, self.inner.clone(), version.into())
+                .get_namespace_exports(namespace_name)
+                .unwrap_or_default();
+
+        // The clock and the random functions are replaced by the
+        // deterministic ones, if any.
+        if let Some(clock) = &self.clock {
+            for (name, function) in clock.imports(store) {
+                namespace.insert(name, function);
+            }
+        }
+
+        if let Some(random) = &self.random {
+            for (name, function) in random.imports(store) {
+                namespace.insert(name, function);
+            }
+        }
+
+        namespace
+    }
+}
+
+#[rubymethods]
+impl Environment {
+    pub fn stdout(&self) -> RubyResult<AnyObject> {
+        take_output(&self.stdout)
+    }
+
+    pub fn stderr(&self) -> RubyResult<AnyObject> {
+        take_output(&self.stderr)
+    }
+
+    pub fn generate_import_object(
+        &self,
+        store: &Store,
+        wasi_version: Option<&AnyObject>,
+        options: Option<&Hash>,
+    ) -> RubyResult<AnyObject> {
+        // The keyword arguments take the place of the version when
+        // it's omitted.
+        let (wasi_version, options) = match (wasi_version, options) {
+            (Some(wasi_version), None) if wasi_version.try_convert_to::<Hash>().is_ok() => {
+                (None, Some(wasi_version.try_convert_to::<Hash>()?))
+            }
+            (wasi_version, options) => (wasi_version.cloned(), options.cloned()),
+        };
+        let options = options.as_ref();
+
+        options::check_keys(options, &["extend"])?;
+
+        // Given a module, its version is detected now; without a
+        // version, or when none is detected, both namespaces are
+        // registered and the module picks the one it imports when
+        // instantiating.
+        let all_versions = vec![Version::Snapshot0, Version::Snapshot1];
+        let versions = match wasi_version {
+            Some(wasi_version) => match wasi_version.try_convert_to::<RubyModule>() {
+                Ok(module) => wasmer_wasi::get_wasi_version(module.upcast().inner(), false)
+                    .map(|version| vec![Version::from(&version)])
+                    .unwrap_or(all_versions),
+                Err(_) => vec![
+                    Version::try_from(&wasi_version.try_convert_to::<Integer>()?)
+                        .map_err(to_ruby_err::<TypeError, _>)?,
+                ],
+            },
+            None => all_versions,
+        };
+        let mut import_object = wasmer::ImportObject::new();
+
+        for version in versions {
+            import_object.register(
+                version.namespace_name(),
+                self.namespace(store.inner(), version),
+            );
+        }
+
+        let import_object =
+            ImportObject::raw_new_wasi(import_object, self.memory.clone(), &self.roots);
+
+        Ok(ImportObject::ruby_new(
+            match options::get(options, "extend") {
+                Some(other) => {
+                    let other = other.try_convert_to::<RubyImportObject>()?;
+
+                    import_object.merged(other.upcast())?
+                }
+                None => import_object,
+            },
+        ))
+    }
+
+    pub fn memory(&self) -> RubyResult<AnyObject> {
+        Ok(match &*self.memory.0.borrow() {
+            Some(memory) => Memory::ruby_new(Memory::raw_new(memory.clone())),
+            None => NilClass::new().to_any_object(),
+        })
+    }
+
+    pub fn arguments(&self) -> RubyResult<Array> {
+        Ok(self
+            .state()?
+            .args
+            .iter()
+            .map(|argument| to_string(argument).to_any_object())
+            .collect())
+    }
+
+    pub fn environments(&self) -> RubyResult<Hash> {
+        let mut environments = Hash::new();
+
+        for pair in self.state()?.envs.iter() {
+            let mut pair = pair.splitn(2, |byte| *byte == b'=');
+            let key = pair.next().unwrap_or_default();
+            let value = pair.next().unwrap_or_default();
+
+            environments.store(to_string(key), to_string(value));
+        }
+
+        Ok(environments)
+    }
+
+    pub fn file_descriptors(&self) -> RubyResult<Array> {
+        use wasmer_wasi::types::{
+            __WASI_RIGHT_FD_READ, __WASI_RIGHT_FD_WRITE, __WASI_RIGHT_PATH_CREATE_FILE,
+        };
+
+        let state = self.state()?;
+        let fs = &state.fs;
+        let mut fds = fs.fd_map.keys().copied().collect::<Vec<_>>();
+        fds.sort_unstable();
+
+        Ok(fds
+            .iter()
+            .map(|number| {
+                let fd = &fs.fd_map[number];
+                let inode = &fs.inodes[fd.inode];
+                let mut entry = Hash::new();
+
+                entry.store(Symbol::new("fd"), Integer::from(*number));
+                entry.store(Symbol::new("name"), RString::new_utf8(&inode.name));
+                entry.store(
+                    Symbol::new("type"),
+                    Symbol::new(file_type_name(inode.stat.st_filetype)),
+                );
+                entry.store(
+                    Symbol::new("preopened"),
+                    Boolean::new(fs.preopen_fds.contains(number)),
+                );
+                entry.store(
+                    Symbol::new("read"),
+                    Boolean::new(fd.rights & __WASI_RIGHT_FD_READ != 0),
+                );
+                entry.store(
+                    Symbol::new("write"),
+                    Boolean::new(fd.rights & __WASI_RIGHT_FD_WRITE != 0),
+                );
+                entry.store(
+                    Symbol::new("create"),
+                    Boolean::new(fd.rights & __WASI_RIGHT_PATH_CREATE_FILE != 0),
+                );
+
+                entry.to_any_object()
+            })
+            .collect())
+    }
+
+    pub fn read_file(&self, path: &RString) -> RubyResult<RString> {
+        let state = self.state()?;
+        let fs = &state.fs;
+        let resolved_path = resolve_path(&self.directories, path.to_str()).ok_or_else(|| {
+            to_ruby_err::<IOError, _>(format!(
+                "`{}` isn't in a preopened directory",
+                path.to_str()
+            ))
+        })?;
+        let mut bytes = Vec::new();
+
+        fs.fs_backing
+            .new_open_options()
+            .read(true)
+            .open(&resolved_path)
+            .and_then(|mut file| file.read_to_end(&mut bytes).map_err(Into::into))
+            .map_err(|error| {
+                to_ruby_err::<IOError, _>(format!("Failed to read `{}`: {}", path.to_str(), error))
+            })?;
+
+        stdio::to_binary_string(&bytes)
+    }
+}
+
+#[rubyfunction]
+pub fn get_version(module: &Module, strict: &Boolean) -> RubyResult<AnyObject> {
+    Ok(
+        wasmer_wasi::get_wasi_version(&module.inner(), strict.to_bool())
+            .map(|version| Version::from(&version).to_integer().to_any_object())
+            .unwrap_or_else(|| NilClass::new().to_any_object()),
+    )
+}
+
+#[rubyfunction]
+pub fn detect(module: &Module) -> RubyResult<Hash> {
+    let module = module.inner();
+    let version =
+        wasmer_wasi::get_wasi_version(module, false).map(|version| Version::from(&version));
+
+    // The functions are looked up in the import object generated by
+    // default, i.e. the one providing both versions.
+    let environment = wasmer_wasi::WasiState::new("detect")
+        .finalize()
+        .map_err(to_ruby_err::<RuntimeError, _>)?;
+    let mut import_object = wasmer::ImportObject::new();
+
+    for version in &[Version::Snapshot0, Version::Snapshot1] {
+        let namespace_name = version.namespace_name();
+
+        if let Some(namespace) = wasmer_wasi::generate_import_object_from_env(
+            module.store(),
+            environment.clone(),
+            (*version).into(),
+        )
+        .get_namespace_exports(namespace_name)
+        {
+            import_object.register(namespace_name, namespace);
+        }
+    }
+
+    let mut functions = Array::new();
+    let mut unsupported = Array::new();
+
+    // Only the imports of the WASI namespaces are considered; the
+    // other ones, e.g. host functions, are not WASI's business.
+    for import_type in module.imports().filter(|import_type| {
+        [Version::Snapshot0, Version::Snapshot1]
+            .iter()
+            .any(|version| version.namespace_name() == import_type.module())
+    }) {
+        let is_provided = import_object
+            .get_export(import_type.module(), import_type.name())
+            .is_some();
+
+        if !is_provided {
+            unsupported.push(ImportType::ruby_new(ImportType::try_from(import_type)?));
+        } else if version.map(|version| version.namespace_name()) == Some(import_type.module()) {
+            functions.push(RString::new_utf8(import_type.name()));
+        }
+    }
+
+    let mut hash = Hash::new();
+
+    hash.store(
+        Symbol::new("version"),
+        match version {
+            Some(version) => Symbol::new(version.name()).to_any_object(),
+            None => NilClass::new().to_any_object(),
+        },
+    );
+    hash.store(Symbol::new("functions"), functions);
+    hash.store(Symbol::new("unsupported"), unsupported);
+
+    Ok(hash)
+}
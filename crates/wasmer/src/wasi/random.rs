@@ -0,0 +1,136 @@
+//! Random generators replacing the host's one, so that the random
+//! bytes read by a program are reproducible.
+
+use super::stdio::Rooted;
+use crate::gc::Roots;
+use rutie::{Integer, Object, RString};
+use std::sync::{Arc, Mutex};
+use wasmer::{Array, LazyInit, WasmPtr, WasmerEnv};
+use wasmer_wasi::types::{__wasi_errno_t, __WASI_EFAULT, __WASI_ESUCCESS};
+
+/// The context of the seed derivation, see `blake3::derive_key`.
+const SEED_CONTEXT: &str = "wasmer-ruby 2021-11-01 WASI random seed";
+
+/// Where the random bytes come from.
+enum Source {
+    /// An infinite stream of bytes derived from a seed.
+    Seeded(blake3::OutputReader),
+    /// A block receiving a length, and returning as many bytes.
+    Block(Rooted),
+}
+
+/// A random generator given to the program instead of the host's
+/// one. It's shared by all the instances using the same
+/// `Environment`.
+#[derive(Clone)]
+pub(crate) struct Random {
+    source: Arc<Mutex<Source>>,
+}
+
+impl Random {
+    /// Creates a generator producing the same bytes for the same
+    /// `seed`, on all the platforms.
+    pub(crate) fn seeded(seed: i64) -> Self {
+        let key = blake3::derive_key(SEED_CONTEXT, &seed.to_le_bytes());
+
+        Self::new(Source::Seeded(
+            blake3::Hasher::new_keyed(&key).finalize_xof(),
+        ))
+    }
+
+    /// Creates a generator calling `block` with the number of bytes
+    /// to produce.
+    pub(crate) fn from_block(block: rutie::Proc, roots: &Roots) -> Self {
+        Self::new(Source::Block(Rooted::new(block.to_any_object(), roots)))
+    }
+
+    /// Names where the bytes come from, i.e. `seeded` or `block`.
+    pub(crate) fn kind(&self) -> &'static str {
+        match &*self.source.lock().unwrap() {
+            Source::Seeded(_) => "seeded",
+            Source::Block(_) => "block",
+        }
+    }
+
+    fn new(source: Source) -> Self {
+        Self {
+            source: Arc::new(Mutex::new(source)),
+        }
+    }
+
+    fn fill(&self, buffer: &mut [u8]) -> Result<(), wasmer::RuntimeError> {
+        match &mut *self.source.lock().unwrap() {
+            Source::Seeded(reader) => reader.fill(buffer),
+            Source::Block(block) => {
+                let bytes = block
+                    .send(
+                        "call",
+                        &[Integer::from(buffer.len() as u64).to_any_object()],
+                    )
+                    .map_err(|error| wasmer::RuntimeError::new(error.to_string()))?;
+                let bytes = bytes.try_convert_to::<RString>().map_err(|_| {
+                    wasmer::RuntimeError::new(format!(
+                        "The random block must return a `String`, received `{:?}`",
+                        bytes.ty()
+                    ))
+                })?;
+                let bytes = bytes.to_bytes_unchecked();
+
+                if bytes.len() != buffer.len() {
+                    return Err(wasmer::RuntimeError::new(format!(
+                        "The random block must return {} bytes, received {}",
+                        buffer.len(),
+                        bytes.len()
+                    )));
+                }
+
+                buffer.copy_from_slice(bytes);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates the `random_get` function reading this generator.
+    pub(crate) fn imports(&self, store: &wasmer::Store) -> Vec<(&'static str, wasmer::Function)> {
+        let env = RandomEnv {
+            random: self.clone(),
+            memory: LazyInit::new(),
+        };
+
+        vec![(
+            "random_get",
+            wasmer::Function::new_native_with_env(store, env, random_get),
+        )]
+    }
+}
+
+#[derive(Clone, WasmerEnv)]
+struct RandomEnv {
+    random: Random,
+    #[wasmer(export)]
+    memory: LazyInit<wasmer::Memory>,
+}
+
+fn random_get(
+    env: &RandomEnv,
+    buffer: WasmPtr<u8, Array>,
+    buffer_length: u32,
+) -> Result<__wasi_errno_t, wasmer::RuntimeError> {
+    let cells = match env
+        .memory_ref()
+        .and_then(|memory| buffer.deref(memory, 0, buffer_length))
+    {
+        Some(cells) => cells,
+        None => return Ok(__WASI_EFAULT),
+    };
+
+    let mut bytes = vec![0; buffer_length as usize];
+    env.random.fill(&mut bytes)?;
+
+    for (cell, byte) in cells.iter().zip(bytes) {
+        cell.set(byte);
+    }
+
+    Ok(__WASI_ESUCCESS)
+}
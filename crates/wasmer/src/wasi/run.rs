@@ -0,0 +1,159 @@
+//! Runs a WASI program in one call, from the module to its exit code
+//! and its captured output.
+
+use super::{
+    check_argument, check_environment,
+    stdio::{to_binary_string, CapturedOutput},
+    to_input, Permissions,
+};
+use crate::{
+    error::{to_exit_code, to_ruby_err, to_trap_err, unwrap_or_raise, RuntimeError},
+    gc::Roots,
+    module::Module,
+    options,
+    prelude::*,
+    target,
+};
+use rutie::{AnyObject, Array, Boolean, Hash, Integer, Object, RString};
+use std::path::{Path, PathBuf};
+
+#[rubyclass(module = "Wasmer::Wasi")]
+pub struct Output {
+    exit_code: u32,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+#[rubymethods]
+impl Output {
+    pub fn exit_code(&self) -> RubyResult<Integer> {
+        Ok(Integer::from(self.exit_code))
+    }
+
+    pub fn stdout(&self) -> RubyResult<RString> {
+        to_binary_string(&self.stdout)
+    }
+
+    pub fn stderr(&self) -> RubyResult<RString> {
+        to_binary_string(&self.stderr)
+    }
+
+    pub fn is_success(&self) -> RubyResult<Boolean> {
+        Ok(Boolean::new(self.exit_code == 0))
+    }
+}
+
+/// Builds the WASI state from the options given to `run`.
+fn build_state(
+    program_name: &str,
+    options: Option<&Hash>,
+) -> RubyResult<wasmer_wasi::WasiStateBuilder> {
+    let mut state = wasmer_wasi::WasiState::new(program_name);
+
+    if let Some(arguments) = options::get(options, "args") {
+        for argument in arguments.try_convert_to::<Array>()? {
+            let argument = argument.try_convert_to::<RString>()?;
+            check_argument(argument.to_str())?;
+
+            state.arg(argument.to_str());
+        }
+    }
+
+    if let Some(environments) = options::get(options, "env") {
+        environments.try_convert_to::<Hash>()?.each(|key, value| {
+            unwrap_or_raise(|| {
+                let key = key.try_convert_to::<RString>()?;
+                let value = value.try_convert_to::<RString>()?;
+                check_environment(key.to_str(), value.to_str())?;
+
+                state.env(key.to_str(), value.to_str());
+
+                Ok(())
+            });
+        });
+    }
+
+    if let Some(input) = options::get(options, "stdin") {
+        // The input is referenced by the options during the whole
+        // run, so its roots don't need to be marked.
+        state.stdin(Box::new(to_input(&input, &Roots::default())?));
+    }
+
+    let permissions = options::get(options, "permissions")
+        .map(|permissions| permissions.try_convert_to::<Hash>())
+        .transpose()?;
+    let permissions = Permissions::from_options(permissions.as_ref())?;
+
+    if let Some(directories) = options::get(options, "dirs") {
+        // An `Array` of directories is preopened as is, and a `Hash`
+        // maps aliases to directories.
+        if let Ok(directories) = directories.try_convert_to::<Array>() {
+            for directory in directories {
+                let directory = directory.try_convert_to::<RString>()?;
+
+                permissions.preopen(&mut state, Path::new(directory.to_str()), None)?;
+            }
+        } else {
+            let mut map_directory_pairs = Vec::new();
+
+            directories.try_convert_to::<Hash>()?.each(|key, value| {
+                unwrap_or_raise(|| {
+                    map_directory_pairs.push((
+                        key.try_convert_to::<RString>()?.to_string(),
+                        PathBuf::from(value.try_convert_to::<RString>()?.to_str()),
+                    ));
+
+                    Ok(())
+                });
+            });
+
+            for (alias, path) in &map_directory_pairs {
+                permissions.preopen(&mut state, path, Some(alias.as_str()))?;
+            }
+        }
+    }
+
+    Ok(state)
+}
+
+#[rubyfunction]
+pub fn run(module: &Module, options: Option<&Hash>) -> RubyResult<AnyObject> {
+    options::check_keys(options, &["args", "env", "stdin", "dirs", "permissions"])?;
+
+    target::check_host(module.store().target())?;
+
+    let program_name = module.inner().name().unwrap_or("main").to_string();
+    let mut state = build_state(&program_name, options)?;
+
+    let stdout = CapturedOutput::new(None, &Roots::default());
+    let stderr = CapturedOutput::new(None, &Roots::default());
+    state.stdout(stdout.to_file());
+    state.stderr(stderr.to_file());
+
+    let environment = state.finalize().map_err(to_ruby_err::<RuntimeError, _>)?;
+    let version = wasmer_wasi::get_wasi_version(module.inner(), false)
+        .unwrap_or(wasmer_wasi::WasiVersion::Latest);
+    let import_object =
+        wasmer_wasi::generate_import_object_from_env(module.inner().store(), environment, version);
+
+    let instance =
+        wasmer::Instance::new(module.inner(), &import_object).map_err(|error| match error {
+            wasmer::InstantiationError::Start(error) => to_trap_err(error),
+            error => to_ruby_err::<RuntimeError, _>(error),
+        })?;
+    let start = instance
+        .exports
+        .get_function("_start")
+        .map_err(|_| to_ruby_err::<RuntimeError, _>("The WASI program has no `_start` function"))?;
+
+    let exit_code = match start.call(&[]) {
+        Ok(_) => 0,
+        Err(error) => to_exit_code(error).map_err(to_trap_err)?,
+    };
+
+    Ok(Output::ruby_new(Output {
+        exit_code,
+        stdout: stdout.take(),
+        stderr: stderr.take(),
+    }))
+}
@@ -0,0 +1,318 @@
+//! The standard streams of a WASI program, which can be captured
+//! from Ruby instead of using the host process's ones.
+
+use crate::{
+    gc::{Held, Roots},
+    prelude::*,
+};
+use rutie::{AnyObject, Encoding, Integer, Object, Proc, RString};
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, Read, Seek, Write},
+    sync::{Arc, Mutex},
+};
+use wasmer_wasi::{FsError, VirtualFile};
+
+/// A Ruby object used by the program, e.g. a block receiving the
+/// chunks written by the program, an `IO` the program reads from, or
+/// a block providing the time. It's held by the roots of the
+/// `StateBuilder`, and thus kept alive as long as the builder, the
+/// `Environment`, the imports or the instance using it.
+pub(super) struct Rooted(Held);
+
+impl Rooted {
+    pub(super) fn new(object: AnyObject, roots: &Roots) -> Self {
+        Self(roots.hold(&object))
+    }
+
+    pub(super) fn send(&self, method: &str, arguments: &[AnyObject]) -> io::Result<AnyObject> {
+        // An exception raised by the object can't unwind through the
+        // WebAssembly frames, so it becomes an I/O error for the
+        // program.
+        self.0
+            .object()
+            .protect_send(method, arguments)
+            .map_err(|error| to_io_error(error.to_string()))
+    }
+}
+
+fn to_io_error(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, message)
+}
+
+struct Buffer {
+    bytes: Vec<u8>,
+    callback: Option<Arc<Rooted>>,
+}
+
+/// An output stream (`stdout` or `stderr`) captured into memory, or
+/// streamed to a Ruby block. It's shared between the WASI state,
+/// which writes into it, and the `Environment`, which reads it.
+#[derive(Clone)]
+pub(crate) struct CapturedOutput {
+    buffer: Arc<Mutex<Buffer>>,
+}
+
+impl CapturedOutput {
+    /// Creates an output that streams the chunks to `block` if any,
+    /// or that buffers them otherwise.
+    pub(crate) fn new(block: Option<Proc>, roots: &Roots) -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(Buffer {
+                bytes: Vec::new(),
+                callback: block.map(|block| Arc::new(Rooted::new(block.to_any_object(), roots))),
+            })),
+        }
+    }
+
+    /// Tells whether the output is streamed to a block.
+    pub(crate) fn is_streamed(&self) -> bool {
+        self.buffer.lock().unwrap().callback.is_some()
+    }
+
+    /// Takes the bytes written so far.
+    pub(crate) fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.buffer.lock().unwrap().bytes)
+    }
+
+    /// Returns the file to give to the WASI state.
+    pub(crate) fn to_file(&self) -> Box<dyn VirtualFile> {
+        Box::new(self.clone())
+    }
+}
+
+impl fmt::Debug for CapturedOutput {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("CapturedOutput")
+    }
+}
+
+impl Write for CapturedOutput {
+    fn write(&mut self, chunk: &[u8]) -> io::Result<usize> {
+        let callback = {
+            let mut buffer = self.buffer.lock().unwrap();
+
+            match &buffer.callback {
+                Some(callback) => callback.clone(),
+                None => {
+                    buffer.bytes.extend_from_slice(chunk);
+
+                    return Ok(chunk.len());
+                }
+            }
+        };
+
+        // The buffer is unlocked before calling the block, which may
+        // read the output through the `Environment`.
+        let string = to_binary_string(chunk)
+            .map_err(|error| to_io_error(error.to_string()))?
+            .to_any_object();
+
+        callback.send("call", &[string])?;
+
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for CapturedOutput {
+    fn read(&mut self, _buffer: &mut [u8]) -> io::Result<usize> {
+        Err(to_io_error("cannot read from an output stream".to_string()))
+    }
+}
+
+impl Seek for CapturedOutput {
+    fn seek(&mut self, _position: io::SeekFrom) -> io::Result<u64> {
+        Err(to_io_error("cannot seek in a stream".to_string()))
+    }
+}
+
+impl VirtualFile for CapturedOutput {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+
+    fn last_modified(&self) -> u64 {
+        0
+    }
+
+    fn created_time(&self) -> u64 {
+        0
+    }
+
+    fn size(&self) -> u64 {
+        self.buffer.lock().unwrap().bytes.len() as u64
+    }
+
+    fn set_len(&mut self, size: u64) -> Result<(), FsError> {
+        self.buffer.lock().unwrap().bytes.resize(size as usize, 0);
+
+        Ok(())
+    }
+
+    fn unlink(&mut self) -> Result<(), FsError> {
+        Ok(())
+    }
+
+    fn bytes_available(&self) -> Result<usize, FsError> {
+        Ok(0)
+    }
+}
+
+/// Where the standard input of a program is read from.
+enum Source {
+    /// An `IO`-like object, read lazily with `read(length)`.
+    Io(Rooted),
+    /// A block returning the chunks, and `nil` at the end.
+    Block(Rooted),
+}
+
+/// The standard input of a program, given from Ruby.
+pub(crate) struct Input {
+    pending: VecDeque<u8>,
+    source: Option<Source>,
+}
+
+impl Input {
+    /// Creates an input whose content is known in advance.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            pending: bytes.iter().copied().collect(),
+            source: None,
+        }
+    }
+
+    /// Creates an input read lazily from an `IO`-like object.
+    pub(crate) fn from_io(io: AnyObject, roots: &Roots) -> Self {
+        Self {
+            pending: VecDeque::new(),
+            source: Some(Source::Io(Rooted::new(io, roots))),
+        }
+    }
+
+    /// Creates an input produced by a block.
+    pub(crate) fn from_block(block: Proc, roots: &Roots) -> Self {
+        Self {
+            pending: VecDeque::new(),
+            source: Some(Source::Block(Rooted::new(block.to_any_object(), roots))),
+        }
+    }
+
+    /// Reads the next chunk from the source, if any. It returns
+    /// `false` at the end of the input.
+    fn fill(&mut self, length: usize) -> io::Result<bool> {
+        let chunk = match &self.source {
+            Some(Source::Io(io)) => {
+                io.send("read", &[Integer::new(length as i64).to_any_object()])?
+            }
+            Some(Source::Block(block)) => block.send("call", &[])?,
+            None => return Ok(false),
+        };
+
+        if chunk.is_nil() {
+            self.source = None;
+
+            return Ok(false);
+        }
+
+        let chunk = chunk.try_convert_to::<RString>().map_err(|_| {
+            to_io_error(format!(
+                "the standard input must produce `String`s, received `{:?}`",
+                chunk.ty()
+            ))
+        })?;
+        let chunk = chunk.to_bytes_unchecked();
+
+        // Some objects return an empty string instead of `nil`.
+        if chunk.is_empty() {
+            self.source = None;
+
+            return Ok(false);
+        }
+
+        self.pending.extend(chunk);
+
+        Ok(true)
+    }
+}
+
+impl fmt::Debug for Input {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Input")
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        if self.pending.is_empty() && !self.fill(buffer.len())? {
+            return Ok(0);
+        }
+
+        let length = buffer.len().min(self.pending.len());
+
+        for (slot, byte) in buffer.iter_mut().zip(self.pending.drain(..length)) {
+            *slot = byte;
+        }
+
+        Ok(length)
+    }
+}
+
+impl Write for Input {
+    fn write(&mut self, _chunk: &[u8]) -> io::Result<usize> {
+        Err(to_io_error("cannot write into an input stream".to_string()))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for Input {
+    fn seek(&mut self, _position: io::SeekFrom) -> io::Result<u64> {
+        Err(to_io_error("cannot seek in a stream".to_string()))
+    }
+}
+
+impl VirtualFile for Input {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+
+    fn last_modified(&self) -> u64 {
+        0
+    }
+
+    fn created_time(&self) -> u64 {
+        0
+    }
+
+    fn size(&self) -> u64 {
+        self.pending.len() as u64
+    }
+
+    fn set_len(&mut self, _size: u64) -> Result<(), FsError> {
+        Err(FsError::PermissionDenied)
+    }
+
+    fn unlink(&mut self) -> Result<(), FsError> {
+        Ok(())
+    }
+
+    fn bytes_available(&self) -> Result<usize, FsError> {
+        Ok(self.pending.len())
+    }
+}
+
+/// Converts captured bytes to a binary Ruby string.
+pub(crate) fn to_binary_string(bytes: &[u8]) -> RubyResult<RString> {
+    Ok(RString::from_bytes(bytes, &Encoding::find("ASCII-8BIT")?))
+}
@@ -0,0 +1,521 @@
+//! A runner for the `.wast` scripts of the WebAssembly specification
+//! test suite.
+
+use crate::{
+    error::{to_ruby_err, ArgumentError, IOError, RuntimeError},
+    options,
+    prelude::*,
+    store::RubyStore,
+    target, wat,
+};
+use rutie::{Array, Hash, Integer, NilClass, Object, RString, Symbol};
+use std::{collections::HashMap, convert::TryInto, fs, path::Path, str};
+use wast::{
+    parser::{self, ParseBuffer},
+    AssertExpression, Expression, HeapType, Id, Instruction, NanPattern, QuoteModule, V128Pattern,
+    Wast, WastDirective, WastExecute, WastInvoke,
+};
+
+/// Why a directive didn't pass.
+enum Failure {
+    Failed(String),
+    Skipped(String),
+}
+
+fn failed<Message: ToString>(message: Message) -> Failure {
+    Failure::Failed(message.to_string())
+}
+
+fn skipped<Message: ToString>(message: Message) -> Failure {
+    Failure::Skipped(message.to_string())
+}
+
+/// The values returned by an execution, or the trap it raised.
+type Execution = Result<Vec<wasmer::Val>, wasmer::RuntimeError>;
+
+struct Runner<'store> {
+    store: &'store wasmer::Store,
+    spectest: wasmer::Exports,
+    current: Option<wasmer::Instance>,
+    instances: HashMap<String, wasmer::Instance>,
+    registered: HashMap<String, wasmer::Exports>,
+}
+
+impl<'store> Runner<'store> {
+    fn new(store: &'store wasmer::Store) -> RubyResult<Self> {
+        Ok(Self {
+            store,
+            spectest: spectest(store)?,
+            current: None,
+            instances: HashMap::new(),
+            registered: HashMap::new(),
+        })
+    }
+
+    fn run(&mut self, directive: WastDirective) -> Result<(), Failure> {
+        match directive {
+            WastDirective::Module(mut module) => {
+                let id = module.id.map(|id| id.name().to_string());
+                let bytes = module.encode().map_err(failed)?;
+
+                self.define(id, &bytes)
+            }
+
+            WastDirective::QuoteModule { source, .. } => {
+                let bytes = quote(&source).map_err(failed)?;
+
+                self.define(None, &bytes)
+            }
+
+            WastDirective::Register { name, module, .. } => {
+                let exports = self.instance(module.as_ref())?.exports.clone();
+                self.registered.insert(name.to_string(), exports);
+
+                Ok(())
+            }
+
+            WastDirective::Invoke(invoke) => self
+                .invoke(&invoke)?
+                .map(drop)
+                .map_err(|trap| failed(format!("Unexpected trap: {}", trap.message()))),
+
+            WastDirective::AssertReturn { exec, results, .. } => {
+                let values = self
+                    .execute(exec)?
+                    .map_err(|trap| failed(format!("Unexpected trap: {}", trap.message())))?;
+
+                check_results(&values, &results)
+            }
+
+            WastDirective::AssertTrap { exec, message, .. } => {
+                check_trap(self.execute(exec)?, message)
+            }
+
+            WastDirective::AssertExhaustion { call, message, .. } => {
+                check_trap(self.invoke(&call)?, message)
+            }
+
+            WastDirective::AssertInvalid {
+                module, message, ..
+            }
+            | WastDirective::AssertMalformed {
+                module, message, ..
+            } => match self.compile(module) {
+                Ok(_) => Err(failed(format!(
+                    "Expected the module to be rejected with `{}`",
+                    message
+                ))),
+                Err(_) => Ok(()),
+            },
+
+            WastDirective::AssertUnlinkable {
+                mut module,
+                message,
+                ..
+            } => {
+                let bytes = module.encode().map_err(failed)?;
+                let module = wasmer::Module::new(self.store, &bytes).map_err(failed)?;
+
+                match wasmer::Instance::new(&module, &self.imports()) {
+                    Ok(_) => Err(failed(format!(
+                        "Expected the module to be unlinkable with `{}`",
+                        message
+                    ))),
+                    Err(_) => Ok(()),
+                }
+            }
+
+            WastDirective::AssertException { .. } => {
+                Err(skipped("The exceptions proposal is not supported"))
+            }
+        }
+    }
+
+    /// Compiles and instantiates a module, which becomes the current
+    /// one.
+    fn define(&mut self, id: Option<String>, bytes: &[u8]) -> Result<(), Failure> {
+        let instance = self.instantiate(bytes)?.map_err(|trap| {
+            failed(format!(
+                "Unexpected trap in the start function: {}",
+                trap.message()
+            ))
+        })?;
+
+        if let Some(id) = id {
+            self.instances.insert(id, instance.clone());
+        }
+
+        self.current = Some(instance);
+
+        Ok(())
+    }
+
+    fn instantiate(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Result<wasmer::Instance, wasmer::RuntimeError>, Failure> {
+        let module = wasmer::Module::new(self.store, bytes).map_err(failed)?;
+
+        match wasmer::Instance::new(&module, &self.imports()) {
+            Ok(instance) => Ok(Ok(instance)),
+            Err(wasmer::InstantiationError::Start(trap)) => Ok(Err(trap)),
+            Err(error) => Err(failed(error)),
+        }
+    }
+
+    fn compile(&self, module: QuoteModule) -> Result<wasmer::Module, String> {
+        let bytes = match module {
+            QuoteModule::Module(mut module) => {
+                module.encode().map_err(|error| error.to_string())?
+            }
+            QuoteModule::Quote(source) => quote(&source)?,
+        };
+
+        wasmer::Module::new(self.store, &bytes).map_err(|error| error.to_string())
+    }
+
+    fn imports(&self) -> wasmer::ImportObject {
+        let mut import_object = wasmer::ImportObject::new();
+        import_object.register("spectest", self.spectest.clone());
+
+        for (name, exports) in &self.registered {
+            import_object.register(name, exports.clone());
+        }
+
+        import_object
+    }
+
+    fn instance(&self, id: Option<&Id>) -> Result<&wasmer::Instance, Failure> {
+        match id {
+            Some(id) => self
+                .instances
+                .get(id.name())
+                .ok_or_else(|| failed(format!("Unknown module `${}`", id.name()))),
+            None => self
+                .current
+                .as_ref()
+                .ok_or_else(|| failed("No module has been defined yet")),
+        }
+    }
+
+    fn invoke(&self, invoke: &WastInvoke) -> Result<Execution, Failure> {
+        let function = self
+            .instance(invoke.module.as_ref())?
+            .exports
+            .get_function(invoke.name)
+            .map_err(failed)?;
+        let arguments = invoke
+            .args
+            .iter()
+            .map(to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(function.call(&arguments).map(Vec::from))
+    }
+
+    fn execute(&self, execute: WastExecute) -> Result<Execution, Failure> {
+        match execute {
+            WastExecute::Invoke(invoke) => self.invoke(&invoke),
+
+            WastExecute::Module(mut module) => {
+                let bytes = module.encode().map_err(failed)?;
+
+                Ok(self.instantiate(&bytes)?.map(|_| Vec::new()))
+            }
+
+            WastExecute::Get { module, global } => Ok(Ok(vec![self
+                .instance(module.as_ref())?
+                .exports
+                .get_global(global)
+                .map_err(failed)?
+                .get()])),
+        }
+    }
+}
+
+/// The `spectest` module, imported by the scripts of the test suite.
+fn spectest(store: &wasmer::Store) -> RubyResult<wasmer::Exports> {
+    let mut exports = wasmer::Exports::new();
+
+    exports.insert("print", wasmer::Function::new_native(store, || {}));
+    exports.insert(
+        "print_i32",
+        wasmer::Function::new_native(store, |_: i32| {}),
+    );
+    exports.insert(
+        "print_i64",
+        wasmer::Function::new_native(store, |_: i64| {}),
+    );
+    exports.insert(
+        "print_f32",
+        wasmer::Function::new_native(store, |_: f32| {}),
+    );
+    exports.insert(
+        "print_f64",
+        wasmer::Function::new_native(store, |_: f64| {}),
+    );
+    exports.insert(
+        "print_i32_f32",
+        wasmer::Function::new_native(store, |_: i32, _: f32| {}),
+    );
+    exports.insert(
+        "print_f64_f64",
+        wasmer::Function::new_native(store, |_: f64, _: f64| {}),
+    );
+    exports.insert(
+        "global_i32",
+        wasmer::Global::new(store, wasmer::Val::I32(666)),
+    );
+    exports.insert(
+        "global_i64",
+        wasmer::Global::new(store, wasmer::Val::I64(666)),
+    );
+    exports.insert(
+        "global_f32",
+        wasmer::Global::new(store, wasmer::Val::F32(666.0)),
+    );
+    exports.insert(
+        "global_f64",
+        wasmer::Global::new(store, wasmer::Val::F64(666.0)),
+    );
+    exports.insert(
+        "table",
+        wasmer::Table::new(
+            store,
+            wasmer::TableType::new(wasmer::Type::FuncRef, 10, Some(20)),
+            wasmer::Val::FuncRef(None),
+        )
+        .map_err(to_ruby_err::<RuntimeError, _>)?,
+    );
+    exports.insert(
+        "memory",
+        wasmer::Memory::new(store, wasmer::MemoryType::new(1, Some(2), false))
+            .map_err(to_ruby_err::<RuntimeError, _>)?,
+    );
+
+    Ok(exports)
+}
+
+/// Parses and encodes a `(module quote …)`.
+fn quote(source: &[&[u8]]) -> Result<Vec<u8>, String> {
+    let mut text = String::new();
+
+    for chunk in source {
+        text.push_str(str::from_utf8(chunk).map_err(|error| error.to_string())?);
+        text.push(' ');
+    }
+
+    let buffer = ParseBuffer::new(&text).map_err(|error| error.to_string())?;
+    let mut wat = parser::parse::<wast::Wat>(&buffer).map_err(|error| error.to_string())?;
+
+    wat.module.encode().map_err(|error| error.to_string())
+}
+
+fn to_value(expression: &Expression) -> Result<wasmer::Val, Failure> {
+    Ok(match &*expression.instrs {
+        [Instruction::I32Const(value)] => wasmer::Val::I32(*value),
+        [Instruction::I64Const(value)] => wasmer::Val::I64(*value),
+        [Instruction::F32Const(value)] => wasmer::Val::F32(f32::from_bits(value.bits)),
+        [Instruction::F64Const(value)] => wasmer::Val::F64(f64::from_bits(value.bits)),
+        [Instruction::V128Const(value)] => {
+            wasmer::Val::V128(u128::from_le_bytes(value.to_le_bytes()))
+        }
+        [Instruction::RefNull(HeapType::Func)] => wasmer::Val::FuncRef(None),
+        [Instruction::RefNull(HeapType::Extern)] => wasmer::Val::null(),
+        _ => return Err(skipped("Unsupported argument")),
+    })
+}
+
+fn check_trap(execution: Execution, message: &str) -> Result<(), Failure> {
+    match execution {
+        Ok(_) => Err(failed(format!("Expected a trap with `{}`", message))),
+        Err(trap) if trap.message().contains(message) => Ok(()),
+        Err(trap) => Err(failed(format!(
+            "Expected a trap with `{}`, got `{}`",
+            message,
+            trap.message()
+        ))),
+    }
+}
+
+fn check_results(values: &[wasmer::Val], expected: &[AssertExpression]) -> Result<(), Failure> {
+    if values.len() != expected.len() {
+        return Err(failed(format!(
+            "Expected {} result(s), got {}",
+            expected.len(),
+            values.len()
+        )));
+    }
+
+    for (nth, (value, expected)) in values.iter().zip(expected).enumerate() {
+        if !value_matches(value, expected)? {
+            return Err(failed(format!(
+                "Result #{}: expected {:?}, got {:?}",
+                nth, expected, value
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn value_matches(value: &wasmer::Val, expected: &AssertExpression) -> Result<bool, Failure> {
+    Ok(match (value, expected) {
+        (wasmer::Val::I32(value), AssertExpression::I32(expected)) => value == expected,
+        (wasmer::Val::I64(value), AssertExpression::I64(expected)) => value == expected,
+        (wasmer::Val::F32(value), AssertExpression::F32(expected)) => {
+            f32_matches(value.to_bits(), expected)
+        }
+        (wasmer::Val::F64(value), AssertExpression::F64(expected)) => {
+            f64_matches(value.to_bits(), expected)
+        }
+        (wasmer::Val::F32(value), AssertExpression::LegacyCanonicalNaN) => {
+            f32_matches(value.to_bits(), &NanPattern::CanonicalNan)
+        }
+        (wasmer::Val::F64(value), AssertExpression::LegacyCanonicalNaN) => {
+            f64_matches(value.to_bits(), &NanPattern::CanonicalNan)
+        }
+        (wasmer::Val::F32(value), AssertExpression::LegacyArithmeticNaN) => {
+            f32_matches(value.to_bits(), &NanPattern::ArithmeticNan)
+        }
+        (wasmer::Val::F64(value), AssertExpression::LegacyArithmeticNaN) => {
+            f64_matches(value.to_bits(), &NanPattern::ArithmeticNan)
+        }
+        (wasmer::Val::V128(value), AssertExpression::V128(expected)) => {
+            v128_matches(*value, expected)
+        }
+        (
+            wasmer::Val::FuncRef(function),
+            AssertExpression::RefNull(None) | AssertExpression::RefNull(Some(HeapType::Func)),
+        ) => function.is_none(),
+        (
+            wasmer::Val::ExternRef(reference),
+            AssertExpression::RefNull(None) | AssertExpression::RefNull(Some(HeapType::Extern)),
+        ) => reference.is_null(),
+        (wasmer::Val::FuncRef(function), AssertExpression::RefFunc(_)) => function.is_some(),
+        (_, AssertExpression::RefExtern(_)) => {
+            return Err(skipped("Unsupported `ref.extern` result"))
+        }
+        _ => false,
+    })
+}
+
+fn f32_matches(bits: u32, expected: &NanPattern<wast::Float32>) -> bool {
+    match expected {
+        NanPattern::CanonicalNan => bits & 0x7fff_ffff == 0x7fc0_0000,
+        NanPattern::ArithmeticNan => bits & 0x7fc0_0000 == 0x7fc0_0000,
+        NanPattern::Value(expected) => bits == expected.bits,
+    }
+}
+
+fn f64_matches(bits: u64, expected: &NanPattern<wast::Float64>) -> bool {
+    match expected {
+        NanPattern::CanonicalNan => bits & 0x7fff_ffff_ffff_ffff == 0x7ff8_0000_0000_0000,
+        NanPattern::ArithmeticNan => bits & 0x7ff8_0000_0000_0000 == 0x7ff8_0000_0000_0000,
+        NanPattern::Value(expected) => bits == expected.bits,
+    }
+}
+
+fn v128_matches(value: u128, expected: &V128Pattern) -> bool {
+    let bytes = value.to_le_bytes();
+
+    match expected {
+        V128Pattern::I8x16(lanes) => lanes
+            .iter()
+            .zip(bytes.chunks(1))
+            .all(|(lane, chunk)| lane.to_le_bytes()[..] == *chunk),
+        V128Pattern::I16x8(lanes) => lanes
+            .iter()
+            .zip(bytes.chunks(2))
+            .all(|(lane, chunk)| lane.to_le_bytes()[..] == *chunk),
+        V128Pattern::I32x4(lanes) => lanes
+            .iter()
+            .zip(bytes.chunks(4))
+            .all(|(lane, chunk)| lane.to_le_bytes()[..] == *chunk),
+        V128Pattern::I64x2(lanes) => lanes
+            .iter()
+            .zip(bytes.chunks(8))
+            .all(|(lane, chunk)| lane.to_le_bytes()[..] == *chunk),
+        V128Pattern::F32x4(lanes) => lanes
+            .iter()
+            .zip(bytes.chunks(4))
+            .all(|(lane, chunk)| f32_matches(u32::from_le_bytes(chunk.try_into().unwrap()), lane)),
+        V128Pattern::F64x2(lanes) => lanes
+            .iter()
+            .zip(bytes.chunks(8))
+            .all(|(lane, chunk)| f64_matches(u64::from_le_bytes(chunk.try_into().unwrap()), lane)),
+    }
+}
+
+fn directive_name(directive: &WastDirective) -> &'static str {
+    match directive {
+        WastDirective::Module(_) | WastDirective::QuoteModule { .. } => "module",
+        WastDirective::Register { .. } => "register",
+        WastDirective::Invoke(_) => "invoke",
+        WastDirective::AssertReturn { .. } => "assert_return",
+        WastDirective::AssertTrap { .. } => "assert_trap",
+        WastDirective::AssertExhaustion { .. } => "assert_exhaustion",
+        WastDirective::AssertInvalid { .. } => "assert_invalid",
+        WastDirective::AssertMalformed { .. } => "assert_malformed",
+        WastDirective::AssertUnlinkable { .. } => "assert_unlinkable",
+        WastDirective::AssertException { .. } => "assert_exception",
+    }
+}
+
+#[rubyfunction]
+pub fn run(source: &RString, options: Option<&Hash>) -> RubyResult<Array> {
+    options::check_keys(options, &["store"])?;
+
+    let store = options::get(options, "store")
+        .ok_or_else(|| to_ruby_err::<ArgumentError, _>("missing keyword: :store"))?
+        .try_convert_to::<RubyStore>()?;
+    let store = store.upcast();
+
+    target::check_host(store.target())?;
+
+    let source = source.to_string();
+    let (text, path) = if Path::new(&source).is_file() {
+        let text = fs::read_to_string(&source).map_err(|error| {
+            to_ruby_err::<IOError, _>(format!("Failed to read `{}`: {}", source, error))
+        })?;
+
+        (text, Some(source))
+    } else {
+        (source, None)
+    };
+
+    let buffer = ParseBuffer::new(&text)
+        .map_err(|error| wat::to_wat_error(error, &text, path.as_deref()))?;
+    let wast = parser::parse::<Wast>(&buffer)
+        .map_err(|error| wat::to_wat_error(error, &text, path.as_deref()))?;
+
+    let mut runner = Runner::new(store.inner())?;
+    let mut report = Array::with_capacity(wast.directives.len());
+
+    for directive in wast.directives {
+        let (line, column) = directive.span().linecol_in(&text);
+        let name = directive_name(&directive);
+        let (status, message) = match runner.run(directive) {
+            Ok(()) => ("passed", None),
+            Err(Failure::Failed(message)) => ("failed", Some(message)),
+            Err(Failure::Skipped(message)) => ("skipped", Some(message)),
+        };
+
+        let mut entry = Hash::new();
+        entry.store(Symbol::new("line"), Integer::from(line as u64 + 1));
+        entry.store(Symbol::new("column"), Integer::from(column as u64 + 1));
+        entry.store(Symbol::new("directive"), Symbol::new(name));
+        entry.store(Symbol::new("status"), Symbol::new(status));
+        entry.store(
+            Symbol::new("message"),
+            message.map_or_else(
+                || NilClass::new().to_any_object(),
+                |message| RString::new_utf8(&message).to_any_object(),
+            ),
+        );
+
+        report.push(entry);
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,117 @@
+use crate::{
+    error::{to_ruby_err, to_wasmer_err, RuntimeError},
+    options,
+    prelude::*,
+};
+use rutie::{AnyException, Encoding, Hash, Integer, NilClass, Object, RString};
+use std::{borrow::Cow, path::Path};
+use wast::parser::{self, ParseBuffer};
+
+/// Converts the WebAssembly text `wat` to WebAssembly bytes. The
+/// optional `path` is only used to render the errors.
+pub(crate) fn parse_wat(wat: &str, path: Option<&str>) -> Result<Vec<u8>, AnyException> {
+    let parse = || -> Result<Vec<u8>, wast::Error> {
+        let buffer = ParseBuffer::new(wat)?;
+        let mut module = parser::parse::<wast::Wat>(&buffer)?.module;
+
+        module.encode()
+    };
+
+    parse().map_err(|error| to_wat_error(error, wat, path))
+}
+
+/// Returns the WebAssembly bytes of `bytes`, which can also be
+/// WebAssembly text. The optional `path` is only used to render the
+/// errors.
+pub(crate) fn to_binary<'a>(bytes: &'a [u8], path: Option<&str>) -> RubyResult<Cow<'a, [u8]>> {
+    if bytes.starts_with(b"\0asm") {
+        return Ok(Cow::Borrowed(bytes));
+    }
+
+    let wat = std::str::from_utf8(bytes)
+        .map_err(|_| to_ruby_err::<RuntimeError, _>("Input bytes aren't valid UTF-8"))?;
+
+    parse_wat(wat, path).map(Cow::Owned)
+}
+
+/// Creates a `Wasmer::WatError` from a `wast::Error`, with the
+/// position of the error and a snippet of the source pointing at it.
+pub(crate) fn to_wat_error(mut error: wast::Error, wat: &str, path: Option<&str>) -> AnyException {
+    let (line, column) = error.span().linecol_in(wat);
+    let source_line = wat.lines().nth(line).unwrap_or("");
+    let snippet = format!(
+        "{line:4} | {source}\n     | {caret:>width$}",
+        line = line + 1,
+        source = source_line,
+        caret = "^",
+        width = column + 1,
+    );
+
+    error.set_text(wat);
+
+    if let Some(path) = path {
+        error.set_path(Path::new(path));
+    }
+
+    to_wasmer_err(
+        "Wasmer::WatError",
+        &error,
+        &[
+            ("line", Integer::from(line as u64 + 1).to_any_object()),
+            ("column", Integer::from(column as u64 + 1).to_any_object()),
+            (
+                "path",
+                path.map_or_else(
+                    || NilClass::new().to_any_object(),
+                    |path| RString::new_utf8(path).to_any_object(),
+                ),
+            ),
+            ("snippet", RString::new_utf8(&snippet).to_any_object()),
+        ],
+    )
+}
+
+#[rubyfunction]
+pub fn wat2wasm(wat: &RString, options: Option<&Hash>) -> RubyResult<RString> {
+    options::check_keys(options, &["filename"])?;
+
+    let path = options::get_string(options, "filename")?;
+
+    parse_wat(wat.to_str(), path.as_deref())
+        .map(|bytes| RString::from_bytes(bytes.as_slice(), &Encoding::us_ascii()))
+}
+
+/// Disassembles the WebAssembly `bytes`, according to the options of
+/// `wasm2wat` and `Module#to_wat`.
+pub(crate) fn print(bytes: &[u8], options: Option<&Hash>) -> RubyResult<String> {
+    options::check_keys(
+        options,
+        &["fold", "print_offsets", "skeleton", "name_unnamed"],
+    )?;
+
+    let mut config = wasmprinter::Config::new();
+    config
+        .fold_instructions(options::get_bool(options, "fold")?.unwrap_or(false))
+        .print_offsets(options::get_bool(options, "print_offsets")?.unwrap_or(false))
+        .print_skeleton(options::get_bool(options, "skeleton")?.unwrap_or(false))
+        .name_unnamed(options::get_bool(options, "name_unnamed")?.unwrap_or(false));
+
+    let mut wat = String::new();
+    config
+        .print(bytes, &mut wasmprinter::PrintFmtWrite(&mut wat))
+        .map_err(to_ruby_err::<RuntimeError, _>)?;
+
+    // Keep the output of the previous versions of `wasmprinter`,
+    // which didn't end with a new line.
+    wat.truncate(wat.trim_end_matches('\n').len());
+
+    Ok(wat)
+}
+
+#[rubyfunction]
+pub fn wasm2wat(bytes: &RString, options: Option<&Hash>) -> RubyResult<RString> {
+    Ok(RString::new_utf8(&print(
+        bytes.to_bytes_unchecked(),
+        options,
+    )?))
+}
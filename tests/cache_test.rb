@@ -0,0 +1,181 @@
+require "prelude"
+require "tmpdir"
+
+class CacheTest < Minitest::Test
+  TEST_BYTES =
+    (<<~WAST)
+    (module
+      (func (export "sum") (param i32 i32) (result i32)
+        (i32.add (local.get 0) (local.get 1))))
+    WAST
+
+  def test_load_or_compile
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory
+      store = Store.new
+
+      module_ = cache.load_or_compile store, TEST_BYTES
+      assert_equal cache.stats, {hits: 0, misses: 1, evictions: 0}
+
+      module_ = cache.load_or_compile store, TEST_BYTES
+      assert_equal cache.stats, {hits: 1, misses: 1, evictions: 0}
+
+      instance = Instance.new module_, nil
+      assert_equal instance.exports.sum.(1, 2), 3
+    end
+  end
+
+  def test_load_or_compile_invalid
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory
+
+      assert_raises(Wasmer::CompileError) {
+        cache.load_or_compile Store.new, "(module (func (result i32) (i64.const 1)))"
+      }
+    end
+  end
+
+  def test_shared_directory
+    Dir.mktmpdir do |directory|
+      Cache::FileSystem.new(directory).load_or_compile Store.new, TEST_BYTES
+
+      cache = Cache::FileSystem.new directory
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      assert_equal cache.stats[:hits], 1
+    end
+  end
+
+  def test_key_depends_on_features
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory
+
+      cache.load_or_compile Store.new, TEST_BYTES
+      cache.load_or_compile Store.new(features: Features.new(simd: false)), TEST_BYTES
+
+      assert_equal cache.stats[:misses], 2
+    end
+  end
+
+  def test_corrupted_entry
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      Dir.glob(File.join(directory, "*.wasmer-module")).each do |path|
+        IO.write path, "garbage"
+      end
+
+      module_ = cache.load_or_compile Store.new, TEST_BYTES
+
+      assert_equal cache.stats, {hits: 0, misses: 2, evictions: 0}
+      assert_equal Instance.new(module_, nil).exports.sum.(1, 2), 3
+    end
+  end
+
+  def test_key
+    Dir.mktmpdir do |directory|
+      Cache::FileSystem.new(directory, key: "secret").load_or_compile Store.new, TEST_BYTES
+
+      cache = Cache::FileSystem.new directory, key: "secret"
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      assert_equal cache.stats[:hits], 1
+
+      cache = Cache::FileSystem.new directory, key: "another secret"
+      module_ = cache.load_or_compile Store.new, TEST_BYTES
+
+      assert_equal cache.stats, {hits: 0, misses: 1, evictions: 0}
+      assert_equal Instance.new(module_, nil).exports.sum.(1, 2), 3
+
+      cache = Cache::FileSystem.new directory
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      assert_equal cache.stats[:misses], 1
+    end
+  end
+
+  def test_keep_bytes
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      module_ = cache.load_or_compile Store.new, TEST_BYTES, keep_bytes: true
+
+      assert_equal cache.stats[:hits], 1
+      assert_includes module_.to_wat, "i32.add"
+    end
+  end
+
+  def test_hit_marks_the_entry_as_recently_used
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      path = Dir.glob(File.join(directory, "*.wasmer-module")).first
+      long_ago = Time.at 0
+      File.utime long_ago, long_ago, path
+
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      assert_operator File.mtime(path), :>, long_ago
+    end
+  end
+
+  def test_orphaned_temporary_files
+    Dir.mktmpdir do |directory|
+      orphan = File.join directory, ".orphan.1.2.tmp"
+      recent = File.join directory, ".recent.1.2.tmp"
+      IO.write orphan, "partial"
+      IO.write recent, "partial"
+      File.utime Time.at(0), Time.at(0), orphan
+
+      cache = Cache::FileSystem.new directory
+
+      refute File.exist?(orphan)
+      assert File.exist?(recent)
+
+      File.utime Time.at(0), Time.at(0), recent
+      cache.clear
+
+      refute File.exist?(recent)
+    end
+  end
+
+  def test_eviction
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory, max_size: 1
+
+      cache.load_or_compile Store.new, TEST_BYTES
+      cache.load_or_compile Store.new, "(module)"
+
+      assert_equal cache.stats[:evictions], 1
+      assert_equal Dir.glob(File.join(directory, "*.wasmer-module")).length, 1
+    end
+  end
+
+  def test_size_and_clear
+    Dir.mktmpdir do |directory|
+      cache = Cache::FileSystem.new directory
+
+      assert_equal cache.size, 0
+
+      cache.load_or_compile Store.new, TEST_BYTES
+
+      assert cache.size > 0
+
+      cache.clear
+
+      assert_equal cache.size, 0
+      assert_equal cache.directory, directory
+    end
+  end
+
+  def test_invalid_max_size
+    Dir.mktmpdir do |directory|
+      assert_raises(ArgumentError) {
+        Cache::FileSystem.new directory, max_size: -1
+      }
+    end
+  end
+end
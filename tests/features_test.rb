@@ -0,0 +1,61 @@
+require "prelude"
+
+class FeaturesTest < Minitest::Test
+  def test_new
+    assert Features.new
+  end
+
+  def test_defaults
+    features = Features.new
+
+    assert_equal features.threads?, false
+    assert_equal features.reference_types?, true
+    assert_equal features.simd?, true
+    assert_equal features.bulk_memory?, true
+    assert_equal features.multi_value?, true
+    assert_equal features.tail_call?, false
+    assert_equal features.module_linking?, false
+    assert_equal features.multi_memory?, false
+    assert_equal features.memory64?, false
+  end
+
+  def test_toggles
+    features = Features.new simd: false, memory64: true
+
+    assert_equal features.simd?, false
+    assert_equal features.memory64?, true
+  end
+
+  def test_unknown_feature
+    assert_raises(ArgumentError) {
+      Features.new foo: true
+    }
+  end
+
+  def test_to_h
+    assert_equal Features.new(threads: true).to_h, {
+      threads: true,
+      reference_types: true,
+      simd: true,
+      bulk_memory: true,
+      multi_value: true,
+      tail_call: false,
+      module_linking: false,
+      multi_memory: false,
+      memory64: false,
+    }
+  end
+
+  def test_store
+    store = Store.new features: Features.new(simd: false)
+
+    assert_equal store.features.simd?, false
+    assert_equal store.threads?, false
+  end
+
+  def test_store_threads_overrides_features
+    store = Store.new features: Features.new(threads: false), threads: true
+
+    assert_equal store.features.threads?, true
+  end
+end
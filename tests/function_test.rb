@@ -123,4 +123,63 @@ class FunctionTest < Minitest::Test
 
     assert_equal error.message, "oops"
   end
+
+  def test_name
+    instance = Instance.new(
+      Module.new(
+        Store.new,
+        (<<~WAST)
+        (module
+          (func $sum (export "sum") (param i32 i32) (result i32)
+            (i32.add (local.get 0) (local.get 1)))
+          (func (export "nop")))
+        WAST
+      ),
+      nil
+    )
+
+    assert_equal instance.exports.sum.name, "sum"
+    assert_equal instance.exports.nop.name, "func[1]"
+  end
+
+  def test_name_host_function
+    function = Function.new Store.new, -> {}, FunctionType.new([], [])
+
+    assert_nil function.name
+  end
+
+  def test_inspect
+    store = Store.new
+    instance = Instance.new(
+      Module.new(store, '(module (func $sum (export "sum") (param i32 i64) (result f32) (f32.const 0)))'),
+      nil
+    )
+
+    assert_equal instance.exports.sum.inspect, "#<Wasmer::Function sum: (i32, i64) -> (f32)>"
+    assert_equal Function.new(store, -> {}, FunctionType.new([], [])).inspect, "#<Wasmer::Function () -> ()>"
+  end
+
+  def test_trap_backtrace
+    instance = Instance.new(
+      Module.new(
+        Store.new,
+        (<<~WAST)
+        (module
+          (func $fail
+            unreachable)
+          (func (export "run")
+            call $fail))
+        WAST
+      ),
+      nil
+    )
+
+    error = assert_raises(RuntimeError) {
+      instance.exports.run.()
+    }
+
+    assert_match(/unreachable/, error.message)
+    assert_match(/at fail \(/, error.message)
+    assert_match(/at func\[1\] \(/, error.message)
+  end
 end
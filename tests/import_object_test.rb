@@ -113,4 +113,47 @@ class ImportObjectTest < Minitest::Test
     instance.exports.write_g.(11)
     assert_equal global.value, 11
   end
+
+  def test_merge
+    store = Store.new
+    function = Function.new store, -> () { }, FunctionType.new([], [])
+
+    math = ImportObject.new
+    math.register "math", {:sum => function}
+
+    env = ImportObject.new
+    env.register "env", {:log => function}
+    env.register "math", {:product => function}
+
+    import_object = math.merge env
+
+    assert import_object.contains_namespace?("math")
+    assert import_object.contains_namespace?("env")
+    assert !math.contains_namespace?("env")
+
+    math.extend env
+
+    assert math.contains_namespace?("env")
+  end
+
+  def test_merge_conflict
+    store = Store.new
+    function = Function.new store, -> () { }, FunctionType.new([], [])
+
+    import_object = ImportObject.new
+    import_object.register "math", {:sum => function}
+
+    other = ImportObject.new
+    other.register "math", {:sum => function}
+
+    error = assert_raises(RuntimeError) {
+      import_object.merge other
+    }
+
+    assert_includes error.message, "`math`.`sum`"
+
+    assert_raises(RuntimeError) {
+      import_object.extend other
+    }
+  end
 end
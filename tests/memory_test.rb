@@ -149,4 +149,124 @@ class MemoryTest < Minitest::Test
     assert_equal int16[1], 0b01000000_00010000
     assert_equal int32[0], 0b01000000_00010000_00000100_00000001
   end
+
+  def shared_memory
+    Memory.new Store.new(threads: true), MemoryType.new(1, 1, true)
+  end
+
+  def test_shared_memory
+    assert_equal shared_memory.type.shared?, true
+  end
+
+  def test_shared_memory_requires_threads
+    assert_raises(RuntimeError) {
+      Memory.new Store.new, MemoryType.new(1, 1, true)
+    }
+  end
+
+  def test_shared_memory_requires_maximum
+    assert_raises(ArgumentError) {
+      Memory.new Store.new(threads: true), MemoryType.new(1, nil, true)
+    }
+  end
+
+  def test_shared_memory_declared_by_a_module_is_unsupported
+    error = assert_raises(Wasmer::CompileError) {
+      Module.new Store.new(threads: true), "(module (memory 1 1 shared))"
+    }
+
+    assert_nil error.offset
+    assert error.reason.include?("shared memories are not supported")
+  end
+
+  def test_shared_memory_imported_by_several_instances
+    store = Store.new threads: true
+    memory = Memory.new store, MemoryType.new(1, 1, true)
+    module_ = Module.new store, (<<~WAST)
+    (module
+      (import "env" "memory" (memory 1 1 shared))
+      (func (export "add") (param i32)
+        (drop (i32.atomic.rmw.add (i32.const 0) (local.get 0)))))
+    WAST
+
+    import_object = ImportObject.new
+    import_object.register "env", {:memory => memory}
+
+    threads = 4.times.map {
+      Thread.new {
+        instance = Instance.new module_, import_object
+        100.times { instance.exports.add.(1) }
+      }
+    }
+    threads.each(&:join)
+
+    assert_equal memory.atomic_load(0), 400
+  end
+
+  def test_atomic_load_store
+    memory = shared_memory
+    memory.atomic_store 4, 42
+
+    assert_equal memory.atomic_load(4), 42
+    assert_equal memory.uint32_view(0)[1], 42
+  end
+
+  def test_atomic_unaligned_or_out_of_bound
+    memory = shared_memory
+
+    assert_raises(IndexError) {
+      memory.atomic_load 1
+    }
+
+    assert_raises(IndexError) {
+      memory.atomic_store memory.data_size, 0
+    }
+  end
+
+  def test_atomic_wait_not_equal
+    assert_equal shared_memory.atomic_wait(0, 1), :not_equal
+  end
+
+  def test_atomic_wait_timed_out
+    assert_equal shared_memory.atomic_wait(0, 0, 0.01), :timed_out
+  end
+
+  def test_atomic_wait_requires_shared_memory
+    assert_raises(RuntimeError) {
+      instance.exports.memory.atomic_wait 0, 0
+    }
+  end
+
+  def test_atomic_notify_without_waiters
+    assert_equal shared_memory.atomic_notify(0), 0
+  end
+
+  def test_atomic_wait_notify_across_threads
+    memory = shared_memory
+    parking = Queue.new
+    waiters = 3.times.map {
+      Thread.new {
+        parking << true
+        memory.atomic_wait 0, 0, 5
+      }
+    }
+
+    # Let the waiters park before notifying them.
+    3.times { parking.pop }
+    sleep 0.05
+
+    woken = 0
+    deadline = Process.clock_gettime(Process::CLOCK_MONOTONIC) + 5
+
+    until woken == 3
+      if Process.clock_gettime(Process::CLOCK_MONOTONIC) > deadline
+        flunk "Only #{woken} of the 3 waiters have been woken up"
+      end
+
+      woken += memory.atomic_notify(0)
+      sleep 0.01
+    end
+
+    assert_equal waiters.map(&:value), [:ok, :ok, :ok]
+  end
 end
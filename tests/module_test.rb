@@ -1,4 +1,7 @@
 require "prelude"
+require "stringio"
+require "tempfile"
+require "tmpdir"
 
 class ModuleTest < Minitest::Test
   def bytes
@@ -17,6 +20,125 @@ class ModuleTest < Minitest::Test
     assert not(Module.validate Store.new, self.invalid_bytes)
   end
 
+  def test_validate_io
+    File.open File.expand_path("tests.wasm", File.dirname(__FILE__)), "rb" do |file|
+      assert Module.validate Store.new, file
+    end
+  end
+
+  def test_validate_not_bytes
+    assert_raises(TypeError) {
+      Module.validate Store.new, 42
+    }
+  end
+
+  def bulk_memory_bytes
+    Wasmer::wat2wasm(
+      (<<~WAST)
+      (module
+        (memory 1)
+        (func (memory.fill (i32.const 0) (i32.const 0) (i32.const 0))))
+      WAST
+    )
+  end
+
+  def test_validate_wat
+    assert Module.validate(Store.new, "(module)")
+    assert not(Module.validate(Store.new, "(module"))
+    assert not(Module.validate(Store.new, "(module (func (result i32) (i64.const 1)))"))
+    assert_nil Module.validate!(Store.new, "(module)")
+    assert_equal Module.validation_errors(Store.new, "(module)"), []
+  end
+
+  def test_validate_with_features
+    assert Module.validate Store.new, bulk_memory_bytes
+    assert Module.validate Store.new, bulk_memory_bytes, features: Features.new
+    assert not(Module.validate Store.new, bulk_memory_bytes, features: Features.new(bulk_memory: false))
+  end
+
+  def test_validate_with_store_features
+    store = Store.new features: Features.new(bulk_memory: false)
+
+    assert not(Module.validate store, bulk_memory_bytes)
+    assert_raises(RuntimeError) {
+      Module.new store, bulk_memory_bytes
+    }
+  end
+
+  def type_mismatch_bytes
+    Wasmer::wat2wasm(
+      (<<~WAST)
+      (module
+        (func (result i32) (i64.const 1))
+        (func (result i32) (i32.const 2))
+        (func (result i64) (i32.const 3)))
+      WAST
+    )
+  end
+
+  def test_validate_or_raise
+    assert_nil Module.validate!(Store.new, self.bytes)
+  end
+
+  def test_validate_or_raise_invalid
+    error = assert_raises(Wasmer::CompileError) {
+      Module.validate! Store.new, type_mismatch_bytes
+    }
+
+    assert_kind_of RuntimeError, error
+    assert_kind_of Integer, error.offset
+    assert error.reason.start_with?("type mismatch")
+    assert error.message.include?(error.reason)
+  end
+
+  def test_new_invalid
+    error = assert_raises(Wasmer::CompileError) {
+      Module.new Store.new, type_mismatch_bytes
+    }
+
+    assert_kind_of RuntimeError, error
+    assert error.message.include?("type mismatch")
+  end
+
+  def test_validate_or_raise_with_features
+    assert_raises(Wasmer::CompileError) {
+      Module.validate! Store.new, bulk_memory_bytes, features: Features.new(bulk_memory: false)
+    }
+  end
+
+  def test_validate_or_raise_missing_argument
+    error = assert_raises(ArgumentError) {
+      Module.validate! Store.new
+    }
+
+    assert_equal error.message, "Argument #1 (`AnyObject`) of method `Module.validate!` is missing"
+  end
+
+  def test_validate_or_raise_not_a_string
+    assert_raises(TypeError) {
+      Module.validate! Store.new, 42
+    }
+  end
+
+  def test_validation_errors
+    assert_equal Module.validation_errors(Store.new, self.bytes), []
+  end
+
+  def test_validation_errors_invalid
+    errors = Module.validation_errors Store.new, type_mismatch_bytes
+
+    assert_equal errors.length, 2
+    assert_equal errors.map(&:keys), [[:offset, :reason], [:offset, :reason]]
+    assert errors[0][:offset] < errors[1][:offset]
+    assert errors.all? { |error| error[:reason].start_with? "type mismatch" }
+  end
+
+  def test_validation_errors_invalid_section
+    errors = Module.validation_errors Store.new, self.invalid_bytes
+
+    assert_equal errors.length, 1
+  end
+
   def test_compile_bytes
     assert Module.new Store.new, self.bytes
   end
@@ -25,6 +147,47 @@ class ModuleTest < Minitest::Test
     assert Module.new Store.new, "(module)"
   end
 
+  def test_compile_wat_with_exports
+    module_ = Module.new Store.new, "(module (func (export \"answer\") (result i32) (i32.const 42)))"
+
+    assert_equal module_.exports[0].name, "answer"
+  end
+
+  def test_from_file_wasm
+    module_ = Module.from_file Store.new, File.expand_path("tests.wasm", File.dirname(__FILE__))
+
+    assert_kind_of Module, module_
+    assert_equal module_.exports.map(&:name), Module.new(Store.new, self.bytes).exports.map(&:name)
+  end
+
+  def test_from_file_wat
+    Tempfile.create(["module", ".wat"]) do |file|
+      file.write "(module (func (export \"answer\") (result i32) (i32.const 42)))"
+      file.close
+
+      instance = Instance.new Module.from_file(Store.new, file.path), nil
+
+      assert_equal instance.exports.answer.(), 42
+    end
+  end
+
+  def test_from_file_missing
+    error = assert_raises(IOError) {
+      Module.from_file Store.new, "/does/not/exist.wasm"
+    }
+
+    assert error.message.include?("/does/not/exist.wasm")
+  end
+
+  def test_from_file_invalid
+    path = File.expand_path("invalid.wasm", File.dirname(__FILE__))
+    error = assert_raises(RuntimeError) {
+      Module.from_file Store.new, path
+    }
+
+    assert error.message.include?(path)
+  end
+
   def test_failed_to_compile
     assert_raises(RuntimeError) {
       Module.new Store.new, self.invalid_bytes
@@ -127,6 +290,164 @@ class ModuleTest < Minitest::Test
     assert_equal imports[3].type.shared?, false
   end
 
+  def test_to_wat
+    module_ = Module.new Store.new, "(module (func (export \"answer\") (result i32) (i32.const 42)))", keep_bytes: true
+
+    assert_includes module_.to_wat, "i32.const 42"
+    assert_includes module_.to_wat(fold: true), "(i32.const 42)"
+    assert_equal Module.new(Store.new, module_.to_wat).exports.map(&:name), ["answer"]
+  end
+
+  def test_to_wat_from_bytes
+    assert_equal Module.new(Store.new, self.bytes, keep_bytes: true).to_wat, Wasmer::wasm2wat(self.bytes)
+  end
+
+  def test_to_wat_without_keep_bytes
+    assert_raises(RuntimeError) {
+      Module.new(Store.new, "(module)").to_wat
+    }
+  end
+
+  def test_new_unknown_option
+    assert_raises(ArgumentError) {
+      Module.new Store.new, "(module)", keep: true
+    }
+  end
+
+  def test_to_wat_deserialized
+    store = Store.new
+    module_ = Module.deserialize store, Module.new(store, "(module)").serialize
+
+    assert_raises(RuntimeError) {
+      module_.to_wat
+    }
+  end
+
+  def test_info
+    module_ = Module.new(
+      Store.new,
+      (<<~WAST)
+      (module
+        (import "env" "log" (func $log (param i32)))
+        (import "env" "offset" (global $offset i32))
+        (table 2 10 funcref)
+        (memory 1 2)
+        (global $counter (mut i64) (i64.const 0))
+        (elem (i32.const 1) $main)
+        (elem func $log)
+        (data (i32.const 16) "hello")
+        (data (global.get $offset) "world!")
+        (data "passive")
+        (func $main (param $input i32) (result i32)
+          (local i32 i32)
+          (local f64)
+          (local.get 0))
+        (func (export "run"))
+        (start 2))
+      WAST
+    )
+    info = module_.info
+
+    assert_equal info[:functions].length, 2
+    assert_equal info[:functions][0][:index], 1
+    assert_equal info[:functions][0][:name], "main"
+    assert_equal info[:functions][0][:type].params, [Type::I32]
+    assert_equal info[:functions][0][:type].results, [Type::I32]
+    assert_equal info[:functions][0][:locals], 3
+    assert_equal info[:functions][0][:local_names], {0 => "input"}
+    assert_equal info[:functions][1][:index], 2
+    assert_nil info[:functions][1][:name]
+    assert_equal info[:functions][1][:locals], 0
+    assert_equal info[:functions][1][:local_names], {}
+
+    assert_equal info[:start], 2
+
+    assert_equal info[:tables].length, 1
+    assert_equal info[:tables][0].type, Type::FUNC_REF
+    assert_equal info[:tables][0].minimum, 2
+    assert_equal info[:tables][0].maximum, 10
+
+    assert_equal info[:memories].length, 1
+    assert_equal info[:memories][0].minimum, 1
+    assert_equal info[:memories][0].maximum, 2
+
+    assert_equal info[:globals].length, 1
+    assert_equal info[:globals][0].type, Type::I64
+    assert_equal info[:globals][0].mutable?, true
+
+    assert_equal info[:elements], [
+      {mode: :active, table: 0, offset: 1, type: Type::FUNC_REF, size: 1},
+      {mode: :passive, table: nil, offset: nil, type: Type::FUNC_REF, size: 1},
+    ]
+
+    assert_equal info[:data], [
+      {mode: :active, memory: 0, offset: 16, size: 5},
+      {mode: :active, memory: 0, offset: nil, size: 6},
+      {mode: :passive, memory: nil, offset: nil, size: 7},
+    ]
+  end
+
+  def test_info_empty
+    info = Module.new(Store.new, "(module)").info
+
+    assert_equal info, {
+      functions: [],
+      tables: [],
+      memories: [],
+      globals: [],
+      start: nil,
+      elements: [],
+      data: [],
+    }
+  end
+
+  def test_info_read_once
+    module_ = Module.new Store.new, "(module (func $main (local i32)))", keep_bytes: true
+
+    assert_equal module_.info, module_.info
+    assert_equal module_.info[:functions][0][:name], "main"
+    assert_includes module_.to_wat, "(func $main"
+
+    module_ = Module.new Store.new, "(module (func $main (local i32)))"
+
+    assert_equal module_.info[:functions][0][:locals], 1
+    assert_equal module_.info[:functions][0][:locals], 1
+    assert_raises(RuntimeError) {
+      module_.to_wat
+    }
+  end
+
+  def test_info_deserialized
+    store = Store.new
+    module_ = Module.deserialize store, Module.new(store, "(module)").serialize
+
+    assert_raises(RuntimeError) {
+      module_.info
+    }
+  end
+
+  def test_function_names
+    module_ = Module.new(
+      Store.new,
+      (<<~WAST)
+      (module
+        (import "env" "log" (func $log (param i32)))
+        (import "env" "exit" (func))
+        (func $main)
+        (func))
+      WAST
+    )
+
+    assert_equal module_.function_names, ["log", "func[1]", "main", "func[3]"]
+  end
+
+  def test_function_names_deserialized
+    store = Store.new
+    module_ = Module.deserialize store, Module.new(store, "(module (func $main) (func))").serialize
+
+    assert_equal module_.function_names, ["main", "func[1]"]
+  end
+
   def test_custom_section
     bytes = IO.read File.expand_path("custom_sections.wasm", File.dirname(__FILE__)), mode: "rb"
     module_ = Module.new Store.new, bytes
@@ -136,6 +457,98 @@ class ModuleTest < Minitest::Test
     assert_equal module_.custom_sections("foo"), []
   end
 
+  def test_custom_section_encoding
+    bytes = IO.read File.expand_path("custom_sections.wasm", File.dirname(__FILE__)), mode: "rb"
+    module_ = Module.new Store.new, bytes
+
+    assert_equal module_.custom_sections("hello")[0].encoding, Encoding::ASCII_8BIT
+  end
+
+  def test_custom_section_names
+    bytes = IO.read File.expand_path("custom_sections.wasm", File.dirname(__FILE__)), mode: "rb"
+    module_ = Module.new Store.new, bytes
+
+    assert_equal module_.custom_section_names.sort, ["easter_egg", "hello"]
+    assert_equal Module.new(Store.new, "(module)").custom_section_names, []
+  end
+
+  def test_producers
+    module_ = Module.new(
+      Store.new,
+      '(module (@custom "producers" "\02\08language\01\04Rust\00\0cprocessed-by\01\05rustc\061.56.0"))'
+    )
+
+    assert_equal module_.producers, {
+      "language" => {"Rust" => ""},
+      "processed-by" => {"rustc" => "1.56.0"},
+    }
+  end
+
+  def test_target_features
+    module_ = Module.new(
+      Store.new,
+      '(module (@custom "target_features" "\02+\07simd128-\07atomics"))'
+    )
+
+    assert_equal module_.target_features, {"simd128" => :used, "atomics" => :disallowed}
+  end
+
+  def test_target_features_unknown_prefix
+    module_ = Module.new(
+      Store.new,
+      '(module (@custom "target_features" "\01?\07simd128"))'
+    )
+
+    error = assert_raises(RuntimeError) {
+      module_.target_features
+    }
+
+    assert_equal error.message, "Malformed `target_features` custom section: the feature `simd128` has an unknown prefix 0x3f"
+  end
+
+  def test_source_mapping_url
+    module_ = Module.new(
+      Store.new,
+      '(module (@custom "sourceMappingURL" "\0dmain.wasm.map"))'
+    )
+
+    assert_equal module_.source_mapping_url, "main.wasm.map"
+  end
+
+  def test_dylink
+    module_ = Module.new(
+      Store.new,
+      '(module (@custom "dylink.0" "\01\04\10\02\01\00\02\09\01\07libc.so\03\04\01\01f\01\04\08\01\03env\01g\00"))'
+    )
+
+    assert_equal module_.dylink, {
+      memory_size: 16,
+      memory_alignment: 2,
+      table_size: 1,
+      table_alignment: 0,
+      needed: ["libc.so"],
+      exports: {"f" => 1},
+      imports: [{module: "env", name: "g", flags: 0}],
+    }
+  end
+
+  def test_well_known_custom_sections_absent
+    module_ = Module.new Store.new, "(module)"
+
+    assert_nil module_.producers
+    assert_nil module_.target_features
+    assert_nil module_.source_mapping_url
+    assert_nil module_.dylink
+  end
+
+  def test_malformed_custom_section
+    module_ = Module.new Store.new, '(module (@custom "producers" "\05"))'
+
+    assert_raises(RuntimeError) {
+      module_.producers
+    }
+  end
+
   def test_serialize
     module_ = Module.new Store.new, "(module)"
     assert_kind_of String, module_.serialize
@@ -163,4 +576,144 @@ class ModuleTest < Minitest::Test
     assert_equal exports[0].type.params, [Type::I32, Type::I64]
     assert_equal exports[0].type.results, []
   end
+
+  def test_deserialize_with_key
+    store = Store.new
+    serialized_module = Module.new(store, "(module (func (export \"f\")))").serialize key: "secret"
+
+    module_ = Module.deserialize store, serialized_module, key: "secret"
+
+    assert_equal module_.exports[0].name, "f"
+  end
+
+  def test_deserialize_with_another_key
+    store = Store.new
+    serialized_module = Module.new(store, "(module)").serialize key: "secret"
+
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize store, serialized_module, key: "another secret"
+    }
+    assert_equal error.reason, :tampered
+  end
+
+  def test_deserialize_without_key
+    store = Store.new
+    signed_module = Module.new(store, "(module)").serialize key: "secret"
+    unsigned_module = Module.new(store, "(module)").serialize
+
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize store, signed_module
+    }
+    assert_equal error.reason, :unauthenticated
+
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize store, unsigned_module, key: "secret"
+    }
+    assert_equal error.reason, :unauthenticated
+  end
+
+  def test_deserialize_tampered
+    store = Store.new
+    serialized_module = Module.new(store, "(module)").serialize
+    serialized_module.setbyte(-40, serialized_module.getbyte(-40) ^ 1)
+
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize store, serialized_module
+    }
+    assert_equal error.reason, :tampered
+  end
+
+  def test_deserialize_malformed
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize Store.new, "\0asm\1\0\0\0"
+    }
+    assert_equal error.reason, :malformed
+
+    serialized_module = Module.new(Store.new, "(module)").serialize
+
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize Store.new, serialized_module[0, 20]
+    }
+    assert_equal error.reason, :malformed
+  end
+
+  def test_deserialize_incompatible
+    serialized_module = Module.new(Store.new, "(module)").serialize
+
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize Store.new(features: Features.new(simd: false)), serialized_module
+    }
+    assert_equal error.reason, :incompatible
+    assert_match(/features/, error.message)
+  end
+
+  def test_serialize_to_file
+    Dir.mktmpdir do |directory|
+      path = File.join directory, "module.wasmer"
+      store = Store.new
+
+      Module.new(store, "(module (func (export \"f\")))").serialize_to_file path, key: "secret"
+      module_ = Module.deserialize_from_file store, path, key: "secret"
+
+      assert_equal module_.exports[0].name, "f"
+    end
+  end
+
+  def test_deserialize_from_missing_file
+    assert_raises(IOError) {
+      Module.deserialize_from_file Store.new, "/does/not/exist.wasmer"
+    }
+  end
+
+  def test_new_frozen_string
+    module_ = Module.new Store.new, self.bytes.freeze
+
+    assert_kind_of Module, module_
+  end
+
+  def test_new_file
+    File.open File.expand_path("tests.wasm", File.dirname(__FILE__)), "rb" do |file|
+      assert_equal Module.new(Store.new, file).exports.length, Module.new(Store.new, self.bytes).exports.length
+    end
+  end
+
+  def test_new_string_io
+    module_ = Module.new Store.new, StringIO.new("(module (func (export \"f\")))")
+
+    assert_equal module_.exports[0].name, "f"
+  end
+
+  def test_new_tempfile
+    Tempfile.create ["module", ".wasm"] do |file|
+      file.binmode
+      file.write self.bytes
+      file.rewind
+
+      assert_kind_of Module, Module.new(Store.new, file)
+    end
+  end
+
+  def test_new_not_bytes
+    assert_raises(TypeError) {
+      Module.new Store.new, 42
+    }
+
+    reader = Object.new
+    def reader.read(length)
+      42
+    end
+
+    assert_raises(TypeError) {
+      Module.new Store.new, reader
+    }
+  end
+
+  def test_deserialize_io
+    store = Store.new
+    serialized_module = Module.new(store, "(module (func (export \"f\")))").serialize
+
+    module_ = Module.deserialize store, StringIO.new(serialized_module)
+
+    assert_equal module_.exports[0].name, "f"
+  end
 end
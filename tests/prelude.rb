@@ -7,8 +7,10 @@ require "minitest/reporters"
 
 Minitest::Reporters.use! Minitest::Reporters::SpecReporter.new
 
+Cache = Wasmer::Cache
 ExportType = Wasmer::ExportType
 Exports = Wasmer::Exports
+Features = Wasmer::Features
 Function = Wasmer::Function
 FunctionType = Wasmer::FunctionType
 Global = Wasmer::Global
@@ -25,9 +27,11 @@ Module = Wasmer::Module
 Store = Wasmer::Store
 Table = Wasmer::Table
 TableType = Wasmer::TableType
+Target = Wasmer::Target
 Type = Wasmer::Type
 Uint16Array = Wasmer::Uint16Array
 Uint32Array = Wasmer::Uint32Array
 Uint8Array = Wasmer::Uint8Array
 Value = Wasmer::Value
 Wasi = Wasmer::Wasi
+Wast = Wasmer::Wast
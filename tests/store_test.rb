@@ -4,4 +4,15 @@ class StoreTest < Minitest::Test
   def test_new
     assert Store.new
   end
+
+  def test_threads
+    assert_equal Store.new.threads?, false
+    assert_equal Store.new(threads: true).threads?, true
+  end
+
+  def test_unknown_option
+    assert_raises(ArgumentError) {
+      Store.new foo: true
+    }
+  end
 end
@@ -0,0 +1,201 @@
+require "prelude"
+require "weakref"
+
+class TableTest < Minitest::Test
+  TEST_BYTES =
+    (<<~WAST)
+    (module
+      (type $callback (func (result i32)))
+      (table $table (export "table") 2 10 funcref)
+      (elem (i32.const 0) $forty_two)
+      (func $forty_two (result i32)
+        (i32.const 42))
+      (func (export "call") (param i32) (result i32)
+        (call_indirect (type $callback) (local.get 0))))
+    WAST
+
+  def instance
+    Instance.new Module.new(Store.new, TEST_BYTES), nil
+  end
+
+  def test_constructor
+    table = Table.new Store.new, TableType.new(Type::FUNC_REF, 3, nil)
+
+    assert_equal table.size, 3
+    assert_equal table.to_a, [nil, nil, nil]
+  end
+
+  def test_constructor_with_initial_value
+    table = Table.new Store.new, TableType.new(Type::EXTERN_REF, 2, nil), :foo
+
+    assert_equal table.to_a, [:foo, :foo]
+  end
+
+  def test_type
+    type = instance.exports.table.type
+
+    assert_equal type.type, Type::FUNC_REF
+    assert_equal type.minimum, 2
+    assert_equal type.maximum, 10
+  end
+
+  def test_get
+    table = instance.exports.table
+
+    assert_kind_of Function, table[0]
+    assert_equal table[0].(), 42
+    assert_nil table[1]
+  end
+
+  def test_get_name
+    instance = Instance.new(
+      Module.new(
+        Store.new,
+        (<<~WAST)
+        (module
+          (table (export "table") 2 funcref)
+          (elem (i32.const 0) $sum 1)
+          (func $sum (export "sum") (param i32 i32) (result i32)
+            (i32.add (local.get 0) (local.get 1)))
+          (func))
+        WAST
+      ),
+      nil
+    )
+    table = instance.exports.table
+
+    assert_equal table[0].name, "sum"
+    assert_equal table[0].inspect, instance.exports.sum.inspect
+    assert_equal table[1].name, "func[1]"
+    assert_equal table.map(&:name), ["sum", "func[1]"]
+  end
+
+  def test_get_out_of_bound
+    table = instance.exports.table
+
+    assert_raises(IndexError) {
+      table[2]
+    }
+
+    assert_raises(IndexError) {
+      table[-1]
+    }
+  end
+
+  def test_set
+    store = Store.new
+    instance = Instance.new Module.new(store, TEST_BYTES), nil
+    table = instance.exports.table
+
+    table[1] = Function.new store, -> { 7 }, FunctionType.new([], [Type::I32])
+
+    assert_equal instance.exports.call.(1), 7
+
+    table[0] = nil
+
+    assert_nil table[0]
+  end
+
+  def test_set_type_mismatch
+    table = instance.exports.table
+
+    assert_raises(TypeError) {
+      table[0] = 42
+    }
+  end
+
+  def test_set_externref
+    table = Table.new Store.new, TableType.new(Type::EXTERN_REF, 2, nil)
+    object = Object.new
+
+    table[0] = object
+
+    assert_same table[0], object
+    assert_nil table[1]
+  end
+
+  def test_externref_survives_garbage_collection
+    table = Table.new Store.new, TableType.new(Type::EXTERN_REF, 1, nil)
+    table[0] = "hello" * 2
+
+    GC.start
+
+    assert_equal table[0], "hellohello"
+  end
+
+  def referencing_table
+    table = Table.new Store.new, TableType.new(Type::EXTERN_REF, 1, nil)
+    table[0] = [table]
+
+    WeakRef.new table
+  end
+
+  def test_table_referenced_by_its_externref_is_collected
+    tables = 10.times.map { referencing_table }
+
+    GC.start full_mark: true, immediate_sweep: true
+
+    assert tables.count(&:weakref_alive?) < tables.length
+  end
+
+  def test_grow
+    table = instance.exports.table
+
+    assert_equal table.grow(3), 2
+    assert_equal table.size, 5
+    assert_nil table[4]
+  end
+
+  def test_grow_with_initial_value
+    table = instance.exports.table
+    function = table[0]
+
+    table.grow 1, function
+
+    assert_equal table[2].(), 42
+  end
+
+  def test_grow_beyond_maximum
+    table = instance.exports.table
+
+    assert_raises(RuntimeError) {
+      table.grow 9
+    }
+  end
+
+  def test_each
+    table = instance.exports.table
+
+    assert_equal table.map(&:class), [Function, NilClass]
+  end
+
+  def test_each_returns_the_table
+    table = instance.exports.table
+
+    assert_same table.each { }, table
+  end
+
+  def test_each_without_block
+    table = instance.exports.table
+    enumerator = table.each
+
+    assert_kind_of Enumerator, enumerator
+    assert_equal enumerator.map(&:class), [Function, NilClass]
+  end
+
+  def test_copy
+    table = instance.exports.table
+
+    table.copy 1, table, 0, 1
+
+    assert_kind_of Function, table[1]
+  end
+
+  def test_copy_out_of_bound
+    table = instance.exports.table
+
+    assert_raises(IndexError) {
+      table.copy 1, table, 0, 2
+    }
+  end
+end
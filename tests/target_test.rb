@@ -0,0 +1,76 @@
+require "prelude"
+
+class TargetTest < Minitest::Test
+  FOREIGN_TRIPLE =
+    if RbConfig::CONFIG["host_cpu"] =~ /aarch64|arm64/
+      "x86_64-unknown-linux-gnu"
+    else
+      "aarch64-unknown-linux-gnu"
+    end
+
+  def test_host
+    target = Target.new
+
+    assert target.host?
+    assert_kind_of String, target.triple
+    assert_kind_of Array, target.cpu_features
+  end
+
+  def test_foreign
+    target = Target.new triple: FOREIGN_TRIPLE
+
+    assert_equal target.triple, FOREIGN_TRIPLE
+    assert_equal target.cpu_features, []
+    assert_equal target.host?, false
+    assert_equal target.to_s, "#{FOREIGN_TRIPLE} ()"
+  end
+
+  def test_cpu_features
+    target = Target.new triple: "x86_64-unknown-linux-gnu", cpu_features: ["sse2", "avx2"]
+
+    assert_equal target.cpu_features, ["sse2", "avx2"]
+  end
+
+  def test_invalid_triple
+    assert_raises(ArgumentError) {
+      Target.new triple: "foo-bar-baz-qux"
+    }
+  end
+
+  def test_unknown_cpu_feature
+    assert_raises(ArgumentError) {
+      Target.new cpu_features: ["foo"]
+    }
+  end
+
+  def test_store
+    store = Store.new target: Target.new(triple: FOREIGN_TRIPLE)
+
+    assert_equal store.target.triple, FOREIGN_TRIPLE
+    assert Store.new.target.host?
+  end
+
+  def test_cross_compilation
+    store = Store.new target: Target.new(triple: FOREIGN_TRIPLE)
+    module_ = Module.new store, "(module (func (export \"f\")))"
+
+    assert_kind_of String, module_.serialize
+
+    error = assert_raises(RuntimeError) {
+      Instance.new module_, nil
+    }
+    assert_match(/can't run on this host/, error.message)
+  end
+
+  def test_deserialize_for_another_target
+    serialized_module = Module.new(
+      Store.new(target: Target.new(triple: FOREIGN_TRIPLE)),
+      "(module)"
+    ).serialize
+
+    error = assert_raises(Wasmer::DeserializeError) {
+      Module.deserialize Store.new, serialized_module
+    }
+    assert_equal error.reason, :incompatible
+  end
+end
@@ -1,4 +1,7 @@
 require "prelude"
+require "stringio"
+require "tmpdir"
+require "weakref"
 
 class WasiTest < Minitest::Test
   def bytes
@@ -40,6 +43,107 @@ class WasiTest < Minitest::Test
     assert_kind_of Instance, instance
   end
 
+  def test_generate_import_object_without_version
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("foo").finalize
+    import_object = wasi_env.generate_import_object store
+
+    assert import_object.contains_namespace? "wasi_unstable"
+    assert import_object.contains_namespace? "wasi_snapshot_preview1"
+
+    instance = Instance.new Module.new(store, bytes), import_object
+
+    assert_kind_of Instance, instance
+  end
+
+  def test_generate_import_object_with_module
+    store = Store.new
+    module_ = Module.new store, bytes
+    wasi_env = Wasi::StateBuilder.new("foo").finalize
+    import_object = wasi_env.generate_import_object store, module_
+
+    assert import_object.contains_namespace? "wasi_snapshot_preview1"
+    refute import_object.contains_namespace? "wasi_unstable"
+
+    instance = Instance.new module_, import_object
+
+    assert_kind_of Instance, instance
+  end
+
+  def test_generate_import_object_with_module_without_wasi
+    store = Store.new
+    module_ = Module.new store, "(module)"
+    wasi_env = Wasi::StateBuilder.new("foo").finalize
+    import_object = wasi_env.generate_import_object store, module_
+
+    assert import_object.contains_namespace? "wasi_unstable"
+    assert import_object.contains_namespace? "wasi_snapshot_preview1"
+  end
+
+  def test_generate_import_object_extend
+    store = Store.new
+    logs = []
+    module_ = Module.new store, (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+      (import "env" "log" (func $log (param i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (call $log (i32.const 42))))
+    WAST
+
+    host = ImportObject.new
+    host.register "env", {:log => Function.new(store, -> (value) { logs << value; nil }, FunctionType.new([Type::I32], []))}
+
+    wasi_env = Wasi::StateBuilder.new("test-program").finalize
+    instance = Instance.new module_, wasi_env.generate_import_object(store, extend: host)
+    instance.exports._start.()
+
+    assert_equal logs, [42]
+    assert_kind_of Memory, wasi_env.memory
+
+    instance = Instance.new module_, wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1, extend: host)
+    instance.exports._start.()
+
+    assert_equal logs, [42, 42]
+
+    conflicting = ImportObject.new
+    conflicting.register "wasi_snapshot_preview1", {:proc_exit => Function.new(store, -> (code) { nil }, FunctionType.new([Type::I32], []))}
+
+    assert_raises(RuntimeError) {
+      wasi_env.generate_import_object store, extend: conflicting
+    }
+
+    assert_raises(ArgumentError) {
+      wasi_env.generate_import_object store, merge: host
+    }
+  end
+
+  def test_detect
+    store = Store.new
+    detection = Wasi::detect Module.new(store, bytes)
+
+    assert_equal detection[:version], :snapshot1
+    assert_includes detection[:functions], "fd_write"
+    assert_equal detection[:unsupported], []
+
+    detection = Wasi::detect Module.new(store, (<<~WAST))
+    (module
+      (import "wasi_unstable" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+      (import "wasi_unstable" "unknown" (func))
+      (import "env" "log" (func (param i32))))
+    WAST
+
+    assert_equal detection[:version], :snapshot0
+    assert_equal detection[:functions], ["fd_write"]
+    assert_equal detection[:unsupported].map { |import| [import.module, import.name] }, [["wasi_unstable", "unknown"]]
+
+    detection = Wasi::detect Module.new(store, "(module)")
+
+    assert_nil detection[:version]
+    assert_equal detection[:functions], []
+  end
+
   def test_wasi
     store = Store.new
     module_ = Module.new store, bytes
@@ -54,4 +158,737 @@ class WasiTest < Minitest::Test
 
     instance.exports._start.()
   end
+
+  def stderr_bytes
+    (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 16) "oops\n")
+      (func (export "_start")
+        (i32.store (i32.const 0) (i32.const 16))
+        (i32.store (i32.const 4) (i32.const 5))
+        (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 8)))))
+    WAST
+  end
+
+  def test_capture_stdout
+    store = Store.new
+    module_ = Module.new store, bytes
+    wasi_env = Wasi::StateBuilder.new("test-program")
+                 .argument("--foo")
+                 .capture_stdout
+                 .finalize
+    instance = Instance.new module_, wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start.()
+
+    stdout = wasi_env.stdout
+
+    assert_equal stdout.encoding, Encoding::ASCII_8BIT
+    assert stdout.include?("Found program name: `test-program`")
+    assert stdout.include?("Found 1 arguments: --foo")
+    assert_equal wasi_env.stdout, ""
+    assert_nil wasi_env.stderr
+  end
+
+  def test_capture_stderr
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("test-program").capture_stderr.finalize
+    instance = Instance.new Module.new(store, stderr_bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start.()
+
+    assert_equal wasi_env.stderr, "oops\n"
+    assert_nil wasi_env.stdout
+  end
+
+  def test_stream_stdout
+    chunks = []
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("test-program")
+                 .capture_stdout { |chunk| chunks << chunk }
+                 .finalize
+    instance = Instance.new Module.new(store, bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start.()
+
+    assert chunks.length > 0
+    assert chunks.join.include?("Found program name: `test-program`")
+    assert_equal wasi_env.stdout, ""
+  end
+
+  def test_stream_stdout_reads_the_environment
+    chunks = []
+    store = Store.new
+    wasi_env = nil
+    wasi_env = Wasi::StateBuilder.new("test-program")
+                 .capture_stdout { |chunk| chunks << [chunk, wasi_env.stdout] }
+                 .finalize
+    instance = Instance.new Module.new(store, bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start.()
+
+    assert chunks.map(&:first).join.include?("Found program name: `test-program`")
+    assert chunks.all? { |_, stdout| stdout == "" }
+  end
+
+  def test_stream_stdout_survives_garbage_collection
+    chunks = []
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("test-program")
+                 .capture_stdout { |chunk| chunks << chunk }
+                 .finalize
+
+    GC.start
+
+    instance = Instance.new Module.new(store, bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+    instance.exports._start.()
+
+    assert chunks.join.include?("Found program name: `test-program`")
+  end
+
+  def streaming_start(store, chunks)
+    wasi_env = Wasi::StateBuilder.new("test-program")
+                 .capture_stdout { |chunk| chunks << chunk }
+                 .finalize
+    instance = Instance.new Module.new(store, bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start
+  end
+
+  def test_stream_stdout_survives_garbage_collection_through_the_function
+    chunks = []
+    start = streaming_start Store.new, chunks
+
+    GC.start
+
+    start.()
+
+    assert chunks.join.include?("Found program name: `test-program`")
+  end
+
+  def capturing_environment
+    wasi_env = nil
+    wasi_env = Wasi::StateBuilder.new("test-program")
+                 .capture_stdout { |chunk| wasi_env.stdout }
+                 .finalize
+
+    WeakRef.new wasi_env
+  end
+
+  def test_environment_captured_by_its_block_is_collected
+    environments = 10.times.map { capturing_environment }
+
+    GC.start full_mark: true, immediate_sweep: true
+
+    assert environments.count(&:weakref_alive?) < environments.length
+  end
+
+  def cat_bytes
+    (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (loop $continue
+          (i32.store (i32.const 0) (i32.const 64))
+          (i32.store (i32.const 4) (i32.const 16))
+          (if (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8))
+            (then (unreachable)))
+          (if (i32.eqz (i32.load (i32.const 8)))
+            (then (return)))
+          (i32.store (i32.const 4) (i32.load (i32.const 8)))
+          (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 12)))
+          (br $continue))))
+    WAST
+  end
+
+  def run_cat(state_builder)
+    store = Store.new
+    wasi_env = state_builder.capture_stdout.finalize
+    instance = Instance.new Module.new(store, cat_bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start.()
+
+    wasi_env.stdout
+  end
+
+  def test_stdin_string
+    input = "Hello, World! " * 10
+
+    assert_equal run_cat(Wasi::StateBuilder.new("cat").stdin(input.freeze)), input
+  end
+
+  def test_stdin_io
+    input = "Hello, World! " * 10
+
+    assert_equal run_cat(Wasi::StateBuilder.new("cat").stdin(StringIO.new(input))), input
+  end
+
+  def test_stdin_block
+    chunks = ["Hello, ", "World!"]
+
+    assert_equal run_cat(Wasi::StateBuilder.new("cat").stdin { chunks.shift }), "Hello, World!"
+  end
+
+  def test_stdin_invalid
+    assert_raises(TypeError) {
+      Wasi::StateBuilder.new("cat").stdin 42
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("cat").stdin
+    }
+  end
+
+  def test_inherit_stdin
+    assert_kind_of Wasi::StateBuilder, Wasi::StateBuilder.new("cat").stdin("foo").inherit_stdin
+  end
+
+  def test_memfs
+    fs = Wasi::MemFS.new
+    fs.write "/in/data.csv", "a,b,c"
+    fs.mkdir "out/logs"
+
+    assert_equal fs.entries("/"), ["in", "out"]
+    assert_equal fs.read("/in/data.csv"), "a,b,c"
+    assert_equal fs.read("/in/data.csv").encoding, Encoding::ASCII_8BIT
+    assert fs.exist?("/in/data.csv")
+    assert fs.file?("/in/data.csv")
+    assert fs.directory?("/out/logs")
+    assert !fs.directory?("/in/data.csv")
+
+    fs.remove "/in/data.csv"
+
+    assert !fs.exist?("/in/data.csv")
+    assert_equal fs.entries("/in"), []
+  end
+
+  def test_memfs_errors
+    fs = Wasi::MemFS.new
+
+    error = assert_raises(IOError) {
+      fs.read "/missing.txt"
+    }
+
+    assert_match "Failed to read `/missing.txt`", error.message
+
+    assert_raises(IOError) {
+      fs.remove "/missing"
+    }
+  end
+
+  def copy_bytes
+    (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "path_open" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 100) "data.csv")
+      (data (i32.const 120) "copy.csv")
+      (func (export "_start")
+        ;; Open `data.csv` with the `fd_read` right. The fd 3 is the
+        ;; virtual root, and the fd 4 is the first preopened directory.
+        (if (call $path_open (i32.const 4) (i32.const 0) (i32.const 100) (i32.const 8) (i32.const 0) (i64.const 2) (i64.const 0) (i32.const 0) (i32.const 16))
+          (then (unreachable)))
+        ;; Create `copy.csv` with the `fd_write` right.
+        (if (call $path_open (i32.const 4) (i32.const 0) (i32.const 120) (i32.const 8) (i32.const 9) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 20))
+          (then (unreachable)))
+        (i32.store (i32.const 0) (i32.const 200))
+        (i32.store (i32.const 4) (i32.const 64))
+        (if (call $fd_read (i32.load (i32.const 16)) (i32.const 0) (i32.const 1) (i32.const 8))
+          (then (unreachable)))
+        (i32.store (i32.const 4) (i32.load (i32.const 8)))
+        (if (call $fd_write (i32.load (i32.const 20)) (i32.const 0) (i32.const 1) (i32.const 24))
+          (then (unreachable)))))
+    WAST
+  end
+
+  def test_mount
+    fs = Wasi::MemFS.new
+    fs.write "/data.csv", "a,b,c"
+
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("copy").mount("/", fs).finalize
+    instance = Instance.new Module.new(store, copy_bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start.()
+
+    assert_equal fs.entries("/"), ["copy.csv", "data.csv"]
+    assert_equal fs.read("/copy.csv"), "a,b,c"
+  end
+
+  def test_mount_exclusive
+    fs = Wasi::MemFS.new
+
+    assert_raises(RuntimeError) {
+      Wasi::StateBuilder.new("foo").map_directory("host", ".").mount("/", fs)
+    }
+
+    assert_raises(RuntimeError) {
+      Wasi::StateBuilder.new("foo").mount("/", fs).preopen_directory(".")
+    }
+
+    assert_raises(RuntimeError) {
+      Wasi::StateBuilder.new("foo").mount("/", fs).mount("/tmp", fs)
+    }
+  end
+
+  def create_bytes
+    (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "path_open" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 100) "new.txt")
+      ;; Creates `new.txt` in the first preopened directory, i.e. the
+      ;; fd 4 after the virtual root, and returns the error number.
+      (func (export "create") (result i32)
+        (call $path_open (i32.const 4) (i32.const 0) (i32.const 100) (i32.const 7) (i32.const 1) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 0))))
+    WAST
+  end
+
+  def run_create(state_builder)
+    store = Store.new
+    wasi_env = state_builder.finalize
+    instance = Instance.new Module.new(store, create_bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports.create.()
+  end
+
+  def test_preopen_directory_permissions
+    Dir.mktmpdir do |directory|
+      refute_equal run_create(Wasi::StateBuilder.new("create").preopen_directory(directory, write: false)), 0
+      refute File.exist?(File.join(directory, "new.txt"))
+
+      assert_equal run_create(Wasi::StateBuilder.new("create").preopen_directory(directory)), 0
+      assert File.exist?(File.join(directory, "new.txt"))
+    end
+  end
+
+  def test_map_directory_permissions
+    Dir.mktmpdir do |directory|
+      refute_equal run_create(Wasi::StateBuilder.new("create").map_directory("data", directory, read: true, write: false, create: false)), 0
+      refute File.exist?(File.join(directory, "new.txt"))
+    end
+  end
+
+  def test_preopen_directories_permissions
+    Dir.mktmpdir do |directory|
+      refute_equal run_create(Wasi::StateBuilder.new("create").preopen_directories([directory], write: false)), 0
+      refute File.exist?(File.join(directory, "new.txt"))
+    end
+  end
+
+  def test_map_directories_permissions
+    Dir.mktmpdir do |directory|
+      refute_equal run_create(Wasi::StateBuilder.new("create").map_directories({"data" => directory}, write: false)), 0
+      refute File.exist?(File.join(directory, "new.txt"))
+    end
+  end
+
+  def test_run_permissions
+    module_ = Module.new Store.new, (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "path_open" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 100) "new.txt")
+      (func (export "_start")
+        (call $proc_exit
+          (call $path_open (i32.const 4) (i32.const 0) (i32.const 100) (i32.const 7) (i32.const 1) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 0)))))
+    WAST
+
+    Dir.mktmpdir do |directory|
+      refute Wasi.run(module_, dirs: [directory], permissions: {write: false}).success?
+      refute File.exist?(File.join(directory, "new.txt"))
+
+      refute Wasi.run(module_, dirs: {"data" => directory}, permissions: {write: false}).success?
+      refute File.exist?(File.join(directory, "new.txt"))
+
+      assert Wasi.run(module_, dirs: [directory]).success?
+      assert File.exist?(File.join(directory, "new.txt"))
+    end
+
+    assert_raises(ArgumentError) {
+      Wasi.run module_, dirs: ["."], permissions: {read: false, write: false}
+    }
+  end
+
+  def test_invalid_permissions
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("foo").preopen_directory(".", write: false, create: true)
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("foo").map_directory("host", ".", read: false, write: false)
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("foo").preopen_directory(".", readonly: true)
+    }
+  end
+
+  def exit_bytes(code)
+    (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (call $proc_exit (i32.const #{code}))
+        (unreachable)))
+    WAST
+  end
+
+  def test_run
+    output = Wasi.run Module.new(Store.new, bytes), args: ["--foo"], env: {"ABC" => "DEF"}, dirs: {"the_host_directory" => "."}
+
+    assert_kind_of Wasi::Output, output
+    assert_equal output.exit_code, 0
+    assert output.success?
+    assert output.stdout.include?("Found 1 arguments: --foo")
+    assert output.stdout.include?("ABC")
+    assert_equal output.stdout.encoding, Encoding::ASCII_8BIT
+    assert_equal output.stderr, ""
+  end
+
+  def test_run_stdin
+    assert_equal Wasi.run(Module.new(Store.new, cat_bytes), stdin: StringIO.new("Hello, World!")).stdout, "Hello, World!"
+  end
+
+  def test_run_exit_code
+    output = Wasi.run Module.new(Store.new, exit_bytes(3))
+
+    assert_equal output.exit_code, 3
+    assert !output.success?
+
+    assert Wasi.run(Module.new(Store.new, exit_bytes(0))).success?
+  end
+
+  def test_run_invalid_options
+    assert_raises(ArgumentError) {
+      Wasi.run Module.new(Store.new, exit_bytes(0)), argv: ["--foo"]
+    }
+  end
+
+  def test_exit_error
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("exit").finalize
+    instance = Instance.new Module.new(store, exit_bytes(3)), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    error = assert_raises(Wasi::ExitError) {
+      instance.exports._start.()
+    }
+
+    assert_equal error.code, 3
+    assert_kind_of RuntimeError, error
+  end
+
+  def test_exit_success
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("exit").finalize
+    instance = Instance.new Module.new(store, exit_bytes(0)), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    assert_nil instance.exports._start.()
+  end
+
+  def test_environment_memory
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("test-program").finalize
+
+    assert_nil wasi_env.memory
+
+    instance = Instance.new Module.new(store, bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    assert_kind_of Memory, wasi_env.memory
+    assert_equal wasi_env.memory.data_size, instance.exports.memory.data_size
+  end
+
+  def test_environment_reuse
+    store = Store.new
+    module_ = Module.new store, bytes
+    wasi_env = Wasi::StateBuilder.new("test-program").capture_stdout.finalize
+    import_object = wasi_env.generate_import_object store, Wasi::Version::SNAPSHOT1
+
+    Instance.new(module_, import_object).exports._start.()
+
+    assert_raises(RuntimeError) {
+      Instance.new module_, import_object
+    }
+
+    Instance.new(module_, wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)).exports._start.()
+
+    assert_equal wasi_env.stdout.scan("Found program name").length, 2
+  end
+
+  def test_environment_reuse_after_merge
+    store = Store.new
+    module_ = Module.new store, bytes
+    wasi_env = Wasi::StateBuilder.new("test-program").finalize
+    import_object = wasi_env.generate_import_object store, Wasi::Version::SNAPSHOT1
+    merged = import_object.merge ImportObject.new
+
+    Instance.new module_, import_object
+
+    assert_raises(RuntimeError) {
+      Instance.new module_, merged
+    }
+
+    merged = ImportObject.new.merge wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+    import_object = merged.merge ImportObject.new
+
+    Instance.new module_, import_object
+
+    assert_raises(RuntimeError) {
+      Instance.new module_, merged
+    }
+  end
+
+  def test_environment_state
+    Dir.mktmpdir do |directory|
+      File.write File.join(directory, "data.csv"), "a,b,c"
+
+      wasi_env = Wasi::StateBuilder.new("test-program")
+                   .arguments(["--foo"])
+                   .environments({"ABC" => "DEF"})
+                   .map_directory("data", directory, write: false)
+                   .finalize
+
+      assert_equal wasi_env.arguments, ["test-program", "--foo"]
+      assert_equal wasi_env.environments, {"ABC" => "DEF"}
+
+      file_descriptors = wasi_env.file_descriptors
+
+      assert_equal file_descriptors.map { |fd| fd[:fd] }.first(3), [0, 1, 2]
+
+      data = file_descriptors.find { |fd| fd[:name] == "data" }
+
+      assert_equal data[:type], :directory
+      assert data[:preopened]
+      assert data[:read]
+      assert !data[:write]
+      assert !data[:create]
+
+      assert_equal wasi_env.read_file("/data/data.csv"), "a,b,c"
+      assert_raises(IOError) { wasi_env.read_file "/data/missing.csv" }
+      assert_raises(IOError) { wasi_env.read_file "/data/../secret" }
+      assert_raises(IOError) { wasi_env.read_file "/elsewhere/data.csv" }
+    end
+  end
+
+  def test_environment_read_mounted_file
+    fs = Wasi::MemFS.new
+    fs.write "/data.csv", "a,b,c"
+
+    store = Store.new
+    wasi_env = Wasi::StateBuilder.new("copy").mount("/", fs).finalize
+    instance = Instance.new Module.new(store, copy_bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)
+
+    instance.exports._start.()
+
+    assert_equal wasi_env.read_file("/copy.csv"), "a,b,c"
+  end
+
+  def entropy_bytes
+    (<<~WAST)
+    (module
+      (import "wasi_snapshot_preview1" "clock_time_get" (func $clock_time_get (param i32 i64 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "random_get" (func $random_get (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (func (export "now") (param $clock i32) (result i64)
+        (if (call $clock_time_get (local.get $clock) (i64.const 1) (i32.const 0))
+          (then (unreachable)))
+        (i64.load (i32.const 0)))
+      (func (export "random") (result i64)
+        (if (call $random_get (i32.const 8) (i32.const 8))
+          (then (unreachable)))
+        (i64.load (i32.const 8))))
+    WAST
+  end
+
+  def entropy_exports(state_builder)
+    store = Store.new
+    wasi_env = state_builder.finalize
+
+    Instance.new(Module.new(store, entropy_bytes), wasi_env.generate_import_object(store, Wasi::Version::SNAPSHOT1)).exports
+  end
+
+  def test_fixed_clock
+    time = Time.utc 2021, 11, 1
+    exports = entropy_exports Wasi::StateBuilder.new("clock").clock(fixed: time, step: 0.5)
+
+    assert_equal exports.now.(0), time.to_i * 1_000_000_000
+    assert_equal exports.now.(1), time.to_i * 1_000_000_000 + 500_000_000
+  end
+
+  def test_block_clock
+    clocks = []
+    exports = entropy_exports(Wasi::StateBuilder.new("clock").clock { |clock| clocks << clock; 42 })
+
+    assert_equal exports.now.(0), 42
+    assert_equal exports.now.(1), 42
+    assert_equal clocks, [:realtime, :monotonic]
+  end
+
+  def test_invalid_clock
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock(step: 1)
+    }
+
+    assert_raises(TypeError) {
+      Wasi::StateBuilder.new("clock").clock(fixed: "now")
+    }
+  end
+
+  def test_negative_clock
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock(fixed: 0, step: -1)
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock(fixed: 0, step: -0.5)
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock(fixed: -1)
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock(fixed: Time.at(-1))
+    }
+  end
+
+  def test_clock_overflow
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock(fixed: 0, step: 2**40)
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("clock").clock(fixed: Time.at(2**40))
+    }
+  end
+
+  def test_random_seed
+    random = -> (seed) { entropy_exports(Wasi::StateBuilder.new("random").random_seed(seed)).random.() }
+
+    assert_equal random.(42), random.(42)
+    refute_equal random.(42), random.(43)
+  end
+
+  def test_random_block
+    exports = entropy_exports(Wasi::StateBuilder.new("random").random_seed { |length| "\x01" * length })
+
+    assert_equal exports.random.(), 0x0101010101010101
+
+    exports = entropy_exports(Wasi::StateBuilder.new("random").random_seed { |length| "" })
+
+    assert_raises(RuntimeError) {
+      exports.random.()
+    }
+  end
+
+  def test_inherit_arguments
+    arguments = ARGV.dup
+    ARGV.replace ["--foo", "bar"]
+
+    wasi_env = Wasi::StateBuilder.new("test-program").inherit_arguments.finalize
+
+    assert_equal wasi_env.arguments, ["test-program", "--foo", "bar"]
+  ensure
+    ARGV.replace arguments
+  end
+
+  def test_inherit_environment
+    ENV["WASMER_RUBY_A"] = "a"
+    ENV["WASMER_RUBY_B"] = "b"
+
+    wasi_env = Wasi::StateBuilder.new("test-program")
+                 .inherit_environment(only: ["WASMER_RUBY_A", "WASMER_RUBY_B"], except: ["WASMER_RUBY_B"])
+                 .finalize
+
+    assert_equal wasi_env.environments, {"WASMER_RUBY_A" => "a"}
+
+    wasi_env = Wasi::StateBuilder.new("test-program").inherit_environment(except: ["WASMER_RUBY_B"]).finalize
+
+    assert_equal wasi_env.environments["WASMER_RUBY_A"], "a"
+    assert !wasi_env.environments.key?("WASMER_RUBY_B")
+    assert_equal wasi_env.environments.keys, wasi_env.environments.keys.sort
+  ensure
+    ENV.delete "WASMER_RUBY_A"
+    ENV.delete "WASMER_RUBY_B"
+  end
+
+  def test_invalid_environment
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("test-program").environment("A=B", "C")
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("test-program").environments({"A\0" => "B"})
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("test-program").environment("", "B")
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("test-program").argument("--foo\0")
+    }
+
+    assert_raises(ArgumentError) {
+      Wasi::StateBuilder.new("test-program").inherit_environment(exclude: ["A"])
+    }
+
+    assert_equal Wasi::StateBuilder.new("test-program").environment("A", "B=C").to_h[:environments], {"A" => "B=C"}
+  end
+
+  def test_state_builder_to_h
+    state_builder = Wasi::StateBuilder.new("test-program")
+
+    assert_equal state_builder.to_h, {
+      program_name: "test-program",
+      arguments: [],
+      environments: {},
+      directories: {},
+      mounted: false,
+      stdin: :inherit,
+      stdout: :inherit,
+      stderr: :inherit,
+      clock: nil,
+      random: nil,
+    }
+
+    Dir.mktmpdir do |directory|
+      state_builder
+        .arguments(["--foo"])
+        .environment("ABC", "DEF")
+        .map_directory("data", directory)
+        .stdin("hello")
+        .capture_stdout
+        .capture_stderr { |chunk| }
+        .clock(fixed: 0)
+        .random_seed(42)
+
+      state = state_builder.to_h
+
+      assert_equal state[:arguments], ["--foo"]
+      assert_equal state[:environments], {"ABC" => "DEF"}
+      assert_equal state[:directories], {"data" => directory}
+      assert_equal state[:stdin], :string
+      assert_equal state[:stdout], :captured
+      assert_equal state[:stderr], :streamed
+      assert_equal state[:clock], :fixed
+      assert_equal state[:random], :seeded
+
+      state_builder.finalize
+
+      assert_equal state_builder.to_h[:stdin], :inherit
+    end
+  end
 end
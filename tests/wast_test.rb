@@ -0,0 +1,110 @@
+require "prelude"
+require "tempfile"
+
+class WastTest < Minitest::Test
+  def script
+    (<<~WAST)
+    (module $math
+      (func (export "add") (param i32 i32) (result i32)
+        (i32.add (local.get 0) (local.get 1)))
+      (func (export "div") (param i32 i32) (result i32)
+        (i32.div_s (local.get 0) (local.get 1)))
+      (func $loop (export "loop")
+        (call $loop)))
+
+    (register "math" $math)
+
+    (module
+      (import "math" "add" (func $add (param i32 i32) (result i32)))
+      (import "spectest" "global_i32" (global i32))
+      (func (export "add_global") (param i32) (result i32)
+        (call $add (local.get 0) (global.get 0))))
+
+    (invoke "add_global" (i32.const 1))
+    (assert_return (invoke "add_global" (i32.const 1)) (i32.const 667))
+    (assert_return (invoke $math "add" (i32.const 1) (i32.const 2)) (i32.const 3))
+    (assert_trap (invoke $math "div" (i32.const 1) (i32.const 0)) "integer divide by zero")
+    (assert_exhaustion (invoke $math "loop") "call stack exhausted")
+    (assert_invalid (module (func (result i32) (i64.const 1))) "type mismatch")
+    (assert_malformed (module quote "(func (i32.const))") "unexpected token")
+    WAST
+  end
+
+  def test_run
+    report = Wast.run script, store: Store.new
+
+    assert_equal report.map { |entry| entry[:directive] }, [
+      :module,
+      :register,
+      :module,
+      :invoke,
+      :assert_return,
+      :assert_return,
+      :assert_trap,
+      :assert_exhaustion,
+      :assert_invalid,
+      :assert_malformed,
+    ]
+    assert report.all? { |entry| entry[:status] == :passed }, report.inspect
+    assert report.all? { |entry| entry[:message].nil? }
+    assert_equal report[0][:line], 1
+    assert_equal report[1][:line], 9
+  end
+
+  def test_run_failures
+    report = Wast.run(
+      (<<~WAST),
+      (module
+        (func (export "answer") (result i32) (i32.const 42)))
+
+      (assert_return (invoke "answer") (i32.const 41))
+      (assert_trap (invoke "answer") "unreachable")
+      (assert_return (invoke "unknown"))
+      (assert_invalid (module (func)) "type mismatch")
+      WAST
+      store: Store.new
+    )
+
+    assert_equal report.map { |entry| entry[:status] }, [:passed, :failed, :failed, :failed, :failed]
+    assert report.drop(1).all? { |entry| entry[:message].is_a? String }
+  end
+
+  def test_run_nan
+    report = Wast.run(
+      (<<~WAST),
+      (module
+        (func (export "nan") (result f32) (f32.div (f32.const 0) (f32.const 0))))
+
+      (assert_return (invoke "nan") (f32.const nan:arithmetic))
+      WAST
+      store: Store.new
+    )
+
+    assert_equal report.map { |entry| entry[:status] }, [:passed, :passed]
+  end
+
+  def test_run_file
+    Tempfile.create(["script", ".wast"]) do |file|
+      file.write script
+      file.close
+
+      report = Wast.run file.path, store: Store.new
+
+      assert_equal report.length, 10
+    end
+  end
+
+  def test_run_parse_error
+    error = assert_raises(Wasmer::WatError) {
+      Wast.run "(module)\n(assert_return (invoke \"f\") (i32.const))", store: Store.new
+    }
+
+    assert_equal error.line, 2
+  end
+
+  def test_run_missing_store
+    assert_raises(ArgumentError) {
+      Wast.run "(module)"
+    }
+  end
+end
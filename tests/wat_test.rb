@@ -5,10 +5,118 @@ class WatTest < Minitest::Test
     assert_equal Wasmer::wat2wasm("(module)"), "\x00asm\x01\x00\x00\x00"
   end
 
+  def test_wat2wasm_error
+    error = assert_raises(Wasmer::WatError) {
+      Wasmer::wat2wasm "(module\n  (func (result i32)\n    (i32.const)))"
+    }
+
+    assert_kind_of RuntimeError, error
+    assert_equal 3, error.line
+    assert_equal 15, error.column
+    assert_nil error.path
+    assert_equal "   3 |     (i32.const)))\n     |               ^", error.snippet
+    assert_includes error.message, "<anon>:3:15"
+  end
+
+  def test_wat2wasm_error_with_filename
+    error = assert_raises(Wasmer::WatError) {
+      Wasmer::wat2wasm "(module (func $f) (func $f))", filename: "fixtures/duplicate.wat"
+    }
+
+    assert_equal "fixtures/duplicate.wat", error.path
+    assert_equal 1, error.line
+    assert_includes error.message, "fixtures/duplicate.wat:1:"
+  end
+
+  def test_wat2wasm_lexer_error
+    error = assert_raises(Wasmer::WatError) {
+      Wasmer::wat2wasm "(module \"unterminated"
+    }
+
+    assert_equal 1, error.line
+    assert_operator error.column, :>, 0
+  end
+
+  def test_wat2wasm_unknown_option
+    assert_raises(ArgumentError) {
+      Wasmer::wat2wasm "(module)", file: "foo.wat"
+    }
+  end
+
   def test_wasm2wat
     assert_equal Wasmer::wasm2wat("\x00asm\x01\x00\x00\x00"), "(module)"
   end
 
+  def sum_bytes
+    Wasmer::wat2wasm(
+      (<<~WAST)
+      (module
+        (func (export "sum") (param i32 i32) (result i32)
+          local.get 0
+          local.get 1
+          i32.add))
+      WAST
+    )
+  end
+
+  def test_wasm2wat_fold
+    wat = Wasmer::wasm2wat sum_bytes, fold: true
+
+    assert_includes wat, "(i32.add\n"
+    assert_includes wat, "(local.get 0)\n"
+    assert_includes wat, "(local.get 1)))"
+    assert_equal Wasmer::wat2wasm(wat), sum_bytes
+  end
+
+  def test_wasm2wat_fold_blocks
+    wasm_bytes = Wasmer::wat2wasm(
+      (<<~WAST)
+      (module
+        (func (param i32) (result i32)
+          local.get 0
+          if (result i32)
+            i32.const 1
+          else
+            block
+              nop
+            end
+            i32.const 2
+          end))
+      WAST
+    )
+    wat = Wasmer::wasm2wat wasm_bytes, fold: true
+
+    assert_includes wat, "(then\n"
+    assert_includes wat, "(else\n"
+    assert_equal Wasmer::wat2wasm(wat), wasm_bytes
+  end
+
+  def test_wasm2wat_print_offsets
+    wat = Wasmer::wasm2wat sum_bytes, print_offsets: true
+
+    assert wat.lines.grep(/local\.get|i32\.add/).all? { |line| line.start_with? "(;@" }
+    assert_includes wat, "local.get 0"
+  end
+
+  def test_wasm2wat_skeleton
+    wat = Wasmer::wasm2wat sum_bytes, skeleton: true
+
+    assert_includes wat, "..."
+    refute_includes wat, "i32.add"
+  end
+
+  def test_wasm2wat_name_unnamed
+    wat = Wasmer::wasm2wat sum_bytes, name_unnamed: true
+
+    assert_includes wat, "$#func0"
+  end
+
+  def test_wasm2wat_unknown_option
+    assert_raises(ArgumentError) {
+      Wasmer::wasm2wat sum_bytes, folded: true
+    }
+  end
+
   def test_wat2wasm2instance
     wat =
       (<<~WAST)